@@ -15,6 +15,7 @@ INSERT INTO group_memberships (group_id, user_id, role) VALUES
      ('test_group_id1', 'test_id2', 'member'),
      ('test_group_id2', 'test_id', 'admin') ON DUPLICATE KEY UPDATE role=role;
 
--- Then insert restaurants (Explicitly setting id = 100)
-INSERT INTO restaurants (id, restaurant_code, group_id, cuisine) VALUES
-     (100, 'ARMYRA BY PAPAIOANNOU', 'test_group_id1', 'test_cuisine') ON DUPLICATE KEY UPDATE cuisine=cuisine;
+-- Then insert restaurants (Explicitly setting id = 100), adopted from the venue of the same name
+INSERT INTO restaurants (id, venue_id, restaurant_code, group_id, cuisine)
+SELECT 100, id, 'ARMYRA BY PAPAIOANNOU', 'test_group_id1', 'test_cuisine'
+FROM venues WHERE name = 'ARMYRA BY PAPAIOANNOU' ON DUPLICATE KEY UPDATE restaurant_code=restaurant_code;
@@ -0,0 +1,22 @@
+-- Restaurants around Syntagma square (37.9755, 23.7348), loaded after restaurants.sql
+INSERT INTO venues (name, cuisine) VALUES
+     ('SYNTAGMA SOUVLAKI', 'Greek'),
+     ('PLAKA TAVERNA', 'Greek'),
+     ('KOLONAKI CAFE', 'Cafe'),
+     ('PIRAEUS FISH', 'Seafood') ON DUPLICATE KEY UPDATE cuisine=cuisine;
+
+-- About 60m, 570m, 14m (archived) and 8.4km away, and one of the other group right on the square
+INSERT INTO restaurants (id, venue_id, restaurant_code, group_id, latitude, longitude, price_tier, active)
+SELECT 101, id, name, 'test_group_id1', 37.9760, 23.7350, 1, TRUE FROM venues WHERE name = 'SYNTAGMA SOUVLAKI'
+UNION ALL
+SELECT 102, id, name, 'test_group_id1', 37.9790, 23.7300, 2, TRUE FROM venues WHERE name = 'PLAKA TAVERNA'
+UNION ALL
+SELECT 103, id, name, 'test_group_id1', 37.9756, 23.7349, NULL, FALSE FROM venues WHERE name = 'KOLONAKI CAFE'
+UNION ALL
+SELECT 104, id, name, 'test_group_id1', 37.9400, 23.6500, 3, TRUE FROM venues WHERE name = 'PIRAEUS FISH'
+UNION ALL
+SELECT 105, id, name, 'test_group_id2', 37.9755, 23.7348, 1, TRUE FROM venues WHERE name = 'SYNTAGMA SOUVLAKI';
+
+INSERT INTO restaurant_tags (restaurant_id, tag) VALUES
+     (101, 'souvlaki'),
+     (102, 'meze') ON DUPLICATE KEY UPDATE tag=tag;
@@ -11,3 +11,8 @@ INSERT INTO group_memberships (group_id, user_id, role) VALUES
     ("test_group_id1", "test_id", "admin"),
     ("test_group_id1", "test_id2", "member"),
     ("test_group_id2", "test_id", "admin");
+
+INSERT INTO sessions (id, user_id) VALUES
+    ('test_id_session', 'test_id'),
+    ('test_id2_session', 'test_id2'),
+    ('test_id3_session', 'test_id3');
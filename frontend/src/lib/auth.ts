@@ -1,7 +1,9 @@
+import axios, { type AxiosError, type InternalAxiosRequestConfig } from 'axios';
 import { jwtDecode } from 'jwt-decode';
-import type { GroupMembership, User } from './models';
+import type { AuthTokens, GroupMembership, User } from './models';
 import { NOTIFICATION_TOAST_DISMISSED } from './notifications';
-import { COOKIE_DOMAIN } from './endpoints';
+import { COOKIE_DOMAIN, LOGOUT_ENDPOINT, REFRESH_ENDPOINT } from './endpoints';
+import { user } from './store';
 
 const domainAttr = COOKIE_DOMAIN ? `; domain=${COOKIE_DOMAIN}` : '';
 
@@ -18,15 +20,22 @@ function eraseCookie(name: string) {
   }
 }
 
-export function setUserCookies(token: string, color: string) {
+export function setUserCookies(token: string, color: string, refreshToken?: string) {
   setTokenCookie(token);
   setColorCookie(color);
+  if (refreshToken) {
+    setRefreshTokenCookie(refreshToken);
+  }
 }
 
 export function setTokenCookie(value: string) {
   document.cookie = `token=${encodeURIComponent(value)}; path=/${domainAttr}`;
 }
 
+export function setRefreshTokenCookie(value: string) {
+  document.cookie = `refresh_token=${encodeURIComponent(value)}; path=/${domainAttr}`;
+}
+
 export function setColorCookie(color: string) {
   document.cookie = `color=${encodeURIComponent(color)}; path=/${domainAttr}`;
 }
@@ -40,6 +49,10 @@ export function readTokenCookie(): string | null {
   return getCookieValue('token');
 }
 
+export function readRefreshTokenCookie(): string | null {
+  return getCookieValue('refresh_token');
+}
+
 export function readColorCookie(): string {
   return getCookieValue('color') || '';
 }
@@ -60,6 +73,10 @@ export function deleteTokenCookie() {
   eraseCookie('token');
 }
 
+export function deleteRefreshTokenCookie() {
+  eraseCookie('refresh_token');
+}
+
 export function deleteColorCookie() {
   eraseCookie('color');
 }
@@ -70,6 +87,7 @@ export function deleteGroupCookie() {
 
 export function deleteCookies() {
   deleteTokenCookie();
+  deleteRefreshTokenCookie();
   deleteColorCookie();
   deleteGroupCookie();
   localStorage.setItem(NOTIFICATION_TOAST_DISMISSED, 'false');
@@ -88,7 +106,6 @@ export function getUserFromToken(token: string): User | null {
       return {
         id: userId,
         username: username,
-        password: '',
         color: color,
         token: token,
         groupMembership: groupMembership
@@ -98,6 +115,96 @@ export function getUserFromToken(token: string): User | null {
     console.error('Failed to decode token:', e);
   }
 
+  // NOTE: An expired access token can still be renewed while the refresh token is around
+  if (readRefreshTokenCookie()) {
+    deleteTokenCookie();
+  } else {
+    deleteCookies();
+  }
+  return null;
+}
+
+function isTokenExpired(token: string): boolean {
+  try {
+    const decodedToken = jwtDecode(token) as { exp: number };
+    return decodedToken.exp <= Date.now() / 1000;
+  } catch (e) {
+    return true;
+  }
+}
+
+let pendingRefresh: Promise<string | null> | null = null;
+
+export function refreshSession(): Promise<string | null> {
+  // NOTE: Refresh tokens rotate on use, so concurrent callers must share a single request
+  if (!pendingRefresh) {
+    pendingRefresh = requestRefresh().finally(() => (pendingRefresh = null));
+  }
+  return pendingRefresh;
+}
+
+async function requestRefresh(): Promise<string | null> {
+  const refreshToken = readRefreshTokenCookie();
+  if (!refreshToken) return null;
+
+  try {
+    const response = await axios.post(REFRESH_ENDPOINT, { refresh_token: refreshToken });
+    const tokens: AuthTokens | null = response.data.data;
+    if (response.data.success && tokens) {
+      setTokenCookie(tokens.token);
+      setRefreshTokenCookie(tokens.refresh_token);
+      user.update((value) => (value ? { ...value, token: tokens.token } : value));
+      return tokens.token;
+    }
+  } catch (e) {
+    console.error('Failed to refresh session:', e);
+  }
+
   deleteCookies();
   return null;
 }
+
+export async function readValidTokenCookie(): Promise<string | null> {
+  const token = readTokenCookie();
+  if (token && !isTokenExpired(token)) return token;
+  return refreshSession();
+}
+
+export async function revokeSession() {
+  const refreshToken = readRefreshTokenCookie();
+  if (!refreshToken) return;
+
+  try {
+    await axios.post(LOGOUT_ENDPOINT, { refresh_token: refreshToken });
+  } catch (e) {
+    console.error('Failed to revoke session:', e);
+  }
+}
+
+let refreshInterceptorInstalled = false;
+
+export function installRefreshInterceptor() {
+  if (refreshInterceptorInstalled) return;
+  refreshInterceptorInstalled = true;
+
+  axios.interceptors.response.use(undefined, async (error: AxiosError) => {
+    const config = error.config as
+      | (InternalAxiosRequestConfig & { _retried?: boolean })
+      | undefined;
+    if (
+      error.response?.status !== 401 ||
+      !config ||
+      config._retried ||
+      !config.headers?.Authorization
+    ) {
+      throw error;
+    }
+
+    const token = await refreshSession();
+    if (!token) throw error;
+
+    config._retried = true;
+    config.headers.Authorization = 'Bearer ' + token;
+    return axios.request(config);
+  });
+}
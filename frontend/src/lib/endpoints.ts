@@ -5,7 +5,13 @@ export const VAPID_PUBLIC_KEY = env.PUBLIC_VAPID_PUBLIC_KEY;
 export const COOKIE_DOMAIN = env.PUBLIC_COOKIE_DOMAIN || '';
 
 export const LOGIN_ENDPOINT = `${API_BASE_URL}/auth/login`;
+export const LOGIN_TOTP_ENDPOINT = `${API_BASE_URL}/auth/login/totp`;
 export const REGISTER_ENDPOINT = `${API_BASE_URL}/auth/register`;
+export const REFRESH_ENDPOINT = `${API_BASE_URL}/auth/refresh`;
+export const LOGOUT_ENDPOINT = `${API_BASE_URL}/auth/logout`;
+export const LOGOUT_ALL_ENDPOINT = `${API_BASE_URL}/auth/logout_all`;
+export const PASSWORD_RESET_ENDPOINT = `${API_BASE_URL}/auth/password_reset`;
+export const PASSWORD_RESET_CONFIRM_ENDPOINT = `${API_BASE_URL}/auth/password_reset/confirm`;
 
 export const OIDC_PROVIDER_NAME = env.PUBLIC_OIDC_PROVIDER_NAME;
 export const OIDC_PROVIDER_ICON_URL: string | undefined = env.PUBLIC_OIDC_PROVIDER_ICON_URL;
@@ -19,6 +25,18 @@ export function UPDATE_USER_ENDPOINT(userId: string) {
 export function CHANGE_PASSWORD_ENDPOINT(userId: string) {
   return `${API_BASE_URL}/users/${userId}/password`;
 }
+export function UPDATE_EMAIL_ENDPOINT(userId: string) {
+  return `${API_BASE_URL}/users/${userId}/email`;
+}
+export function TOTP_ENDPOINT(userId: string) {
+  return `${API_BASE_URL}/users/${userId}/totp`;
+}
+export function VERIFY_TOTP_ENDPOINT(userId: string) {
+  return `${API_BASE_URL}/users/${userId}/totp/verify`;
+}
+export function RECOVERY_CODES_ENDPOINT(userId: string) {
+  return `${API_BASE_URL}/users/${userId}/totp/recovery_codes`;
+}
 export function DELETE_USER_ENDPOINT(userId: string) {
   return `${API_BASE_URL}/users/${userId}`;
 }
@@ -28,8 +46,17 @@ export function GET_GROUP_MEMBERSHIPS_ENDPOINT(userId: string) {
 }
 export const CREATE_GROUP_ENDPOINT = `${API_BASE_URL}/groups`;
 export const JOIN_GROUP_ENDPOINT = `${API_BASE_URL}/groups/join`;
-export function SHARE_GROUP_ENDPOINT(groupId: string) {
-  return `${window.location.origin}/groups/join/${groupId}`;
+export function GROUP_INVITES_ENDPOINT(groupId: string) {
+  return `${API_BASE_URL}/groups/${groupId}/invites`;
+}
+export function GROUP_DIMENSIONS_ENDPOINT(groupId: string) {
+  return `${API_BASE_URL}/groups/${groupId}/dimensions`;
+}
+export function GROUP_ABSENCES_ENDPOINT(groupId: string) {
+  return `${API_BASE_URL}/groups/${groupId}/absences`;
+}
+export function SHARE_GROUP_ENDPOINT(inviteCode: string) {
+  return `${window.location.origin}/groups/join/${inviteCode}`;
 }
 
 export function RESTAURANTS_ENDPOINT(groupId: string) {
@@ -42,6 +69,9 @@ export const CREATE_RESTAURANT_ENDPOINT = `${API_BASE_URL}/restaurants`;
 export function UPDATE_RESTAURANT_ENDPOINT(restaurantId: number) {
   return `${API_BASE_URL}/restaurants/${restaurantId}`;
 }
+export function RESTAURANT_ACTIVE_ENDPOINT(restaurantId: number) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/active`;
+}
 export function DELETE_RESTAURANT_ENDPOINT(restaurantId: number) {
   return `${API_BASE_URL}/restaurants/${restaurantId}`;
 }
@@ -51,6 +81,34 @@ export function RESTAURANT_RATING_COMPLETE_ENDPOINT(restaurantId: number, groupI
 export function GET_RESTAURANT_RATINGS_ENDPOINT(restaurantId: number, groupId: string) {
   return `${API_BASE_URL}/restaurants/${restaurantId}/ratings?group_id=${groupId}`;
 }
+export function RESTAURANT_COMMENTS_ENDPOINT(restaurantId: number, groupId: string) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/comments?group_id=${groupId}`;
+}
+export function RESTAURANT_COMMENT_ENDPOINT(
+  restaurantId: number,
+  commentId: number,
+  groupId: string
+) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/comments/${commentId}?group_id=${groupId}`;
+}
+export function RESTAURANT_MENU_ENDPOINT(restaurantId: number, groupId: string) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/menu?group_id=${groupId}`;
+}
+export function MENU_ITEM_ENDPOINT(restaurantId: number, menuItemId: number, groupId: string) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/menu/${menuItemId}?group_id=${groupId}`;
+}
+export function DISH_RATINGS_ENDPOINT(restaurantId: number, menuItemId: number, groupId: string) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/menu/${menuItemId}/ratings?group_id=${groupId}`;
+}
+export function BEST_DISHES_ENDPOINT(restaurantId: number, groupId: string) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/best_dishes?group_id=${groupId}`;
+}
+export function RESTAURANT_VISITS_ENDPOINT(restaurantId: number, groupId: string) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/visits?group_id=${groupId}`;
+}
+export function RESTAURANT_VISIT_ENDPOINT(restaurantId: number, visitId: number, groupId: string) {
+  return `${API_BASE_URL}/restaurants/${restaurantId}/visits/${visitId}?group_id=${groupId}`;
+}
 
 export function GET_RATING_ENDPOINT(userId: string, restaurantId: number, groupId: string) {
   return `${API_BASE_URL}/users/${userId}/ratings/${restaurantId}?group_id=${groupId}`;
@@ -64,6 +122,12 @@ export function GET_USER_OIDC_LINKS_ENDPOINT(userId: string) {
 export function UNLINK_OIDC_ENDPOINT(userId: string) {
   return `${API_BASE_URL}/users/${userId}/oidc-links/${encodeURIComponent(OIDC_PROVIDER_NAME)}`;
 }
+export function GET_SESSIONS_ENDPOINT(userId: string) {
+  return `${API_BASE_URL}/users/${userId}/sessions`;
+}
+export function REVOKE_SESSION_ENDPOINT(userId: string, sessionId: string) {
+  return `${API_BASE_URL}/users/${userId}/sessions/${sessionId}`;
+}
 export function RATE_ENDPOINT(userId: string) {
   return `${API_BASE_URL}/users/${userId}/ratings`;
 }
@@ -1,12 +1,37 @@
 export interface User {
   id: string;
   username: string;
-  password: string;
   color: string;
+  email?: string | null;
   token: string;
+  refresh_token?: string;
   groupMembership: GroupMembership | null;
 }
 
+export interface AuthTokens {
+  token: string;
+  refresh_token: string;
+}
+
+export interface Session {
+  id: string;
+  user_agent: string | null;
+  ip_address: string | null;
+  created_at: Date;
+  last_seen_at: Date;
+  current: boolean;
+}
+
+export interface TotpStatus {
+  enabled: boolean;
+  recovery_codes_left: number;
+}
+
+export interface TotpEnrollment {
+  secret: string;
+  provisioning_uri: string;
+}
+
 export interface NewPushSubscription {
   user_id: string;
   subscription_info: PushSubscription;
@@ -28,32 +53,128 @@ export interface Group {
   id: string;
   name: string;
   description: string | null;
+  period_scheme: PeriodScheme;
+  blind_ratings: boolean;
+  scale_min: number;
+  scale_max: number;
+  scale_step: number;
+  rating_quorum: number;
   created_at: Date;
   updated_at: Date;
 }
 
+export interface MemberAbsence {
+  id: number;
+  group_id: string;
+  user_id: string;
+  restaurant_id: number | null;
+  start_date: string | null;
+  end_date: string | null;
+  created_at: Date;
+}
+
 export interface GroupMembership {
   id: number;
   group_id: string;
   group: Group;
   user_id: string;
   role: Role;
+  invited_by: string | null;
   created_at: Date;
   updated_at: Date;
 }
 
+export interface GroupInvite {
+  id: number;
+  code: string;
+  group_id: string;
+  created_by: string;
+  max_uses: number | null;
+  uses: number;
+  expires_at: Date | null;
+  revoked_at: Date | null;
+  created_at: Date;
+}
+
 export interface Restaurant {
   id: number;
+  venue_id: number;
   group_id: string;
   restaurant_code: string;
   cuisine: string;
+  notes?: string | null;
+  address?: string | null;
+  latitude?: number | null;
+  longitude?: number | null;
+  price_tier?: number | null;
+  website?: string | null;
+  menu_url?: string | null;
+  phone?: string | null;
+  opening_hours?: string | null;
+  tags?: string[];
+  active?: boolean;
 }
 
-export enum Period {
-  Q1 = 0,
-  Q2 = 1,
-  Q3 = 2,
-  Q4 = 3
+export enum PeriodScheme {
+  Monthly = 'Monthly',
+  Quarterly = 'Quarterly',
+  Yearly = 'Yearly',
+  Custom = 'Custom'
+}
+
+export interface Period {
+  index: number;
+  name: string;
+  start_date: string;
+  end_date: string;
+}
+
+export interface GroupRound {
+  id: number;
+  group_id: string;
+  name: string;
+  start_date: string;
+  end_date: string;
+  created_at: Date;
+}
+
+export interface RatingDimension {
+  id: number;
+  group_id: string;
+  name: string;
+  weight: number;
+  position: number;
+  created_at: Date;
+}
+
+export interface DimensionScore {
+  dimension_id: number;
+  name: string;
+  score: number;
+}
+
+export interface NewDimensionScore {
+  dimension_id: number;
+  score: number;
+}
+
+export interface DimensionAverage {
+  dimension_id: number;
+  name: string;
+  weight: number;
+  average_score: number;
+}
+
+export interface RatingScale {
+  min: number;
+  max: number;
+  step: number;
+}
+
+export interface InvalidScore {
+  dimension_id: number | null;
+  score: number;
+  scale: RatingScale;
 }
 
 export interface Rating {
@@ -66,8 +187,11 @@ export interface Rating {
   score: number;
   created_at: Date;
   updated_at: Date;
-  period: Period;
+  period: Period | null;
   color: string | undefined;
+  review: string | null;
+  visit_id: number | null;
+  scores: Array<DimensionScore>;
 }
 
 export interface NewRating {
@@ -76,12 +200,82 @@ export interface NewRating {
   username: string;
   score: number;
   group_id: string;
+  scores?: Array<NewDimensionScore>;
+  review?: string | null;
+  visit_id?: number | null;
+}
+
+export interface Visit {
+  id: number;
+  group_id: string;
+  restaurant_id: number;
+  visit_date: string;
+  bill_total: number | null;
+  created_by: string;
+  created_at: Date;
+  attendees: Array<string>;
+  is_complete: boolean;
+}
+
+export interface NewVisit {
+  visit_date: string;
+  bill_total?: number | null;
+  attendee_ids?: Array<string>;
+}
+
+export interface MenuItem {
+  id: number;
+  restaurant_id: number;
+  name: string;
+  price: number;
+  category: string | null;
+  created_at: Date;
+  updated_at: Date;
+}
+
+export interface NewMenuItem {
+  name: string;
+  price: number;
+  category?: string | null;
+}
+
+export interface DishRating {
+  id: number;
+  menu_item_id: number;
+  user_id: string;
+  username: string;
+  color: string | undefined;
+  score: number;
+  created_at: Date;
+  updated_at: Date;
+}
+
+export interface RatedMenuItem {
+  menu_item: MenuItem;
+  average_score: number;
+  num_ratings: number;
+}
+
+export interface Comment {
+  id: number;
+  group_id: string;
+  restaurant_id: number;
+  user_id: string;
+  username: string;
+  color: string | undefined;
+  parent_id: number | null;
+  body: string;
+  created_at: Date;
+  updated_at: Date;
 }
 
 export interface RatingsByPeriod {
   current_year: number;
-  current_period: Period;
+  current_period: Period | null;
   current_period_ratings: Array<Rating>;
+  current_dimension_averages: Array<DimensionAverage>;
+  submitted_ratings: number;
+  ratings_hidden: boolean;
   historical_ratings: Array<AverageRatingPerPeriod>;
 }
 
@@ -91,4 +285,14 @@ export interface AverageRatingPerPeriod {
   year: number;
   period: Period;
   average_score: number;
+  dimension_averages: Array<DimensionAverage>;
+}
+
+export interface RatingsForPeriod {
+  period: Period;
+  average_score: number | null;
+  dimension_averages: Array<DimensionAverage>;
+  submitted_ratings: number;
+  ratings_hidden: boolean;
+  ratings: Array<Rating>;
 }
@@ -0,0 +1,11 @@
+import { error } from '@sveltejs/kit';
+import type { PageLoad } from './$types';
+
+export const load: PageLoad = async ({ params }) => {
+  const { inviteCode } = params;
+  const inviteCodeRegex = /^[0-9a-fA-F]{32}$/;
+  if (!inviteCodeRegex.test(inviteCode)) {
+    return error(500, 'Invalid invite code!');
+  }
+  return { inviteCode };
+};
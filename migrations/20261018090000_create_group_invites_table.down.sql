@@ -0,0 +1,5 @@
+ALTER TABLE group_memberships
+DROP FOREIGN KEY fk_group_memberships_invited_by,
+DROP COLUMN invited_by;
+
+DROP TABLE group_invites;
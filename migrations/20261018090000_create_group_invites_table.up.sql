@@ -0,0 +1,18 @@
+CREATE TABLE group_invites (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    code VARCHAR(64) NOT NULL,
+    group_id CHAR(36) NOT NULL,
+    created_by CHAR(36) NOT NULL,
+    max_uses INTEGER NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    expires_at DATETIME NULL,
+    revoked_at DATETIME NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY unique_invite_code (code),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE group_memberships
+ADD COLUMN invited_by CHAR(36) NULL,
+ADD CONSTRAINT fk_group_memberships_invited_by FOREIGN KEY (invited_by) REFERENCES users(id) ON DELETE SET NULL;
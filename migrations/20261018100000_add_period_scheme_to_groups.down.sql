@@ -0,0 +1,4 @@
+DROP TABLE group_rounds;
+
+ALTER TABLE groups
+DROP COLUMN period_scheme;
@@ -0,0 +1,13 @@
+ALTER TABLE groups
+ADD COLUMN period_scheme ENUM('monthly', 'quarterly', 'yearly', 'custom') NOT NULL DEFAULT 'quarterly';
+
+CREATE TABLE group_rounds (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    group_id CHAR(36) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY group_rounds_group_id_start_date (group_id, start_date),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
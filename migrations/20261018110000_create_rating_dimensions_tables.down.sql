@@ -0,0 +1,3 @@
+DROP TABLE rating_scores;
+
+DROP TABLE rating_dimensions;
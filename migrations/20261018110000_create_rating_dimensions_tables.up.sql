@@ -0,0 +1,30 @@
+CREATE TABLE rating_dimensions (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    group_id CHAR(36) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    weight FLOAT NOT NULL DEFAULT 1,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY unique_group_dimension_name (group_id, name),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
+CREATE TABLE rating_scores (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    rating_id INTEGER NOT NULL,
+    dimension_id INTEGER NOT NULL,
+    score FLOAT NOT NULL,
+    UNIQUE KEY unique_rating_dimension (rating_id, dimension_id),
+    FOREIGN KEY (rating_id) REFERENCES ratings(id) ON DELETE CASCADE,
+    FOREIGN KEY (dimension_id) REFERENCES rating_dimensions(id) ON DELETE CASCADE
+);
+
+-- Every existing group starts with a single "overall" dimension holding the existing scores
+INSERT INTO rating_dimensions (group_id, name, weight, position)
+SELECT id, 'overall', 1, 0
+FROM groups;
+
+INSERT INTO rating_scores (rating_id, dimension_id, score)
+SELECT r.id, d.id, r.score
+FROM ratings r
+JOIN rating_dimensions d ON d.group_id = r.group_id AND d.name = 'overall';
@@ -0,0 +1,4 @@
+DROP TABLE restaurant_comments;
+
+ALTER TABLE ratings
+DROP COLUMN review;
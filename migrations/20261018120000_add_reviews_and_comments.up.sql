@@ -0,0 +1,17 @@
+ALTER TABLE ratings
+ADD COLUMN review TEXT NULL;
+
+CREATE TABLE restaurant_comments (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    group_id CHAR(36) NOT NULL,
+    restaurant_id INTEGER NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    parent_id INTEGER NULL,
+    body TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id) REFERENCES restaurant_comments(id) ON DELETE CASCADE
+);
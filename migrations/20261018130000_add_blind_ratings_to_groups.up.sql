@@ -0,0 +1,2 @@
+ALTER TABLE groups
+ADD COLUMN blind_ratings BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+ALTER TABLE groups
+DROP COLUMN scale_min,
+DROP COLUMN scale_max,
+DROP COLUMN scale_step;
@@ -0,0 +1,4 @@
+ALTER TABLE groups
+ADD COLUMN scale_min FLOAT NOT NULL DEFAULT 0,
+ADD COLUMN scale_max FLOAT NOT NULL DEFAULT 10,
+ADD COLUMN scale_step FLOAT NOT NULL DEFAULT 0.5;
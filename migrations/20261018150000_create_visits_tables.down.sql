@@ -0,0 +1,8 @@
+ALTER TABLE ratings
+DROP FOREIGN KEY fk_ratings_visit,
+DROP INDEX uq_ratings_visit_user,
+DROP COLUMN visit_id;
+
+DROP TABLE visit_attendees;
+
+DROP TABLE visits;
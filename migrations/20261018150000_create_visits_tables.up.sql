@@ -0,0 +1,25 @@
+CREATE TABLE visits (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    group_id CHAR(36) NOT NULL,
+    restaurant_id INTEGER NOT NULL,
+    visit_date DATE NOT NULL,
+    bill_total DOUBLE NULL,
+    created_by CHAR(36) NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE visit_attendees (
+    visit_id INTEGER NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    PRIMARY KEY (visit_id, user_id),
+    FOREIGN KEY (visit_id) REFERENCES visits(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE ratings
+ADD COLUMN visit_id INTEGER NULL,
+ADD CONSTRAINT fk_ratings_visit FOREIGN KEY (visit_id) REFERENCES visits(id) ON DELETE SET NULL,
+ADD CONSTRAINT uq_ratings_visit_user UNIQUE (visit_id, user_id);
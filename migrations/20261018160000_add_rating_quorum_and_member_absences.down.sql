@@ -0,0 +1,4 @@
+DROP TABLE member_absences;
+
+ALTER TABLE groups
+DROP COLUMN rating_quorum;
@@ -0,0 +1,15 @@
+ALTER TABLE groups
+ADD COLUMN rating_quorum INTEGER NOT NULL DEFAULT 100;
+
+CREATE TABLE member_absences (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    group_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    restaurant_id INTEGER NULL,
+    start_date DATE NULL,
+    end_date DATE NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(id) ON DELETE CASCADE
+);
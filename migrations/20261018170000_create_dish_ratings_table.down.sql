@@ -0,0 +1,8 @@
+DROP TABLE dish_ratings;
+
+ALTER TABLE menu_items
+DROP FOREIGN KEY fk_menu_items_restaurant,
+DROP COLUMN restaurant_id,
+DROP COLUMN category,
+DROP COLUMN created_at,
+DROP COLUMN updated_at;
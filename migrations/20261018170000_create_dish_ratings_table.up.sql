@@ -0,0 +1,21 @@
+-- NOTE: Menu items were never linked to a restaurant since restaurant_menu_items was dropped
+DELETE FROM menu_items;
+
+ALTER TABLE menu_items
+ADD COLUMN restaurant_id INTEGER NOT NULL AFTER id,
+ADD COLUMN category VARCHAR(255) NULL,
+ADD COLUMN created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+ADD COLUMN updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+ADD CONSTRAINT fk_menu_items_restaurant FOREIGN KEY (restaurant_id) REFERENCES restaurants(id) ON DELETE CASCADE;
+
+CREATE TABLE dish_ratings (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    menu_item_id INTEGER NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    score FLOAT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE (menu_item_id, user_id),
+    FOREIGN KEY (menu_item_id) REFERENCES menu_items(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
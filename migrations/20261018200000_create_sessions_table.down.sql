@@ -0,0 +1,6 @@
+ALTER TABLE refresh_tokens
+DROP FOREIGN KEY fk_refresh_tokens_session,
+CHANGE COLUMN session_id family_id CHAR(36) NOT NULL,
+ADD INDEX idx_refresh_tokens_family (family_id);
+
+DROP TABLE sessions;
@@ -0,0 +1,7 @@
+CREATE TABLE signing_keys (
+    kid CHAR(36) NOT NULL PRIMARY KEY,
+    private_key TEXT NOT NULL,
+    activates_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_signing_keys_activates_at (activates_at)
+);
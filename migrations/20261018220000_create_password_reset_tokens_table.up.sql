@@ -0,0 +1,15 @@
+ALTER TABLE users
+ADD COLUMN email VARCHAR(255) NULL,
+ADD CONSTRAINT uq_users_email UNIQUE (email);
+
+CREATE TABLE password_reset_tokens (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id CHAR(36) NOT NULL,
+    token_hash CHAR(64) NOT NULL,
+    expires_at DATETIME NOT NULL,
+    used_at DATETIME NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_password_reset_tokens_hash UNIQUE (token_hash),
+    INDEX idx_password_reset_tokens_user (user_id, created_at),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+ALTER TABLE ip_blacklist MODIFY ip_address VARCHAR(15) NOT NULL;
+
+DROP TABLE IF EXISTS failed_logins;
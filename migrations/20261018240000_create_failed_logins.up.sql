@@ -0,0 +1,11 @@
+CREATE TABLE failed_logins (
+    kind ENUM('account', 'ip') NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    locked_until DATETIME NULL,
+    last_failed_at DATETIME NOT NULL,
+    PRIMARY KEY (kind, subject)
+);
+
+-- NOTE: Room for IPv6 addresses, which are blacklisted automatically as well
+ALTER TABLE ip_blacklist MODIFY ip_address VARCHAR(45) NOT NULL;
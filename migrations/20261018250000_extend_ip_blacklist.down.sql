@@ -0,0 +1,5 @@
+ALTER TABLE ip_blacklist
+    DROP COLUMN created_at,
+    DROP COLUMN expires_at,
+    DROP COLUMN reason,
+    MODIFY ip_address VARCHAR(45) NOT NULL;
@@ -0,0 +1,6 @@
+-- NOTE: Entries are single addresses or CIDR ranges of either IP version, like 2001:db8::/32
+ALTER TABLE ip_blacklist
+    MODIFY ip_address VARCHAR(49) NOT NULL,
+    ADD COLUMN reason VARCHAR(255) NULL,
+    ADD COLUMN expires_at DATETIME NULL,
+    ADD COLUMN created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP;
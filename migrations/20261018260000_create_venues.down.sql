@@ -0,0 +1,12 @@
+UPDATE restaurants r
+INNER JOIN venues v ON v.id = r.venue_id
+SET r.cuisine = COALESCE(r.cuisine, v.cuisine);
+
+ALTER TABLE restaurants
+    DROP FOREIGN KEY fk_restaurants_venue_id,
+    DROP INDEX idx_restaurants_group_venue,
+    DROP COLUMN venue_id,
+    DROP COLUMN notes,
+    MODIFY cuisine VARCHAR(255) NOT NULL;
+
+DROP TABLE venues;
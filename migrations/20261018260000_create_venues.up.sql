@@ -0,0 +1,31 @@
+-- NOTE: Venues are the catalogue every group's restaurants are adopted from. A restaurant only
+-- keeps a cuisine of its own when the group overrides the one of the venue.
+CREATE TABLE venues (
+    id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    cuisine VARCHAR(255) NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE (name)
+);
+
+-- NOTE: Groups were given copies of the same restaurants, so copies with the same name become one venue
+INSERT INTO venues (name, cuisine)
+SELECT restaurant_code, MIN(cuisine)
+FROM restaurants
+GROUP BY restaurant_code;
+
+ALTER TABLE restaurants
+    ADD COLUMN venue_id INTEGER NULL AFTER id,
+    ADD COLUMN notes TEXT NULL,
+    MODIFY cuisine VARCHAR(255) NULL;
+
+UPDATE restaurants r
+INNER JOIN venues v ON v.name = r.restaurant_code
+SET r.venue_id = v.id,
+    r.cuisine = NULLIF(r.cuisine, v.cuisine);
+
+ALTER TABLE restaurants
+    MODIFY venue_id INTEGER NOT NULL,
+    ADD CONSTRAINT fk_restaurants_venue_id FOREIGN KEY (venue_id) REFERENCES venues(id),
+    ADD UNIQUE INDEX idx_restaurants_group_venue (group_id, venue_id);
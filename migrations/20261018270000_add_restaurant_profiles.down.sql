@@ -0,0 +1,11 @@
+DROP TABLE restaurant_tags;
+
+ALTER TABLE restaurants
+    DROP COLUMN address,
+    DROP COLUMN latitude,
+    DROP COLUMN longitude,
+    DROP COLUMN price_tier,
+    DROP COLUMN website,
+    DROP COLUMN menu_url,
+    DROP COLUMN phone,
+    DROP COLUMN opening_hours;
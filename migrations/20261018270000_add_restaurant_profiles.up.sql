@@ -0,0 +1,18 @@
+-- NOTE: Profiles belong to the group's restaurant, like its name and notes
+ALTER TABLE restaurants
+    ADD COLUMN address VARCHAR(255) NULL,
+    ADD COLUMN latitude DOUBLE NULL,
+    ADD COLUMN longitude DOUBLE NULL,
+    ADD COLUMN price_tier TINYINT NULL,
+    ADD COLUMN website VARCHAR(2048) NULL,
+    ADD COLUMN menu_url VARCHAR(2048) NULL,
+    ADD COLUMN phone VARCHAR(32) NULL,
+    ADD COLUMN opening_hours TEXT NULL;
+
+CREATE TABLE restaurant_tags (
+    restaurant_id INTEGER NOT NULL,
+    tag VARCHAR(50) NOT NULL,
+    PRIMARY KEY (restaurant_id, tag),
+    INDEX idx_restaurant_tags_tag (tag),
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(id) ON DELETE CASCADE
+);
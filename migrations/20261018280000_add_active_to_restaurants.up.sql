@@ -0,0 +1,3 @@
+-- NOTE: Archived restaurants can't be rated anymore, their ratings stay for past periods
+ALTER TABLE restaurants
+    ADD COLUMN active BOOLEAN NOT NULL DEFAULT TRUE;
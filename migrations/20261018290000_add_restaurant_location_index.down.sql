@@ -0,0 +1 @@
+ALTER TABLE restaurants DROP INDEX idx_restaurants_group_location;
@@ -0,0 +1,2 @@
+-- NOTE: Nearby restaurants are looked up within a bounding box, among the group's restaurants
+ALTER TABLE restaurants ADD INDEX idx_restaurants_group_location (group_id, latitude, longitude);
@@ -0,0 +1,11 @@
+-- NOTE: Access tokens are only accepted while the session they were issued for isn't revoked
+ALTER TABLE sessions
+    ADD COLUMN revoked_at DATETIME NULL;
+
+-- Sessions without a refresh token left to use were already signed out
+UPDATE sessions s
+SET s.revoked_at = NOW()
+WHERE NOT EXISTS (
+    SELECT 1 FROM refresh_tokens rt
+    WHERE rt.session_id = s.id AND rt.revoked_at IS NULL
+);
@@ -1,20 +1,64 @@
 use std::{
-    sync::{Arc, Mutex},
+    net::IpAddr,
+    sync::{Arc, LazyLock, Mutex},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use actix_web::{rt::time::sleep, web, HttpRequest, HttpResponse};
+use actix_web::{http::header, rt::time::sleep, web, HttpRequest, HttpResponse};
 use anyhow::Result;
 use argon2::{
-    password_hash::{rand_core::OsRng, SaltString},
+    password_hash::{
+        rand_core::{OsRng, RngCore},
+        SaltString,
+    },
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
 };
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use sqlx::MySqlPool;
+use chrono::NaiveDateTime;
+use ipnet::IpNet;
+use sha2::{Digest, Sha256};
+use sqlx::{Acquire, MySqlConnection, MySqlPool};
 
-use crate::{db_util, models::UserClaims};
+use crate::{
+    client_ip::TrustedProxies,
+    db_models::NewSession,
+    db_util,
+    jwks::SigningKeys,
+    models::{ApiResponse, AuthTokens, Ip, LoginChallenge, Permission, UserClaims},
+    totp,
+};
+
+// NOTE: Access tokens are kept short-lived and renewed through refresh tokens, which are stored
+// server-side. Both stop working once their session is revoked.
+pub const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+pub const LOGIN_CHALLENGE_TTL_MINUTES: i64 = 5;
+pub const LOGIN_CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+
+const IP_BLACKLIST_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
 
-pub const JWT_SECRET: &str = "JWT_SECRET";
+// NOTE: Failed password logins are counted per account and per IP. Past a few free failures,
+// each one locks logins for twice as long as the one before, and an IP that keeps going is
+// blacklisted. Counters start over after a day without failures.
+pub const FAILED_LOGIN_WINDOW_HOURS: i64 = 24;
+pub const ACCOUNT_FREE_LOGIN_FAILURES: i32 = 5;
+pub const IP_FREE_LOGIN_FAILURES: i32 = 20;
+pub const IP_BLACKLIST_LOGIN_FAILURES: i32 = 100;
+pub const IP_BLACKLIST_LOGIN_DAYS: i64 = 7;
+const LOGIN_LOCKOUT_BASE_SECONDS: i64 = 30;
+const LOGIN_LOCKOUT_MAX_SECONDS: i64 = 3600;
+
+const FAILED_LOGIN_ACCOUNT: &str = "account";
+const FAILED_LOGIN_IP: &str = "ip";
+
+// NOTE: Compared against when a username doesn't exist, so that unknown users take as long to
+// reject as a wrong password
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    generate_password_hash(generate_opaque_token()).expect("Could not hash dummy password")
+});
+
+pub const PASSWORD_RESET_TTL_MINUTES: i64 = 30;
+pub const PASSWORD_RESETS_PER_HOUR: i64 = 3;
 
 pub fn generate_password_hash(password: String) -> Result<String, argon2::password_hash::Error> {
     let argon2 = Argon2::default();
@@ -39,28 +83,37 @@ pub fn validate_password(stored_hash: &str, password: &str) -> bool {
     }
 }
 
-pub fn generate_token(req: &HttpRequest, id: String, username: String) -> String {
-    // TODO: Add roles to user claims
+/// Checks a login password against the user's hash, or against a dummy hash if there is no user.
+pub fn validate_login_password(stored_hash: Option<&str>, password: &str) -> bool {
+    match stored_hash {
+        Some(stored_hash) => validate_password(stored_hash, password),
+        None => {
+            validate_password(&DUMMY_PASSWORD_HASH, password);
+            false
+        }
+    }
+}
+
+pub fn generate_token(req: &HttpRequest, id: String, username: String, sid: String) -> String {
     let claims = UserClaims {
         id,
         username,
-        exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
+        sid,
+        exp: (chrono::Utc::now() + chrono::Duration::minutes(ACCESS_TOKEN_TTL_MINUTES)).timestamp()
+            as usize,
     };
 
-    let secret_key = req
-        .app_data::<web::Data<String>>()
-        .expect("Missing app data: secret key")
+    let signing_keys = req
+        .app_data::<web::Data<SigningKeys>>()
+        .expect("Missing app data: signing keys")
         .as_ref();
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret_key.as_bytes()),
-    )
-    .unwrap()
+    signing_keys.read().unwrap().sign(&claims).unwrap()
 }
 
-pub fn validate_token(req: &HttpRequest) -> Result<UserClaims, HttpResponse> {
+/// Checks the signature and expiry of the request's access token, but not whether its session
+/// was revoked since. Enough to tell who is calling, not to let them in.
+pub fn verify_token(req: &HttpRequest) -> Result<UserClaims, HttpResponse> {
     let token = req
         .headers()
         .get("Authorization")
@@ -68,73 +121,436 @@ pub fn validate_token(req: &HttpRequest) -> Result<UserClaims, HttpResponse> {
         .and_then(|value| value.strip_prefix("Bearer "))
         .ok_or_else(|| HttpResponse::Unauthorized().finish())?;
 
-    let secret_key = req
-        .app_data::<web::Data<String>>()
-        .expect("Missing app data: secret key")
+    let signing_keys = req
+        .app_data::<web::Data<SigningKeys>>()
+        .expect("Missing app data: signing keys")
         .as_ref();
 
-    let validation = Validation::default();
-    let user_claims = decode::<UserClaims>(
-        token,
-        &DecodingKey::from_secret(secret_key.as_bytes()),
-        &validation,
-    )
-    .map_err(|_| HttpResponse::Unauthorized().finish())?;
+    let user_claims = signing_keys
+        .read()
+        .unwrap()
+        .verify(token)
+        .map_err(|_| HttpResponse::Unauthorized().finish())?;
 
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("Time went backwards")
         .as_secs() as usize;
-    if user_claims.claims.exp < now {
+    if user_claims.exp < now {
         return Err(HttpResponse::Unauthorized().finish());
     }
 
-    Ok(user_claims.claims)
+    Ok(user_claims)
 }
 
-pub async fn get_ip_blacklist(pool: MySqlPool) -> Result<Vec<String>> {
-    let result = db_util::get_ip_blacklist(&pool).await;
+/// Accepts the request's access token only while the session it was issued for is active, so
+/// that signing out takes effect before the token expires.
+pub async fn validate_token(req: &HttpRequest) -> Result<UserClaims, HttpResponse> {
+    let user_claims = verify_token(req)?;
+
+    let pool = req
+        .app_data::<web::Data<MySqlPool>>()
+        .expect("Missing app data: database pool");
+    let mut conn = db_util::get_connection(pool).await.ok_or_else(|| {
+        HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+            "Could not get a database connection".to_string(),
+        ))
+    })?;
 
-    match result {
-        Ok(ip_blacklist) => {
-            let ip_addresses: Vec<String> =
-                ip_blacklist.into_iter().map(|ip| ip.ip_address).collect();
-            Ok(ip_addresses)
+    match db_util::session_active(&mut conn, &user_claims.id, &user_claims.sid).await {
+        Ok(true) => Ok(user_claims),
+        Ok(false) => Err(HttpResponse::Unauthorized().finish()),
+        Err(err) => {
+            Err(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(err.to_string())))
         }
-        Err(err) => Err(err),
     }
 }
 
-type IpBlacklist = Arc<Mutex<Vec<String>>>;
+/// A random secret for refresh and password reset tokens.
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Opaque tokens are only stored as hashes, so a leaked table can't be used to sign in.
+pub fn hash_opaque_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub fn refresh_token_expiry() -> chrono::NaiveDateTime {
+    (chrono::Utc::now() + chrono::Duration::days(REFRESH_TOKEN_TTL_DAYS)).naive_utc()
+}
+
+/// The device and address a request comes from, as recorded on its session.
+pub fn session_origin(req: &HttpRequest) -> NewSession {
+    NewSession {
+        user_agent: req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+        ip_address: client_ip(req).map(|ip| ip.to_string()),
+    }
+}
+
+/// Starts a session for a fresh sign-in and returns its first pair of tokens.
+pub async fn start_session(
+    req: &HttpRequest,
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    username: &str,
+) -> Result<AuthTokens> {
+    let mut tx = conn.begin().await?;
+
+    let session_id = db_util::create_session(&mut tx, user_id, &session_origin(req)).await?;
+
+    let refresh_token = generate_opaque_token();
+    db_util::create_refresh_token(
+        &mut tx,
+        user_id,
+        &session_id,
+        &hash_opaque_token(&refresh_token),
+        refresh_token_expiry(),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(AuthTokens {
+        token: generate_token(req, user_id.to_string(), username.to_string(), session_id),
+        refresh_token,
+    })
+}
+
+/// Whether the user has confirmed a TOTP enrollment and has to pass a second factor to sign in.
+pub async fn totp_enabled(conn: &mut MySqlConnection, user_id: &str) -> Result<bool> {
+    let totp_credential = db_util::get_totp_credential(conn, user_id).await?;
+    Ok(totp_credential.is_some_and(|totp_credential| totp_credential.confirmed_at.is_some()))
+}
+
+/// Issues the short-lived token that a TOTP or recovery code is exchanged against for a session.
+pub async fn start_login_challenge(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+) -> Result<LoginChallenge> {
+    let challenge_token = generate_opaque_token();
+    let expires_at =
+        (chrono::Utc::now() + chrono::Duration::minutes(LOGIN_CHALLENGE_TTL_MINUTES)).naive_utc();
+
+    db_util::create_login_challenge(
+        conn,
+        user_id,
+        &hash_opaque_token(&challenge_token),
+        expires_at,
+    )
+    .await?;
+
+    Ok(LoginChallenge {
+        challenge_token,
+        expires_at,
+    })
+}
+
+/// Checks a second factor, which is either a current TOTP code or an unused recovery code.
+/// Either one is used up by a successful check.
+pub async fn verify_second_factor(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    code: &str,
+) -> Result<bool> {
+    let totp_credential = match db_util::get_totp_credential(conn, user_id).await? {
+        Some(totp_credential) if totp_credential.confirmed_at.is_some() => totp_credential,
+        _ => return Ok(false),
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    if let Some(step) = totp::verify_code(
+        &totp_credential.secret,
+        code,
+        now,
+        totp_credential.last_used_step,
+    ) {
+        return db_util::use_totp_step(conn, user_id, step).await;
+    }
+
+    let code_hash = hash_opaque_token(&totp::normalize_recovery_code(code));
+    db_util::use_recovery_code(conn, user_id, &code_hash).await
+}
+
+/// Checks that the caller holds the permission in the group. Roles are resolved on every
+/// request rather than stored in the token, so role changes take effect immediately.
+pub async fn authorize(
+    conn: &mut MySqlConnection,
+    user_claims: &UserClaims,
+    group_id: &str,
+    permission: Permission,
+) -> Result<(), HttpResponse> {
+    let access = db_util::get_group_access(conn, &user_claims.id, group_id)
+        .await
+        .map_err(|err| {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(err.to_string()))
+        })?;
+
+    if access.superadmin {
+        return Ok(());
+    }
+
+    match access.role {
+        Some(role) if role.has_permission(permission) => Ok(()),
+        Some(_) => Err(HttpResponse::Forbidden().json(ApiResponse::<()>::error(
+            permission.denied_message().to_string(),
+        ))),
+        None => Err(HttpResponse::Forbidden().json(ApiResponse::<()>::error(
+            "User is not a member of this group".to_string(),
+        ))),
+    }
+}
+
+pub async fn authorize_superadmin(
+    conn: &mut MySqlConnection,
+    user_claims: &UserClaims,
+) -> Result<(), HttpResponse> {
+    let is_superadmin = db_util::is_superadmin(conn, &user_claims.id)
+        .await
+        .map_err(|err| {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(err.to_string()))
+        })?;
+
+    match is_superadmin {
+        true => Ok(()),
+        false => Err(HttpResponse::Forbidden().json(ApiResponse::<()>::error(
+            "Only superadmins can do this".to_string(),
+        ))),
+    }
+}
+
+/// A blacklist entry, parsed once so every request can be matched against it cheaply.
+#[derive(Clone, Debug)]
+pub struct BlacklistEntry {
+    pub ip_address: String,
+    pub network: IpNet,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl BlacklistEntry {
+    pub fn from_ip(ip: &Ip) -> Option<Self> {
+        Some(Self {
+            ip_address: ip.ip_address.clone(),
+            network: parse_ip_network(&ip.ip_address)?,
+            expires_at: ip.expires_at,
+        })
+    }
+
+    fn blocks(&self, ip: &IpAddr, now: NaiveDateTime) -> bool {
+        self.network.contains(ip) && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+pub type IpBlacklist = Arc<Mutex<Vec<BlacklistEntry>>>;
+
+/// Parses a single address or a CIDR range. IPv4 addresses mapped into IPv6 are treated as the
+/// IPv4 addresses they are, and host bits of ranges are dropped.
+pub fn parse_ip_network(ip_address: &str) -> Option<IpNet> {
+    let ip_address = ip_address.trim();
+    match ip_address.parse::<IpAddr>() {
+        Ok(ip) => Some(IpNet::from(ip.to_canonical())),
+        Err(_) => ip_address
+            .parse::<IpNet>()
+            .ok()
+            .map(|network| network.trunc()),
+    }
+}
+
+/// The form entries are stored in: plain addresses without a prefix, ranges as CIDR.
+pub fn normalize_ip_network(ip_address: &str) -> Option<String> {
+    let network = parse_ip_network(ip_address)?;
+    match network.prefix_len() == network.max_prefix_len() {
+        true => Some(network.addr().to_string()),
+        false => Some(network.to_string()),
+    }
+}
+
+pub async fn get_ip_blacklist(pool: MySqlPool) -> Result<Vec<BlacklistEntry>> {
+    let ip_blacklist = db_util::get_ip_blacklist(&pool).await?;
+
+    Ok(ip_blacklist
+        .iter()
+        .filter_map(|ip| {
+            let entry = BlacklistEntry::from_ip(ip);
+            if entry.is_none() {
+                eprintln!("ERROR: failed parsing blacklisted ip {}", ip.ip_address);
+            }
+            entry
+        })
+        .collect())
+}
 
 pub async fn update_blacklist(db_pool: MySqlPool, blacklist: IpBlacklist) {
     loop {
-        println!("INFO:[{}]: Updating IP blacklist...", chrono::Utc::now());
+        if let Err(err) = db_util::delete_expired_ips(&db_pool).await {
+            eprintln!("ERROR: failed deleting expired ips: {err}");
+        }
 
-        let updated_blacklist = get_ip_blacklist(db_pool.clone()).await.unwrap_or_default();
-        *blacklist.lock().unwrap() = updated_blacklist;
+        // NOTE: Changes made through this instance apply right away, this picks up the ones made
+        // through other instances
+        match get_ip_blacklist(db_pool.clone()).await {
+            Ok(updated_blacklist) => *blacklist.lock().unwrap() = updated_blacklist,
+            Err(err) => eprintln!("ERROR: failed updating ip blacklist: {err}"),
+        }
 
-        // NOTE: Update ip blacklist every 60 minutes
-        sleep(Duration::from_secs(3600)).await;
+        sleep(IP_BLACKLIST_REFRESH_INTERVAL).await;
     }
 }
 
+fn ip_blacklist(req: &HttpRequest) -> &IpBlacklist {
+    req.app_data::<web::Data<IpBlacklist>>()
+        .expect("Missing app data: ip blacklist")
+        .as_ref()
+}
+
+pub fn add_to_blacklist(req: &HttpRequest, ips: &[Ip]) {
+    ip_blacklist(req)
+        .lock()
+        .unwrap()
+        .extend(ips.iter().filter_map(BlacklistEntry::from_ip));
+}
+
+pub fn remove_from_blacklist(req: &HttpRequest, ip_address: &str) {
+    ip_blacklist(req)
+        .lock()
+        .unwrap()
+        .retain(|entry| entry.ip_address != ip_address);
+}
+
+/// The IP the request was made from, taken from the forwarding headers only if the peer is one
+/// of the trusted proxies.
+pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let peer = req.peer_addr()?.ip();
+    Some(match req.app_data::<web::Data<TrustedProxies>>() {
+        Some(trusted_proxies) => trusted_proxies.resolve(peer, req.headers()),
+        None => peer.to_canonical(),
+    })
+}
+
 pub fn validate_ip(req: &HttpRequest) -> Result<(), HttpResponse> {
-    let connection_info = req.connection_info();
-    let ip = connection_info
-        .realip_remote_addr()
-        .ok_or_else(|| HttpResponse::Unauthorized().finish())?;
+    let ip = client_ip(req).ok_or_else(|| HttpResponse::Unauthorized().finish())?;
 
-    let ip_blacklist = req
-        .app_data::<web::Data<IpBlacklist>>()
-        .expect("Missing app data: ip blacklist")
-        .as_ref();
-    let ip_blacklist = ip_blacklist.lock().unwrap();
+    let now = chrono::Utc::now().naive_utc();
+    let ip_blacklist = ip_blacklist(req).lock().unwrap();
 
-    if ip_blacklist.contains(&ip.to_string()) {
+    if ip_blacklist.iter().any(|entry| entry.blocks(&ip, now)) {
         println!("INFO: Blocked ip: {ip}");
         return Err(HttpResponse::Unauthorized().finish());
     }
 
     Ok(())
 }
+
+// NOTE: Usernames are matched case-insensitively, so their lockouts are too
+fn account_subject(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
+fn lockout_duration(failures: i32, free_failures: i32) -> Option<chrono::Duration> {
+    let excess = failures - free_failures;
+    if excess <= 0 {
+        return None;
+    }
+
+    let seconds = LOGIN_LOCKOUT_BASE_SECONDS
+        .saturating_mul(1 << (excess - 1).min(32))
+        .min(LOGIN_LOCKOUT_MAX_SECONDS);
+    Some(chrono::Duration::seconds(seconds))
+}
+
+/// How many seconds are left until the account or the IP may try to log in again, if either is
+/// locked out.
+pub async fn login_lockout(
+    conn: &mut MySqlConnection,
+    username: &str,
+    ip: Option<&str>,
+) -> Result<Option<i64>> {
+    let now = chrono::Utc::now().naive_utc();
+
+    let mut locked_until =
+        db_util::get_login_lockout(conn, FAILED_LOGIN_ACCOUNT, &account_subject(username)).await?;
+    if let Some(ip) = ip {
+        let ip_locked_until = db_util::get_login_lockout(conn, FAILED_LOGIN_IP, ip).await?;
+        locked_until = locked_until.max(ip_locked_until);
+    }
+
+    Ok(locked_until
+        .filter(|locked_until| *locked_until > now)
+        .map(|locked_until| (locked_until - now).num_seconds().max(1)))
+}
+
+pub fn login_locked_out(retry_after: i64) -> HttpResponse {
+    HttpResponse::TooManyRequests()
+        .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+        .json(ApiResponse::<()>::error(
+            "Too many failed login attempts, try again later".to_string(),
+        ))
+}
+
+/// Counts a failed login against the account, if given, and against the client's IP, locks
+/// either one out once it is past its free failures, and blacklists the IP once it crosses
+/// `IP_BLACKLIST_LOGIN_FAILURES`.
+pub async fn record_failed_login(
+    req: &HttpRequest,
+    conn: &mut MySqlConnection,
+    username: Option<&str>,
+) -> Result<()> {
+    let now = chrono::Utc::now().naive_utc();
+    let window_start = now - chrono::Duration::hours(FAILED_LOGIN_WINDOW_HOURS);
+
+    if let Some(username) = username {
+        let subject = account_subject(username);
+        let failures =
+            db_util::record_failed_login(conn, FAILED_LOGIN_ACCOUNT, &subject, window_start)
+                .await?;
+        if let Some(duration) = lockout_duration(failures, ACCOUNT_FREE_LOGIN_FAILURES) {
+            db_util::lock_login(conn, FAILED_LOGIN_ACCOUNT, &subject, now + duration).await?;
+        }
+    }
+
+    let Some(ip) = client_ip(req).map(|ip| ip.to_string()) else {
+        return Ok(());
+    };
+    let failures = db_util::record_failed_login(conn, FAILED_LOGIN_IP, &ip, window_start).await?;
+    if let Some(duration) = lockout_duration(failures, IP_FREE_LOGIN_FAILURES) {
+        db_util::lock_login(conn, FAILED_LOGIN_IP, &ip, now + duration).await?;
+    }
+
+    if failures >= IP_BLACKLIST_LOGIN_FAILURES {
+        println!(
+            "INFO:[{}]: Blacklisting {ip} after {failures} failed logins",
+            chrono::Utc::now()
+        );
+
+        let entry = Ip {
+            ip_address: normalize_ip_network(&ip).unwrap_or_else(|| ip.clone()),
+            reason: Some(format!("{failures} failed logins")),
+            expires_at: Some(now + chrono::Duration::days(IP_BLACKLIST_LOGIN_DAYS)),
+        };
+        let blacklisted = ip_blacklist(req)
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|blacklisted| blacklisted.ip_address == entry.ip_address);
+        if !blacklisted {
+            db_util::create_ip_blacklist(conn, std::slice::from_ref(&entry)).await?;
+            add_to_blacklist(req, &[entry]);
+        }
+        db_util::clear_failed_logins(conn, FAILED_LOGIN_IP, &ip).await?;
+    }
+
+    Ok(())
+}
+
+/// Resets the account's failure counter after a successful login. The IP's counter is left
+/// alone, so that one valid account doesn't hide the failures of others behind the same IP.
+pub async fn clear_failed_logins(conn: &mut MySqlConnection, username: &str) -> Result<()> {
+    db_util::clear_failed_logins(conn, FAILED_LOGIN_ACCOUNT, &account_subject(username)).await?;
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use actix_web::http::header::{HeaderMap, FORWARDED, X_FORWARDED_FOR};
+use anyhow::{anyhow, Result};
+use ipnet::IpNet;
+
+/// The header our proxies write the forwarding chain to. Only that one is read, since a client
+/// can send the other one and the proxies would pass it on untouched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForwardingHeader {
+    #[default]
+    XForwardedFor,
+    Forwarded,
+}
+
+impl FromStr for ForwardingHeader {
+    type Err = anyhow::Error;
+
+    fn from_str(header: &str) -> Result<Self> {
+        match header.trim().to_ascii_lowercase().as_str() {
+            "x-forwarded-for" => Ok(Self::XForwardedFor),
+            "forwarded" => Ok(Self::Forwarded),
+            _ => Err(anyhow!(
+                "Invalid trusted proxy header, expected x-forwarded-for or forwarded: {header}"
+            )),
+        }
+    }
+}
+
+/// The reverse proxies in front of us. Only they are believed about who a request is for, any
+/// other peer is the client itself, whatever its forwarding headers claim.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+    header: ForwardingHeader,
+}
+
+impl TrustedProxies {
+    /// Parses a comma separated list of addresses and CIDR ranges, which forward requests in
+    /// `header`.
+    pub fn parse(trusted_proxies: &str, header: ForwardingHeader) -> Result<Self> {
+        let networks = trusted_proxies
+            .split(',')
+            .map(str::trim)
+            .filter(|network| !network.is_empty())
+            .map(|network| {
+                network
+                    .parse::<IpAddr>()
+                    .map(IpNet::from)
+                    .or_else(|_| network.parse::<IpNet>())
+                    .map_err(|_| anyhow!("Invalid trusted proxy: {network}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { networks, header })
+    }
+
+    fn trusts(&self, ip: &IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    /// The client a request from `peer` was made by. Forwarding headers are read from the nearest
+    /// hop backwards, and the first address that isn't a trusted proxy is the client. Anything
+    /// further back was written by the client itself, so it isn't looked at.
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let mut client = peer.to_canonical();
+        if !self.trusts(&client) {
+            return client;
+        }
+
+        for hop in forwarded_for(self.header, headers).into_iter().rev() {
+            // NOTE: A hop that isn't an address, like "unknown", ends the chain we can follow
+            let Some(hop) = hop else {
+                break;
+            };
+            client = hop;
+            if !self.trusts(&client) {
+                break;
+            }
+        }
+
+        client
+    }
+}
+
+/// The addresses a request was forwarded for in `header`, from the original client to the
+/// nearest proxy.
+fn forwarded_for(header: ForwardingHeader, headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    match header {
+        ForwardingHeader::Forwarded => headers
+            .get_all(FORWARDED)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (name, value) = pair.trim().split_once('=')?;
+                    name.eq_ignore_ascii_case("for").then(|| parse_node(value))
+                })
+            })
+            .collect(),
+        ForwardingHeader::XForwardedFor => headers
+            .get_all(X_FORWARDED_FOR)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(parse_node)
+            .collect(),
+    }
+}
+
+/// Parses a node like `192.0.2.1`, `192.0.2.1:4711`, `"[2001:db8::1]:4711"` or `2001:db8::1`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    let ip = match node.strip_prefix('[') {
+        Some(bracketed) => bracketed.split_once(']')?.0.parse().ok()?,
+        None => node
+            .parse::<IpAddr>()
+            .or_else(|_| node.parse::<SocketAddr>().map(|addr| addr.ip()))
+            .ok()?,
+    };
+
+    Some(ip.to_canonical())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name.parse().unwrap(), HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_untrusted_peer() {
+        let trusted_proxies =
+            TrustedProxies::parse("10.0.0.0/8", ForwardingHeader::XForwardedFor).unwrap();
+        let headers = headers(&[("x-forwarded-for", "198.51.100.1")]);
+
+        let client = trusted_proxies.resolve("203.0.113.7".parse().unwrap(), &headers);
+        assert_eq!(client, "203.0.113.7".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_trusted_proxy_chain() {
+        let trusted_proxies =
+            TrustedProxies::parse("10.0.0.0/8, 2001:db8::1", ForwardingHeader::XForwardedFor)
+                .unwrap();
+        let headers = headers(&[
+            ("x-forwarded-for", "192.0.2.66, 198.51.100.1"),
+            ("x-forwarded-for", "10.1.1.1"),
+        ]);
+
+        // NOTE: 192.0.2.66 was made up by the client and is never reached
+        let client = trusted_proxies.resolve("10.0.0.1".parse().unwrap(), &headers);
+        assert_eq!(client, "198.51.100.1".parse::<IpAddr>().unwrap());
+
+        let client = trusted_proxies.resolve("::ffff:10.0.0.1".parse().unwrap(), &headers);
+        assert_eq!(client, "198.51.100.1".parse::<IpAddr>().unwrap());
+
+        let client = trusted_proxies.resolve("2001:db8::1".parse().unwrap(), &HeaderMap::new());
+        assert_eq!(client, "2001:db8::1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let trusted_proxies =
+            TrustedProxies::parse("10.0.0.1", ForwardingHeader::Forwarded).unwrap();
+        let headers = headers(&[
+            (
+                "forwarded",
+                r#"for="[2001:db8::7]:4711";proto=https, for=10.0.0.1"#,
+            ),
+            ("x-forwarded-for", "198.51.100.1"),
+        ]);
+
+        let client = trusted_proxies.resolve("10.0.0.1".parse().unwrap(), &headers);
+        assert_eq!(client, "2001:db8::7".parse::<IpAddr>().unwrap());
+
+        let headers = self::headers(&[("x-forwarded-for", "198.51.100.1")]);
+        let client = trusted_proxies.resolve("10.0.0.1".parse().unwrap(), &headers);
+        assert_eq!(client, "10.0.0.1".parse::<IpAddr>().unwrap());
+
+        let headers = self::headers(&[("forwarded", "for=unknown")]);
+        let client = trusted_proxies.resolve("10.0.0.1".parse().unwrap(), &headers);
+        assert_eq!(client, "10.0.0.1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_spoofed_forwarded_header() {
+        let trusted_proxies =
+            TrustedProxies::parse("10.0.0.0/8", ForwardingHeader::XForwardedFor).unwrap();
+        let headers = headers(&[
+            ("forwarded", "for=192.0.2.66"),
+            ("x-forwarded-for", "198.51.100.1, 10.1.1.1"),
+        ]);
+
+        // NOTE: The proxies write X-Forwarded-For, the Forwarded header came from the client
+        let client = trusted_proxies.resolve("10.0.0.1".parse().unwrap(), &headers);
+        assert_eq!(client, "198.51.100.1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_trusted_proxies() {
+        let header = ForwardingHeader::default();
+        assert!(TrustedProxies::parse("", header)
+            .unwrap()
+            .networks
+            .is_empty());
+        assert!(TrustedProxies::parse("10.0.0.0/8, proxy.local", header).is_err());
+
+        assert_eq!(
+            "X-Forwarded-For".parse::<ForwardingHeader>().unwrap(),
+            ForwardingHeader::XForwardedFor
+        );
+        assert_eq!(
+            "forwarded".parse::<ForwardingHeader>().unwrap(),
+            ForwardingHeader::Forwarded
+        );
+        assert!("x-real-ip".parse::<ForwardingHeader>().is_err());
+    }
+}
@@ -1,27 +1,43 @@
 use std::env;
 
+use crate::{
+    client_ip::ForwardingHeader,
+    rate_limit::{RateLimit, RateLimits},
+};
+
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     pub database_url: String,
-    pub jwt_secret: String,
+    pub jwt_key_rotation_days: i64,
     pub vapid_public_key: String,
     pub vapid_private_key: String,
     pub public_api_base_url: String,
     pub frontend_base_url: String,
     pub cookie_domain: Option<String>,
+    pub trusted_proxies: String,
+    pub trusted_proxy_header: ForwardingHeader,
+    pub rate_limits: RateLimits,
+
+    pub notifier: String,
+    pub smtp_relay: String,
+    pub smtp_from: Option<String>,
 
     pub oidc_provider_name: String,
     pub oidc_client_id: String,
     pub oidc_client_secret: String,
     pub oidc_issuer_url: String,
     pub oidc_redirect_url: String,
+    pub oidc_enforces_mfa: bool,
 }
 
 impl AppConfig {
     pub fn load() -> anyhow::Result<Self> {
         Ok(Self {
             database_url: env::var("DATABASE_URL")?,
-            jwt_secret: env::var("JWT_SECRET")?,
+            jwt_key_rotation_days: env::var("JWT_KEY_ROTATION_DAYS")
+                .ok()
+                .and_then(|days| days.parse().ok())
+                .unwrap_or(30),
             vapid_public_key: env::var("PUBLIC_VAPID_PUBLIC_KEY")?,
             vapid_private_key: env::var("VAPID_PRIVATE_KEY")?,
             public_api_base_url: env::var("PUBLIC_API_BASE_URL")?,
@@ -29,12 +45,47 @@ impl AppConfig {
             cookie_domain: env::var("PUBLIC_COOKIE_DOMAIN")
                 .ok()
                 .filter(|s| !s.is_empty()),
+            trusted_proxies: env::var("TRUSTED_PROXIES").unwrap_or_default(),
+            trusted_proxy_header: match env::var("TRUSTED_PROXY_HEADER")
+                .ok()
+                .filter(|s| !s.is_empty())
+            {
+                Some(header) => header.parse()?,
+                None => ForwardingHeader::default(),
+            },
+            rate_limits: load_rate_limits()?,
+
+            notifier: env::var("NOTIFIER").unwrap_or_else(|_| "push".to_string()),
+            smtp_relay: env::var("SMTP_RELAY").unwrap_or_else(|_| "localhost:25".to_string()),
+            smtp_from: env::var("SMTP_FROM").ok().filter(|s| !s.is_empty()),
 
             oidc_provider_name: env::var("PUBLIC_OIDC_PROVIDER_NAME")?,
             oidc_client_id: env::var("OIDC_CLIENT_ID")?,
             oidc_client_secret: env::var("OIDC_CLIENT_SECRET")?,
             oidc_issuer_url: env::var("OIDC_ISSUER_URL")?,
             oidc_redirect_url: env::var("OIDC_REDIRECT_URL")?,
+            oidc_enforces_mfa: env::var("OIDC_ENFORCES_MFA")
+                .map(|enforces_mfa| enforces_mfa == "true")
+                .unwrap_or(false),
         })
     }
 }
+
+/// Each limit can be overridden like `RATE_LIMIT_LOGIN=10/300`, ten requests per five minutes.
+fn load_rate_limits() -> anyhow::Result<RateLimits> {
+    let defaults = RateLimits::default();
+    let rate_limit = |name: &str, default: RateLimit| -> anyhow::Result<RateLimit> {
+        match env::var(name).ok().filter(|s| !s.is_empty()) {
+            Some(rate_limit) => rate_limit.parse(),
+            None => Ok(default),
+        }
+    };
+
+    Ok(RateLimits {
+        anonymous: rate_limit("RATE_LIMIT_ANONYMOUS", defaults.anonymous)?,
+        user: rate_limit("RATE_LIMIT_USER", defaults.user)?,
+        login: rate_limit("RATE_LIMIT_LOGIN", defaults.login)?,
+        register: rate_limit("RATE_LIMIT_REGISTER", defaults.register)?,
+        password: rate_limit("RATE_LIMIT_PASSWORD", defaults.password)?,
+    })
+}
@@ -1,7 +1,7 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
-use crate::models::Role;
+use crate::models::{PeriodScheme, Role};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DbUser {
@@ -9,6 +9,7 @@ pub struct DbUser {
     pub username: String,
     pub password: String,
     pub color: String,
+    pub email: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -17,6 +18,13 @@ pub struct NewUser {
     pub username: String,
     pub password: String,
     pub color: String,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateEmail {
+    pub email: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -25,6 +33,81 @@ pub struct ChangePassword {
     pub new_password: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct PasswordResetRequest {
+    pub username: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PasswordResetConfirm {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug)]
+pub struct DbRefreshToken {
+    pub id: i32,
+    pub user_id: String,
+    pub username: String,
+    pub session_id: String,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Default, Debug)]
+pub struct NewSession {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct DbSession {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TotpCode {
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TotpLogin {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+#[derive(Debug)]
+pub struct DbTotpCredential {
+    pub secret: String,
+    pub confirmed_at: Option<NaiveDateTime>,
+    pub last_used_step: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct DbLoginChallenge {
+    pub id: i32,
+    pub user_id: String,
+    pub username: String,
+    pub attempts: i32,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct DbSigningKey {
+    pub kid: String,
+    pub private_key: String,
+    pub activates_at: NaiveDateTime,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DbGroup {
     pub id: String,
@@ -41,12 +124,19 @@ pub struct NewGroup {
     pub creator_id: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct UpdateGroup {
+    pub name: String,
+    pub description: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DbGroupMembership {
     pub id: i32,
     pub group_id: String,
     pub user_id: String,
     pub role: Role,
+    pub invited_by: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -56,14 +146,78 @@ pub struct NewGroupMembership {
     pub group_id: String,
     pub user_id: String,
     pub role: Role,
+    pub invited_by: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateGroupPeriodScheme {
+    pub period_scheme: PeriodScheme,
+}
+
+#[derive(Default, Debug)]
+pub struct DbGroupAccess {
+    pub superadmin: bool,
+    pub role: Option<Role>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateSuperadmin {
+    pub superadmin: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateGroupBlindRatings {
+    pub blind_ratings: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateGroupRatingQuorum {
+    pub rating_quorum: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewGroupRound {
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateGroupMemberRole {
+    pub role: Role,
+}
+
+/// Invites expire within a year at most
+pub const MAX_INVITE_EXPIRY_HOURS: i64 = 24 * 365;
+
+#[derive(Serialize, Deserialize)]
+pub struct NewGroupInvite {
+    pub max_uses: Option<i32>,
+    pub expires_in_hours: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JoinGroup {
+    pub code: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DbRestaurant {
     pub id: i32,
+    pub venue_id: i32,
     pub restaurant_code: String,
     pub group_id: String,
     pub cuisine: String,
+    pub notes: Option<String>,
+    pub address: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub price_tier: Option<i8>,
+    pub website: Option<String>,
+    pub menu_url: Option<String>,
+    pub phone: Option<String>,
+    pub opening_hours: Option<String>,
+    pub active: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -73,6 +227,33 @@ pub struct NewRestaurant {
     pub cuisine: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct UpdateRestaurantActive {
+    pub group_id: String,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewVenue {
+    pub name: String,
+    pub cuisine: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdoptVenue {
+    pub group_id: String,
+    /// Overrides the cuisine of the venue for the group
+    pub cuisine: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportRestaurants {
+    pub source_group_id: String,
+    /// The restaurants of the source group to import, all of them if missing
+    pub restaurant_ids: Option<Vec<i32>>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DbRating {
     pub id: i32,
@@ -85,6 +266,8 @@ pub struct DbRating {
     pub updated_at: NaiveDateTime,
     pub group_id: String,
     pub color: Option<String>,
+    pub review: Option<String>,
+    pub visit_id: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -94,6 +277,144 @@ pub struct NewRating {
     pub username: String,
     pub score: f32,
     pub group_id: String,
+    #[serde(default)]
+    pub scores: Vec<NewDimensionScore>,
+    #[serde(default)]
+    pub review: Option<String>,
+    #[serde(default)]
+    pub visit_id: Option<i32>,
+}
+
+impl NewRating {
+    /// Blank reviews are stored as no review at all.
+    pub fn review(&self) -> Option<&str> {
+        self.review
+            .as_deref()
+            .map(str::trim)
+            .filter(|review| !review.is_empty())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DbVisit {
+    pub id: i32,
+    pub group_id: String,
+    pub restaurant_id: i32,
+    pub visit_date: NaiveDate,
+    pub bill_total: Option<f64>,
+    pub created_by: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewVisit {
+    pub visit_date: NaiveDate,
+    #[serde(default)]
+    pub bill_total: Option<f64>,
+    #[serde(default)]
+    pub attendee_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewMemberAbsence {
+    pub user_id: String,
+    #[serde(default)]
+    pub restaurant_id: Option<i32>,
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewMenuItem {
+    pub name: String,
+    pub price: f32,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+impl NewMenuItem {
+    /// Blank categories are stored as no category at all.
+    pub fn category(&self) -> Option<&str> {
+        self.category
+            .as_deref()
+            .map(str::trim)
+            .filter(|category| !category.is_empty())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewDishRating {
+    pub score: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewComment {
+    pub body: String,
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateComment {
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewDimensionScore {
+    pub dimension_id: i32,
+    pub score: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NewRatingDimension {
+    pub name: String,
+    pub weight: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DbDimensionScore {
+    pub rating_id: i32,
+    pub dimension_id: i32,
+    pub name: String,
+    pub score: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DbDimensionAverage {
+    pub restaurant_id: i32,
+    pub dimension_id: i32,
+    pub name: String,
+    pub weight: f32,
+    pub average_score: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DbDimensionAveragePerPeriod {
+    pub restaurant_id: i32,
+    pub period_scheme: PeriodScheme,
+    pub year: i32,
+    pub period_index: i32,
+    pub round_name: Option<String>,
+    pub round_start_date: Option<NaiveDate>,
+    pub round_end_date: Option<NaiveDate>,
+    pub dimension_id: i32,
+    pub name: String,
+    pub weight: f32,
+    pub average_score: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DbAverageRatingPerPeriod {
+    pub restaurant_id: i32,
+    pub restaurant_code: String,
+    pub period_scheme: PeriodScheme,
+    pub year: i32,
+    pub period_index: i32,
+    pub round_name: Option<String>,
+    pub round_start_date: Option<NaiveDate>,
+    pub round_end_date: Option<NaiveDate>,
+    pub average_score: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -105,6 +426,8 @@ pub struct DbIp {
 #[derive(Serialize, Deserialize)]
 pub struct NewIp<'a> {
     pub ip_address: &'a str,
+    pub reason: Option<&'a str>,
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
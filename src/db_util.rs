@@ -1,9 +1,9 @@
 #![allow(dead_code)]
 
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 use anyhow::{anyhow, Error, Result};
-use chrono::Utc;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta, Utc};
 use serde_json::json;
 use sqlx::{
     migrate,
@@ -58,11 +58,12 @@ pub async fn create_user(conn: &mut MySqlConnection, new_user: &NewUser) -> Resu
 
     let query = sqlx::query_as!(
         User,
-        "INSERT INTO users (id, username, password, color) VALUES (?, ?, ?, ?)",
+        "INSERT INTO users (id, username, password, color, email) VALUES (?, ?, ?, ?, ?)",
         new_user.id,
         new_user.username,
         new_user.password,
-        new_user.color
+        new_user.color,
+        new_user.email
     );
     let result = match query.execute(&mut *tx).await {
         Ok(query_result) => query_result,
@@ -92,7 +93,9 @@ pub async fn create_user(conn: &mut MySqlConnection, new_user: &NewUser) -> Resu
             username: db_user.username,
             password: db_user.password,
             color: db_user.color,
+            email: db_user.email,
             token: String::new(),
+            refresh_token: String::new(),
             ratings: Vec::new(),
             group_memberships: Vec::new(),
         })
@@ -101,69 +104,32 @@ pub async fn create_user(conn: &mut MySqlConnection, new_user: &NewUser) -> Resu
     }
 }
 
-pub async fn get_users(pool: &MySqlPool) -> Result<Vec<User>> {
-    let mut conn = get_connection(pool)
-        .await
-        .ok_or(anyhow!("Failed to get connection."))?;
-    let mut tx = conn.begin().await?;
-
-    let db_users = match sqlx::query_as!(DbUser, "SELECT id, username, password, color FROM users")
-        .fetch_all(&mut *tx)
-        .await
-    {
-        Ok(db_users) => db_users,
-        Err(err) => {
-            tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
-        }
-    };
-
-    let tasks: Vec<_> = db_users
-        .into_iter()
-        .map(|db_user| {
-            let pool = pool.clone();
-            async move {
-                let mut conn = get_connection(&pool)
-                    .await
-                    .ok_or(anyhow!("Failed to get connection."))?;
-                let mut tx = conn.begin().await?;
-
-                let ratings = match get_ratings_by_user(&mut tx, &db_user.id).await {
-                    Ok(ratings_by_period) => ratings_by_period.current_period_ratings,
-                    Err(err) => {
-                        tx.rollback().await?;
-                        return Err(anyhow::anyhow!(err));
-                    }
-                };
-                let group_memberships =
-                    match get_group_memberships_by_user(&mut tx, &db_user.id).await {
-                        Ok(group_memberships) => group_memberships,
-                        Err(err) => {
-                            tx.rollback().await?;
-                            return Err(anyhow::anyhow!(err));
-                        }
-                    };
-
-                tx.commit().await?;
-
-                Ok(User {
-                    id: db_user.id,
-                    token: String::new(),
-                    username: db_user.username,
-                    password: db_user.password,
-                    color: db_user.color,
-                    ratings,
-                    group_memberships,
-                })
-            }
-        })
-        .collect();
-
-    let results: Result<Vec<User>> = futures::future::join_all(tasks).await.into_iter().collect();
-
-    tx.commit().await?;
+/// The user and everyone who shares a group with them.
+pub async fn get_users_sharing_groups(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+) -> Result<Vec<PublicUser>> {
+    let users = sqlx::query_as!(
+        PublicUser,
+        r#"
+        SELECT u.id, u.username, u.color
+        FROM users u
+        WHERE u.id = ?
+           OR u.id IN (
+               SELECT others.user_id
+               FROM group_memberships others
+               INNER JOIN group_memberships mine ON mine.group_id = others.group_id
+               WHERE mine.user_id = ?
+           )
+        ORDER BY u.username, u.id
+        "#,
+        user_id,
+        user_id
+    )
+    .fetch_all(conn)
+    .await?;
 
-    results
+    Ok(users)
 }
 
 pub async fn get_user_by_oidc(
@@ -174,7 +140,7 @@ pub async fn get_user_by_oidc(
     let result = sqlx::query_as!(
         DbUser,
         r#"
-        SELECT u.id, u.username, u.password, u.color
+        SELECT u.id, u.username, u.password, u.color, u.email
         FROM users u
         INNER JOIN oidc_links o ON u.id = o.user_id
         WHERE o.provider = ? AND o.subject = ?
@@ -192,6 +158,7 @@ pub async fn get_user_by_oidc(
             username: db_user.username.clone(),
             password: db_user.password.clone(),
             color: db_user.color.clone(),
+            email: db_user.email.clone(),
             ..Default::default()
         };
         Ok(Some(user))
@@ -267,7 +234,7 @@ pub async fn get_user_by_credentials(
 
     let query = sqlx::query_as!(
         DbUser,
-        "SELECT id, username, password, color FROM users WHERE username = ?",
+        "SELECT id, username, password, color, email FROM users WHERE username = ?",
         username
     );
     let db_user_result = match query.fetch_optional(&mut *tx).await {
@@ -302,16 +269,18 @@ pub async fn get_user_by_credentials(
             Ok(Some(User {
                 id: db_user.id,
                 token: String::new(),
+                refresh_token: String::new(),
                 username: db_user.username,
                 password: db_user.password,
                 color: db_user.color,
+                email: db_user.email,
                 ratings,
                 group_memberships,
             }))
         }
         None => {
             tx.rollback().await?;
-            Err(anyhow!("User not found"))
+            Ok(None)
         }
     }
 }
@@ -402,1350 +371,5050 @@ pub async fn update_user_password(
     Ok(result)
 }
 
-// NOTE: Push Notifications
-
-pub fn init_push_notifications(
-    vapid_public_key: String,
-    vapid_private_key: String,
-) -> Result<PushClient, Error> {
-    let client = IsahcWebPushClient::new()?;
+pub async fn update_user_email(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    email: Option<&str>,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("UPDATE users SET email = ? WHERE id = ?", email, user_id)
+        .execute(conn)
+        .await?;
 
-    Ok(PushClient {
-        vapid_public_key,
-        vapid_private_key,
-        client,
-    })
+    Ok(result)
 }
 
-pub async fn create_push_subscription(
+pub async fn update_user_superadmin(
     conn: &mut MySqlConnection,
     user_id: &str,
-    subscription_info: &SubscriptionInfo,
-) -> Result<PushSubscription> {
-    let mut tx = conn.begin().await?;
+    superadmin: bool,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "UPDATE users SET is_superadmin = ? WHERE id = ?",
+        superadmin,
+        user_id
+    )
+    .execute(conn)
+    .await?;
 
-    let query = sqlx::query_as!(
-        PushSubscription,
-        "INSERT INTO push_subscriptions (endpoint, user_id, p256dh, auth)
-         VALUES (?, ?, ?, ?)
-         ON DUPLICATE KEY UPDATE
-         p256dh = VALUES(p256dh), auth = VALUES(auth)",
-        subscription_info.endpoint,
-        user_id,
-        subscription_info.keys.p256dh,
-        subscription_info.keys.auth,
-    );
-    let result = match query.execute(&mut *tx).await {
-        Ok(query_result) => query_result,
-        Err(err) => {
-            tx.rollback().await?;
-            return Err(anyhow!("Could not create push subscription: {err}"));
-        }
-    };
+    if result.rows_affected() == 0 {
+        return Err(anyhow!("User not found"));
+    }
 
-    if result.rows_affected() > 0 {
-        let push_subscription =
-            match get_push_subscription(&mut tx, &subscription_info.endpoint).await {
-                Ok(push_subscription) => push_subscription,
-                Err(err) => {
-                    tx.rollback().await?;
-                    return Err(anyhow!("Could not get push subscription: {err}"));
-                }
-            };
+    Ok(result)
+}
 
-        tx.commit().await?;
+pub async fn is_superadmin(conn: &mut MySqlConnection, user_id: &str) -> Result<bool> {
+    let is_superadmin = sqlx::query_scalar!(
+        "SELECT is_superadmin as `is_superadmin: bool` FROM users WHERE id = ?",
+        user_id
+    )
+    .fetch_optional(conn)
+    .await?;
 
-        Ok(push_subscription)
-    } else {
-        Err(anyhow::anyhow!("Failed to create push subscription"))
-    }
+    Ok(is_superadmin.unwrap_or(false))
 }
 
-pub async fn get_push_subscription(
+/// The site-wide and group role of a user, resolved together for authorization.
+pub async fn get_group_access(
     conn: &mut MySqlConnection,
-    endpoint: &str,
-) -> Result<PushSubscription> {
-    let query = sqlx::query_as!(
-        PushSubscription,
-        "SELECT endpoint, user_id, p256dh, auth
-         FROM push_subscriptions
-         WHERE endpoint = ?",
-        endpoint,
-    );
-    let push_subscription_result = query.fetch_one(conn).await;
+    user_id: &str,
+    group_id: &str,
+) -> Result<DbGroupAccess> {
+    let access = sqlx::query_as!(
+        DbGroupAccess,
+        "SELECT u.is_superadmin as `superadmin: bool`, gm.role as `role: Role`
+         FROM users u
+         LEFT JOIN group_memberships gm ON gm.user_id = u.id AND gm.group_id = ?
+         WHERE u.id = ?",
+        group_id,
+        user_id
+    )
+    .fetch_optional(conn)
+    .await?;
 
-    match push_subscription_result {
-        Ok(push_subscription) => Ok(push_subscription),
-        Err(err) => Err(anyhow!("Push subscription not found: {err}")),
-    }
+    Ok(access.unwrap_or_default())
 }
 
-pub async fn delete_push_subscription(
+// NOTE: Sessions
+
+pub async fn create_session(
     conn: &mut MySqlConnection,
-    endpoint: &str,
-) -> Result<MySqlQueryResult> {
-    let result = sqlx::query!(
-        "DELETE FROM push_subscriptions WHERE endpoint = ?",
-        endpoint
+    user_id: &str,
+    new_session: &NewSession,
+) -> Result<String> {
+    let session_id = Uuid::new_v4().to_string();
+    let now = Utc::now().naive_utc();
+
+    sqlx::query!(
+        "INSERT INTO sessions (id, user_id, user_agent, ip_address, created_at, last_seen_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        session_id,
+        user_id,
+        new_session.user_agent,
+        new_session.ip_address,
+        now,
+        now
     )
     .execute(conn)
     .await?;
 
-    Ok(result)
+    Ok(session_id)
 }
 
-pub async fn create_rating_notification(
+/// Lists the sessions that can still be refreshed, most recently used first.
+pub async fn get_sessions(conn: &mut MySqlConnection, user_id: &str) -> Result<Vec<DbSession>> {
+    let sessions = sqlx::query_as!(
+        DbSession,
+        "SELECT s.id, s.user_agent, s.ip_address, s.created_at, s.last_seen_at
+         FROM sessions s
+         WHERE s.user_id = ?
+         AND EXISTS (
+             SELECT 1 FROM refresh_tokens rt
+             WHERE rt.session_id = s.id AND rt.revoked_at IS NULL AND rt.expires_at > ?
+         )
+         ORDER BY s.last_seen_at DESC",
+        user_id,
+        Utc::now().naive_utc()
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Whether access tokens issued for the session are still accepted.
+pub async fn session_active(
     conn: &mut MySqlConnection,
-    restaurant_id: i32,
-    group_id: &str,
-) -> Result<RatingNotification> {
+    user_id: &str,
+    session_id: &str,
+) -> Result<bool> {
+    let session_active = sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1
+            FROM sessions
+            WHERE id = ? AND user_id = ? AND revoked_at IS NULL
+         ) as session_active",
+        session_id,
+        user_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(session_active == 1)
+}
+
+pub async fn revoke_session(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    session_id: &str,
+) -> Result<MySqlQueryResult> {
     let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
 
-    let query = sqlx::query_as!(
-        RatingNotification,
-        "INSERT INTO rating_notifications (restaurant_id, group_id)
-         VALUES(?, ?)",
-        restaurant_id,
-        group_id,
-    );
-    let result = match query.execute(&mut *tx).await {
+    let result = match sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = ?
+         WHERE session_id = ? AND user_id = ? AND revoked_at IS NULL",
+        now,
+        session_id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
         Ok(query_result) => query_result,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!("Could not create rating notification: {err}"));
+            return Err(anyhow!("Could not revoke refresh tokens: {err}"));
         }
     };
 
-    if result.rows_affected() > 0 {
-        let last_insert_id = result.last_insert_id();
-
-        let rating_notification =
-            match get_rating_notification_by_id(&mut tx, &(last_insert_id as i32)).await {
-                Ok(rating_notification) => rating_notification,
-                Err(err) => {
-                    tx.rollback().await?;
-                    return Err(anyhow!("Could not get push subscription: {err}"));
-                }
-            };
-
-        tx.commit().await?;
-
-        Ok(rating_notification)
-    } else {
-        Err(anyhow::anyhow!("Failed to create rating notification"))
+    if let Err(err) = sqlx::query!(
+        "UPDATE sessions SET revoked_at = ?
+         WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+        now,
+        session_id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not revoke session: {err}"));
     }
-}
 
-pub async fn get_rating_notification_by_id(
-    conn: &mut MySqlConnection,
-    id: &i32,
-) -> Result<RatingNotification> {
-    let query = sqlx::query_as!(
-        RatingNotification,
-        "SELECT id, restaurant_id, group_id, notified_at
-         FROM rating_notifications
-         WHERE id = ?",
-        id
-    );
-    let rating_notification_result = query.fetch_one(conn).await;
+    tx.commit().await?;
 
-    match rating_notification_result {
-        Ok(rating_notification) => Ok(rating_notification),
-        Err(err) => Err(anyhow!("Rating notification not found: {err}")),
-    }
+    Ok(result)
 }
 
-pub async fn rating_notification_sent(
+pub async fn revoke_session_by_refresh_token(
     conn: &mut MySqlConnection,
-    restaurant_id: i32,
-    group_id: &str,
-) -> Result<bool> {
-    let date_range = Period::current_period_date_range()?;
-
-    let rating_notification_exists = match sqlx::query_scalar!(
-        "SELECT EXISTS (
-            SELECT 1
-            FROM rating_notifications
-            WHERE restaurant_id = ? AND group_id = ? AND notified_at >= ? AND notified_at <= ?
-         ) as rating_notification_exists",
-        restaurant_id,
-        group_id,
-        date_range.0,
-        date_range.1
+    token_hash: &str,
+) -> Result<MySqlQueryResult> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "UPDATE refresh_tokens rt
+         INNER JOIN refresh_tokens presented ON presented.session_id = rt.session_id
+         SET rt.revoked_at = ?
+         WHERE presented.token_hash = ? AND rt.revoked_at IS NULL",
+        now,
+        token_hash
     )
-    .fetch_one(conn)
+    .execute(&mut *tx)
     .await
     {
-        Ok(exists_result) => exists_result,
+        Ok(query_result) => query_result,
         Err(err) => {
-            return Err(anyhow!(
-                "Could not check if rating notification has been sent: {err}"
-            ));
+            tx.rollback().await?;
+            return Err(anyhow!("Could not revoke refresh tokens: {err}"));
         }
     };
 
-    if rating_notification_exists == 1 {
-        return Ok(true);
+    if let Err(err) = sqlx::query!(
+        "UPDATE sessions s
+         INNER JOIN refresh_tokens presented ON presented.session_id = s.id
+         SET s.revoked_at = ?
+         WHERE presented.token_hash = ? AND s.revoked_at IS NULL",
+        now,
+        token_hash
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not revoke session: {err}"));
     }
 
-    Ok(false)
+    tx.commit().await?;
+
+    Ok(result)
 }
 
-pub async fn send_notification_to_group(
+pub async fn revoke_sessions_by_user(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+) -> Result<MySqlQueryResult> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL",
+        now,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not revoke refresh tokens: {err}"));
+        }
+    };
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE sessions SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL",
+        now,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not revoke sessions: {err}"));
+    }
+
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+pub async fn create_refresh_token(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    session_id: &str,
+    token_hash: &str,
+    expires_at: NaiveDateTime,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "INSERT INTO refresh_tokens (user_id, session_id, token_hash, expires_at, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+        user_id,
+        session_id,
+        token_hash,
+        expires_at,
+        Utc::now().naive_utc()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+/// Swaps a refresh token for a new one in the same session and marks the session as seen.
+/// Presenting a token that was already rotated means it leaked, so the whole session is revoked
+/// and `None` is returned.
+pub async fn rotate_refresh_token(
+    conn: &mut MySqlConnection,
+    token_hash: &str,
+    new_token_hash: &str,
+    expires_at: NaiveDateTime,
+    seen_from: &NewSession,
+) -> Result<Option<DbRefreshToken>> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+
+    let refresh_token = match sqlx::query_as!(
+        DbRefreshToken,
+        "SELECT rt.id, rt.user_id, u.username, rt.session_id, rt.expires_at, rt.revoked_at
+         FROM refresh_tokens rt
+         INNER JOIN users u ON u.id = rt.user_id
+         WHERE rt.token_hash = ?
+         FOR UPDATE",
+        token_hash
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(Some(refresh_token)) => refresh_token,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get refresh token: {err}"));
+        }
+    };
+
+    if refresh_token.revoked_at.is_some() {
+        if let Err(err) = sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = ? WHERE session_id = ? AND revoked_at IS NULL",
+            now,
+            refresh_token.session_id
+        )
+        .execute(&mut *tx)
+        .await
+        {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not revoke session: {err}"));
+        }
+        if let Err(err) = sqlx::query!(
+            "UPDATE sessions SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL",
+            now,
+            refresh_token.session_id
+        )
+        .execute(&mut *tx)
+        .await
+        {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not revoke session: {err}"));
+        }
+        tx.commit().await?;
+        return Ok(None);
+    }
+
+    if refresh_token.expires_at <= now {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = ? WHERE id = ?",
+        now,
+        refresh_token.id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not revoke refresh token: {err}"));
+    }
+
+    if let Err(err) = create_refresh_token(
+        &mut tx,
+        &refresh_token.user_id,
+        &refresh_token.session_id,
+        new_token_hash,
+        expires_at,
+    )
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not create refresh token: {err}"));
+    }
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE sessions SET user_agent = ?, ip_address = ?, last_seen_at = ? WHERE id = ?",
+        seen_from.user_agent,
+        seen_from.ip_address,
+        now,
+        refresh_token.session_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update session: {err}"));
+    }
+
+    tx.commit().await?;
+
+    Ok(Some(refresh_token))
+}
+
+// NOTE: Password Resets
+
+pub async fn count_password_reset_tokens(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    since: NaiveDateTime,
+) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM password_reset_tokens WHERE user_id = ? AND created_at >= ?",
+        user_id,
+        since
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(count)
+}
+
+pub async fn create_password_reset_token(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    token_hash: &str,
+    expires_at: NaiveDateTime,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "INSERT INTO password_reset_tokens (user_id, token_hash, expires_at, created_at)
+         VALUES (?, ?, ?, ?)",
+        user_id,
+        token_hash,
+        expires_at,
+        Utc::now().naive_utc()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+/// Sets a new password with a reset token, which is used up along with any other outstanding
+/// reset tokens of the user, and signs out every session. Returns the user's id, or `None` if
+/// the token is unknown, used or expired.
+pub async fn reset_password(
+    conn: &mut MySqlConnection,
+    token_hash: &str,
+    new_hash: &str,
+) -> Result<Option<String>> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+
+    let user_id = match sqlx::query_scalar!(
+        "SELECT user_id FROM password_reset_tokens
+         WHERE token_hash = ? AND used_at IS NULL AND expires_at > ?
+         FOR UPDATE",
+        token_hash,
+        now
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(Some(user_id)) => user_id,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get password reset token: {err}"));
+        }
+    };
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE password_reset_tokens SET used_at = ? WHERE user_id = ? AND used_at IS NULL",
+        now,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not use password reset token: {err}"));
+    }
+
+    if let Err(err) = update_user_password(&mut tx, &user_id, new_hash).await {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update password: {err}"));
+    }
+
+    if let Err(err) = revoke_sessions_by_user(&mut tx, &user_id).await {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not revoke sessions: {err}"));
+    }
+
+    tx.commit().await?;
+
+    Ok(Some(user_id))
+}
+
+// NOTE: Two-Factor Authentication
+
+pub async fn get_totp_credential(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+) -> Result<Option<DbTotpCredential>> {
+    let totp_credential = sqlx::query_as!(
+        DbTotpCredential,
+        "SELECT secret, confirmed_at, last_used_step FROM totp_credentials WHERE user_id = ?",
+        user_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(totp_credential)
+}
+
+/// Stores a new secret that only takes effect once a code from it is confirmed.
+pub async fn start_totp_enrollment(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    secret: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "INSERT INTO totp_credentials (user_id, secret) VALUES (?, ?)
+         ON DUPLICATE KEY UPDATE secret = VALUES(secret), confirmed_at = NULL, last_used_step = NULL",
+        user_id,
+        secret
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn confirm_totp_enrollment(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    step: i64,
+    recovery_code_hashes: &[String],
+) -> Result<()> {
+    let mut tx = conn.begin().await?;
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE totp_credentials SET confirmed_at = ?, last_used_step = ? WHERE user_id = ?",
+        Utc::now().naive_utc(),
+        step,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!(
+            "Could not confirm two-factor authentication: {err}"
+        ));
+    }
+
+    if let Err(err) = replace_recovery_codes(&mut tx, user_id, recovery_code_hashes).await {
+        tx.rollback().await?;
+        return Err(err);
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Marks a time step as used, unless it or a later one already was.
+pub async fn use_totp_step(conn: &mut MySqlConnection, user_id: &str, step: i64) -> Result<bool> {
+    let result = sqlx::query!(
+        "UPDATE totp_credentials SET last_used_step = ?
+         WHERE user_id = ? AND confirmed_at IS NOT NULL
+         AND (last_used_step IS NULL OR last_used_step < ?)",
+        step,
+        user_id,
+        step
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn delete_totp_credential(conn: &mut MySqlConnection, user_id: &str) -> Result<()> {
+    let mut tx = conn.begin().await?;
+
+    if let Err(err) = sqlx::query!("DELETE FROM totp_recovery_codes WHERE user_id = ?", user_id)
+        .execute(&mut *tx)
+        .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not delete recovery codes: {err}"));
+    }
+
+    if let Err(err) = sqlx::query!("DELETE FROM totp_credentials WHERE user_id = ?", user_id)
+        .execute(&mut *tx)
+        .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!(
+            "Could not disable two-factor authentication: {err}"
+        ));
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn replace_recovery_codes(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    recovery_code_hashes: &[String],
+) -> Result<()> {
+    let mut tx = conn.begin().await?;
+
+    if let Err(err) = sqlx::query!("DELETE FROM totp_recovery_codes WHERE user_id = ?", user_id)
+        .execute(&mut *tx)
+        .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not delete recovery codes: {err}"));
+    }
+
+    for code_hash in recovery_code_hashes {
+        if let Err(err) = sqlx::query!(
+            "INSERT INTO totp_recovery_codes (user_id, code_hash) VALUES (?, ?)",
+            user_id,
+            code_hash
+        )
+        .execute(&mut *tx)
+        .await
+        {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create recovery code: {err}"));
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn count_recovery_codes(conn: &mut MySqlConnection, user_id: &str) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM totp_recovery_codes WHERE user_id = ? AND used_at IS NULL",
+        user_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(count)
+}
+
+pub async fn use_recovery_code(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    code_hash: &str,
+) -> Result<bool> {
+    let result = sqlx::query!(
+        "UPDATE totp_recovery_codes SET used_at = ?
+         WHERE user_id = ? AND code_hash = ? AND used_at IS NULL",
+        Utc::now().naive_utc(),
+        user_id,
+        code_hash
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn create_login_challenge(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    token_hash: &str,
+    expires_at: NaiveDateTime,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "INSERT INTO login_challenges (user_id, token_hash, expires_at, created_at)
+         VALUES (?, ?, ?, ?)",
+        user_id,
+        token_hash,
+        expires_at,
+        Utc::now().naive_utc()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn get_login_challenge(
+    conn: &mut MySqlConnection,
+    token_hash: &str,
+) -> Result<Option<DbLoginChallenge>> {
+    let login_challenge = sqlx::query_as!(
+        DbLoginChallenge,
+        "SELECT lc.id, lc.user_id, u.username, lc.attempts, lc.expires_at
+         FROM login_challenges lc
+         INNER JOIN users u ON u.id = lc.user_id
+         WHERE lc.token_hash = ?",
+        token_hash
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(login_challenge)
+}
+
+pub async fn record_login_challenge_attempt(
+    conn: &mut MySqlConnection,
+    id: i32,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "UPDATE login_challenges SET attempts = attempts + 1 WHERE id = ?",
+        id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn delete_login_challenge(
+    conn: &mut MySqlConnection,
+    id: i32,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM login_challenges WHERE id = ?", id)
+        .execute(conn)
+        .await?;
+
+    Ok(result)
+}
+
+// NOTE: Failed Logins
+
+pub async fn get_login_lockout(
+    conn: &mut MySqlConnection,
+    kind: &str,
+    subject: &str,
+) -> Result<Option<NaiveDateTime>> {
+    let locked_until = sqlx::query_scalar!(
+        "SELECT locked_until FROM failed_logins WHERE kind = ? AND subject = ?",
+        kind,
+        subject
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(locked_until.flatten())
+}
+
+/// Counts a failed login and returns how many there have been since the counter was last reset.
+/// Counters whose last failure is older than `window_start` start over.
+pub async fn record_failed_login(
+    conn: &mut MySqlConnection,
+    kind: &str,
+    subject: &str,
+    window_start: NaiveDateTime,
+) -> Result<i32> {
+    let mut tx = conn.begin().await?;
+
+    if let Err(err) = sqlx::query!(
+        "INSERT INTO failed_logins (kind, subject, failures, last_failed_at) VALUES (?, ?, 1, ?)
+         ON DUPLICATE KEY UPDATE
+         failures = IF(last_failed_at < ?, 1, failures + 1),
+         locked_until = IF(last_failed_at < ?, NULL, locked_until),
+         last_failed_at = VALUES(last_failed_at)",
+        kind,
+        subject,
+        Utc::now().naive_utc(),
+        window_start,
+        window_start
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not record failed login: {err}"));
+    }
+
+    let failures = match sqlx::query_scalar!(
+        "SELECT failures FROM failed_logins WHERE kind = ? AND subject = ?",
+        kind,
+        subject
+    )
+    .fetch_one(&mut *tx)
+    .await
+    {
+        Ok(failures) => failures,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not count failed logins: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(failures)
+}
+
+pub async fn lock_login(
+    conn: &mut MySqlConnection,
+    kind: &str,
+    subject: &str,
+    locked_until: NaiveDateTime,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "UPDATE failed_logins SET locked_until = ? WHERE kind = ? AND subject = ?",
+        locked_until,
+        kind,
+        subject
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn clear_failed_logins(
+    conn: &mut MySqlConnection,
+    kind: &str,
+    subject: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "DELETE FROM failed_logins WHERE kind = ? AND subject = ?",
+        kind,
+        subject
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+// NOTE: Signing Keys
+
+pub async fn get_signing_keys(conn: impl MySqlExecutor<'_>) -> Result<Vec<DbSigningKey>> {
+    let signing_keys = sqlx::query_as!(
+        DbSigningKey,
+        "SELECT kid, private_key, activates_at FROM signing_keys ORDER BY activates_at"
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(signing_keys)
+}
+
+pub async fn create_signing_key(
+    conn: impl MySqlExecutor<'_>,
+    signing_key: &DbSigningKey,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "INSERT INTO signing_keys (kid, private_key, activates_at) VALUES (?, ?, ?)",
+        signing_key.kid,
+        signing_key.private_key,
+        signing_key.activates_at
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn delete_signing_key(
+    conn: impl MySqlExecutor<'_>,
+    kid: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM signing_keys WHERE kid = ?", kid)
+        .execute(conn)
+        .await?;
+
+    Ok(result)
+}
+
+// NOTE: Push Notifications
+
+pub fn init_push_notifications(
+    vapid_public_key: String,
+    vapid_private_key: String,
+) -> Result<PushClient, Error> {
+    let client = IsahcWebPushClient::new()?;
+
+    Ok(PushClient {
+        vapid_public_key,
+        vapid_private_key,
+        client,
+    })
+}
+
+pub async fn create_push_subscription(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    subscription_info: &SubscriptionInfo,
+) -> Result<PushSubscription> {
+    let mut tx = conn.begin().await?;
+
+    let query = sqlx::query_as!(
+        PushSubscription,
+        "INSERT INTO push_subscriptions (endpoint, user_id, p256dh, auth)
+         VALUES (?, ?, ?, ?)
+         ON DUPLICATE KEY UPDATE
+         p256dh = VALUES(p256dh), auth = VALUES(auth)",
+        subscription_info.endpoint,
+        user_id,
+        subscription_info.keys.p256dh,
+        subscription_info.keys.auth,
+    );
+    let result = match query.execute(&mut *tx).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create push subscription: {err}"));
+        }
+    };
+
+    if result.rows_affected() > 0 {
+        let push_subscription =
+            match get_push_subscription(&mut tx, &subscription_info.endpoint).await {
+                Ok(push_subscription) => push_subscription,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(anyhow!("Could not get push subscription: {err}"));
+                }
+            };
+
+        tx.commit().await?;
+
+        Ok(push_subscription)
+    } else {
+        Err(anyhow::anyhow!("Failed to create push subscription"))
+    }
+}
+
+pub async fn get_push_subscription(
+    conn: &mut MySqlConnection,
+    endpoint: &str,
+) -> Result<PushSubscription> {
+    let query = sqlx::query_as!(
+        PushSubscription,
+        "SELECT endpoint, user_id, p256dh, auth
+         FROM push_subscriptions
+         WHERE endpoint = ?",
+        endpoint,
+    );
+    let push_subscription_result = query.fetch_one(conn).await;
+
+    match push_subscription_result {
+        Ok(push_subscription) => Ok(push_subscription),
+        Err(err) => Err(anyhow!("Push subscription not found: {err}")),
+    }
+}
+
+pub async fn get_push_subscriptions_by_user(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+) -> Result<Vec<PushSubscription>> {
+    let push_subscriptions = sqlx::query_as!(
+        PushSubscription,
+        "SELECT endpoint, user_id, p256dh, auth
+         FROM push_subscriptions
+         WHERE user_id = ?",
+        user_id,
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(push_subscriptions)
+}
+
+pub async fn delete_push_subscription(
+    conn: &mut MySqlConnection,
+    endpoint: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "DELETE FROM push_subscriptions WHERE endpoint = ?",
+        endpoint
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn create_rating_notification(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    group_id: &str,
+) -> Result<RatingNotification> {
+    let mut tx = conn.begin().await?;
+
+    let query = sqlx::query_as!(
+        RatingNotification,
+        "INSERT INTO rating_notifications (restaurant_id, group_id)
+         VALUES(?, ?)",
+        restaurant_id,
+        group_id,
+    );
+    let result = match query.execute(&mut *tx).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create rating notification: {err}"));
+        }
+    };
+
+    if result.rows_affected() > 0 {
+        let last_insert_id = result.last_insert_id();
+
+        let rating_notification =
+            match get_rating_notification_by_id(&mut tx, &(last_insert_id as i32)).await {
+                Ok(rating_notification) => rating_notification,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(anyhow!("Could not get push subscription: {err}"));
+                }
+            };
+
+        tx.commit().await?;
+
+        Ok(rating_notification)
+    } else {
+        Err(anyhow::anyhow!("Failed to create rating notification"))
+    }
+}
+
+pub async fn get_rating_notification_by_id(
+    conn: &mut MySqlConnection,
+    id: &i32,
+) -> Result<RatingNotification> {
+    let query = sqlx::query_as!(
+        RatingNotification,
+        "SELECT id, restaurant_id, group_id, notified_at
+         FROM rating_notifications
+         WHERE id = ?",
+        id
+    );
+    let rating_notification_result = query.fetch_one(conn).await;
+
+    match rating_notification_result {
+        Ok(rating_notification) => Ok(rating_notification),
+        Err(err) => Err(anyhow!("Rating notification not found: {err}")),
+    }
+}
+
+pub async fn rating_notification_sent(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    group_id: &str,
+) -> Result<bool> {
+    let date_range = match get_current_period(&mut *conn, group_id).await? {
+        Some(period) => period.to_date_range(),
+        None => return Ok(false),
+    };
+
+    let rating_notification_exists = match sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1
+            FROM rating_notifications
+            WHERE restaurant_id = ? AND group_id = ? AND notified_at >= ? AND DATE(notified_at) <= ?
+         ) as rating_notification_exists",
+        restaurant_id,
+        group_id,
+        date_range.0,
+        date_range.1
+    )
+    .fetch_one(conn)
+    .await
+    {
+        Ok(exists_result) => exists_result,
+        Err(err) => {
+            return Err(anyhow!(
+                "Could not check if rating notification has been sent: {err}"
+            ));
+        }
+    };
+
+    if rating_notification_exists == 1 {
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+pub async fn send_notification_to_group(
     push_client: &PushClient,
     pool: &MySqlPool,
     group_id: &str,
-    message: &str,
-) -> Result<()> {
-    let push_subscriptions_query = sqlx::query!(
-        "SELECT DISTINCT ps.*, g.name as group_name
+    message: &str,
+) -> Result<()> {
+    let push_subscriptions_query = sqlx::query!(
+        "SELECT DISTINCT ps.*, g.name as group_name
+         FROM group_memberships gm
+         JOIN groups g on g.id = gm.group_id
+         JOIN push_subscriptions ps ON ps.user_id = gm.user_id
+         WHERE gm.group_id = ?",
+        group_id,
+    );
+
+    let mut conn = get_connection(pool).await.unwrap();
+    let push_subscriptions = match push_subscriptions_query.fetch_all(&mut *conn).await {
+        Ok(result) => result,
+        Err(err) => return Err(anyhow!("Could not get push_subscriptions: {err}")),
+    };
+
+    let futures = push_subscriptions.into_iter().map(|push_subscription| {
+        let push_client = push_client.clone();
+        let subscription_info = SubscriptionInfo::new(
+            push_subscription.endpoint,
+            push_subscription.p256dh,
+            push_subscription.auth,
+        );
+        let body = format!("{}: {}", push_subscription.group_name, message);
+
+        let new_pool = pool.clone();
+        actix_web::rt::spawn(async move {
+            let mut conn = get_connection(&new_pool).await.unwrap();
+
+            if let Err(err) =
+                send_notification(&mut conn, &push_client, &subscription_info, &body).await
+            {
+                eprintln!(
+                    "ERROR: failed sending notification to {}: {err}",
+                    subscription_info.endpoint
+                );
+            }
+        })
+    });
+
+    futures::future::join_all(futures).await;
+
+    Ok(())
+}
+
+pub async fn send_notification(
+    conn: &mut MySqlConnection,
+    push_client: &PushClient,
+    subscription_info: &SubscriptionInfo,
+    body: &str,
+) -> Result<()> {
+    let vapid_builder = VapidSignatureBuilder::from_base64_no_sub(&push_client.vapid_private_key)?;
+
+    let vapid_signature = vapid_builder
+        .clone()
+        .add_sub_info(subscription_info)
+        .build()?;
+
+    let mut push_builder = WebPushMessageBuilder::new(subscription_info);
+
+    push_builder.set_vapid_signature(vapid_signature.clone());
+
+    let payload = json!({
+        "body": body
+    })
+    .to_string();
+    push_builder.set_payload(web_push::ContentEncoding::Aes128Gcm, payload.as_bytes());
+
+    let response = push_client.client.send(push_builder.build()?).await;
+
+    match response {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            let should_remove = matches!(
+                err,
+                WebPushError::EndpointNotValid(_) | WebPushError::EndpointNotFound(_)
+            );
+
+            if should_remove {
+                delete_push_subscription(conn, &subscription_info.endpoint).await?;
+            }
+
+            Err(anyhow!("Error sending notification: {err}"))
+        }
+    }
+}
+
+// NOTE: Groups
+
+pub async fn create_group(
+    conn: &mut MySqlConnection,
+    new_group: &NewGroup,
+) -> Result<GroupMembership> {
+    let mut tx = conn.begin().await?;
+
+    let id = Uuid::new_v4().to_string();
+    let created_at = Utc::now().naive_utc();
+    let updated_at = created_at;
+
+    let group_query = sqlx::query_as!(
+        Group,
+        "INSERT INTO groups (id, name, description, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+        id,
+        new_group.name,
+        new_group.description,
+        created_at,
+        updated_at,
+    );
+
+    let result = match group_query.execute(&mut *tx).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create group: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 1 {
+        let db_group = match get_group(&mut tx, &id).await {
+            Ok(group) => group,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get db group: {err}"));
+            }
+        };
+
+        if let Err(err) = sqlx::query!(
+            "INSERT INTO rating_dimensions (group_id, name, weight, position, created_at)
+             VALUES (?, 'overall', 1, 0, ?)",
+            db_group.id,
+            created_at,
+        )
+        .execute(&mut *tx)
+        .await
+        {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create default rating dimension: {err}"));
+        }
+
+        let db_group_membership = match create_group_membership(
+            &mut tx,
+            &NewGroupMembership {
+                group_id: db_group.id.clone(),
+                user_id: new_group.creator_id.to_owned(),
+                role: Role::Admin,
+                invited_by: None,
+            },
+        )
+        .await
+        {
+            Ok(mut group_membership) => {
+                group_membership.group = db_group;
+                group_membership
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get db group membership: {err}"));
+            }
+        };
+
+        tx.commit().await?;
+
+        Ok(db_group_membership)
+    } else {
+        Err(anyhow::anyhow!("Failed to create group"))
+    }
+}
+
+pub async fn get_group(conn: &mut MySqlConnection, id: &str) -> Result<Group> {
+    let query = sqlx::query_as!(
+        Group,
+        "SELECT id, name, description, period_scheme as `period_scheme: PeriodScheme`, blind_ratings as `blind_ratings: bool`, scale_min, scale_max, scale_step, rating_quorum, created_at, updated_at
+         FROM groups
+         WHERE id = ?",
+        id,
+    );
+    let group = query.fetch_optional(conn).await?;
+
+    match group {
+        Some(group) => Ok(group),
+        None => Err(anyhow!("Group not found")),
+    }
+}
+
+pub async fn get_group_by_membership(
+    conn: &mut MySqlConnection,
+    membership_id: &i32,
+) -> Result<Group> {
+    let query = sqlx::query_as!(
+        Group,
+        "SELECT g.id, g.name, g.description, g.period_scheme as `period_scheme: PeriodScheme`, g.blind_ratings as `blind_ratings: bool`, g.scale_min, g.scale_max, g.scale_step, g.rating_quorum, g.created_at, g.updated_at
+         FROM groups g
+         JOIN group_memberships gm ON gm.group_id = g.id
+         WHERE gm.id = ?",
+        membership_id,
+    );
+    let group = query.fetch_optional(conn).await?;
+
+    match group {
+        Some(group) => Ok(group),
+        None => Err(anyhow!("Group not found")),
+    }
+}
+
+pub async fn update_group(
+    conn: &mut MySqlConnection,
+    id: &str,
+    update_group: &UpdateGroup,
+) -> Result<Group> {
+    let mut tx = conn.begin().await?;
+
+    let updated_at = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "UPDATE groups SET name = ?, description = ?, updated_at = ? WHERE id = ?",
+        update_group.name,
+        update_group.description,
+        updated_at,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not update group: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Err(anyhow!("Group not found"));
+    }
+
+    let group = match get_group(&mut tx, id).await {
+        Ok(group) => group,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db group: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(group)
+}
+
+pub async fn delete_group(conn: &mut MySqlConnection, id: &str) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM groups WHERE id = ?", id)
+        .execute(conn)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn create_group_membership(
+    conn: &mut MySqlConnection,
+    new_group_membership: &NewGroupMembership,
+) -> Result<GroupMembership> {
+    let mut tx = conn.begin().await?;
+
+    let created_at = Utc::now().naive_utc();
+    let updated_at = created_at;
+
+    if check_group_membership_exists(
+        &mut tx,
+        &new_group_membership.user_id,
+        &new_group_membership.group_id,
+    )
+    .await?
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Group membership already exists"));
+    }
+
+    let membership_query = sqlx::query_as!(
+        GroupMembership,
+        "INSERT INTO group_memberships (group_id, user_id, role, invited_by, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        new_group_membership.group_id,
+        new_group_membership.user_id,
+        new_group_membership.role,
+        new_group_membership.invited_by,
+        created_at,
+        updated_at
+    );
+
+    let result = match membership_query.execute(&mut *tx).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create group membership: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 1 {
+        let membership_id = result.last_insert_id();
+
+        let db_group_membership = match get_group_membership(&mut tx, &(membership_id as i32)).await
+        {
+            Ok(group_membership) => group_membership,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get db group membership: {err}"));
+            }
+        };
+
+        tx.commit().await?;
+
+        Ok(db_group_membership)
+    } else {
+        Err(anyhow::anyhow!("Failed to create group membership"))
+    }
+}
+
+pub async fn check_group_membership_exists(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+    group_id: &str,
+) -> Result<bool> {
+    let group_membership_exists_query = sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1
+            FROM group_memberships
+            WHERE user_id = ? AND group_id = ?
+         ) as group_membership_exists;",
+        user_id,
+        group_id
+    );
+    let group_membership_exists = match group_membership_exists_query.fetch_one(&mut *conn).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            return Err(anyhow!("Could not validate group membership: {err}"));
+        }
+    };
+
+    if group_membership_exists == 1 {
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+pub async fn get_group_membership(conn: &mut MySqlConnection, id: &i32) -> Result<GroupMembership> {
+    let mut tx = conn.begin().await?;
+
+    let query = sqlx::query_as!(
+        DbGroupMembership,
+        "SELECT id, group_id, user_id, role, invited_by, created_at, updated_at
+         FROM group_memberships
+         WHERE id = ?",
+        id,
+    );
+    let group_membership = query.fetch_optional(&mut *tx).await?;
+
+    match group_membership {
+        Some(group_membership) => {
+            let db_group = match get_group_by_membership(&mut tx, &group_membership.id).await {
+                Ok(group) => group,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(anyhow!("Could not get db group from membership: {err}"));
+                }
+            };
+
+            tx.commit().await?;
+
+            Ok(GroupMembership::from_db(&group_membership, &db_group))
+        }
+        None => Err(anyhow!("Group membership not found")),
+    }
+}
+
+pub async fn get_group_memberships_by_user(
+    conn: &mut MySqlConnection,
+    user_id: &str,
+) -> Result<Vec<GroupMembership>> {
+    let mut tx = conn.begin().await?;
+
+    let db_group_memberships = match sqlx::query_as!(
+        DbGroupMembership,
+        "SELECT id, group_id, user_id, role, invited_by, created_at, updated_at
+         FROM group_memberships
+         WHERE user_id = ?",
+        user_id,
+    )
+    .fetch_all(&mut *tx)
+    .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Group membership not found: {err}"));
+        }
+    };
+
+    let mut group_memberships = Vec::new();
+
+    for db_group_membership in db_group_memberships {
+        let db_group = match get_group_by_membership(&mut tx, &db_group_membership.id).await {
+            Ok(group) => group,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get db group from membership: {err}"));
+            }
+        };
+
+        group_memberships.push(GroupMembership::from_db(&db_group_membership, &db_group));
+    }
+
+    tx.commit().await?;
+
+    Ok(group_memberships)
+}
+
+pub async fn delete_group_membership(
+    conn: &mut MySqlConnection,
+    id: &i32,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM group_memberships WHERE id = ?", id)
+        .execute(conn)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn get_group_members(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Vec<GroupMember>> {
+    let group_members = sqlx::query_as!(
+        GroupMember,
+        "SELECT gm.id, gm.user_id, u.username, u.color, gm.role, gm.invited_by, gm.created_at
+         FROM group_memberships gm
+         JOIN users u ON u.id = gm.user_id
+         WHERE gm.group_id = ?
+         ORDER BY gm.created_at, gm.id",
+        group_id,
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(group_members)
+}
+
+async fn lock_group_membership(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    user_id: &str,
+) -> Result<(DbGroupMembership, i64)> {
+    // NOTE: Lock every membership row of the group so that concurrent role changes
+    // can't both pass the last admin check
+    let db_group_memberships = sqlx::query_as!(
+        DbGroupMembership,
+        "SELECT id, group_id, user_id, role, invited_by, created_at, updated_at
+         FROM group_memberships
+         WHERE group_id = ?
+         FOR UPDATE",
+        group_id,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let admin_count = db_group_memberships
+        .iter()
+        .filter(|gm| matches!(gm.role, Role::Admin))
+        .count() as i64;
+
+    match db_group_memberships
+        .into_iter()
+        .find(|gm| gm.user_id == user_id)
+    {
+        Some(db_group_membership) => Ok((db_group_membership, admin_count)),
+        None => Err(anyhow!("Group membership not found")),
+    }
+}
+
+pub async fn update_group_member_role(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    user_id: &str,
+    role: &Role,
+) -> Result<GroupMembership> {
+    let mut tx = conn.begin().await?;
+
+    let (db_group_membership, admin_count) =
+        match lock_group_membership(&mut tx, group_id, user_id).await {
+            Ok(result) => result,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+
+    if matches!(db_group_membership.role, Role::Admin)
+        && !matches!(role, Role::Admin)
+        && admin_count <= 1
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("The last admin of a group cannot be demoted"));
+    }
+
+    let updated_at = Utc::now().naive_utc();
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE group_memberships SET role = ?, updated_at = ? WHERE id = ?",
+        role,
+        updated_at,
+        db_group_membership.id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update group membership: {err}"));
+    }
+
+    let group_membership = match get_group_membership(&mut tx, &db_group_membership.id).await {
+        Ok(group_membership) => group_membership,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db group membership: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(group_membership)
+}
+
+pub async fn remove_group_member(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    user_id: &str,
+) -> Result<MySqlQueryResult> {
+    let mut tx = conn.begin().await?;
+
+    let (db_group_membership, admin_count) =
+        match lock_group_membership(&mut tx, group_id, user_id).await {
+            Ok(result) => result,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+
+    if matches!(db_group_membership.role, Role::Admin) && admin_count <= 1 {
+        tx.rollback().await?;
+        return Err(anyhow!(
+            "The last admin of a group cannot leave. Promote another member or delete the group"
+        ));
+    }
+
+    let result = match delete_group_membership(&mut tx, &db_group_membership.id).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not delete group membership: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+// NOTE: Group Invites
+
+pub async fn create_group_invite(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    created_by: &str,
+    new_group_invite: &NewGroupInvite,
+) -> Result<GroupInvite> {
+    let mut tx = conn.begin().await?;
+
+    let code = Uuid::new_v4().simple().to_string();
+    let created_at = Utc::now().naive_utc();
+    let expires_at = match new_group_invite.expires_in_hours {
+        Some(hours) => Some(
+            TimeDelta::try_hours(hours)
+                .and_then(|expires_in| created_at.checked_add_signed(expires_in))
+                .ok_or_else(|| anyhow!("Invalid invite expiry: {hours} hours"))?,
+        ),
+        None => None,
+    };
+
+    let query = sqlx::query!(
+        "INSERT INTO group_invites (code, group_id, created_by, max_uses, expires_at, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        code,
+        group_id,
+        created_by,
+        new_group_invite.max_uses,
+        expires_at,
+        created_at
+    );
+    let result = match query.execute(&mut *tx).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create group invite: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 1 {
+        let group_invite = match get_group_invite_by_code(&mut tx, &code).await {
+            Ok(Some(group_invite)) => group_invite,
+            Ok(None) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get group invite"));
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get group invite: {err}"));
+            }
+        };
+
+        tx.commit().await?;
+
+        Ok(group_invite)
+    } else {
+        tx.rollback().await?;
+        Err(anyhow::anyhow!("Failed to create group invite"))
+    }
+}
+
+pub async fn get_group_invite_by_code(
+    conn: &mut MySqlConnection,
+    code: &str,
+) -> Result<Option<GroupInvite>> {
+    let group_invite = sqlx::query_as!(
+        GroupInvite,
+        "SELECT id, code, group_id, created_by, max_uses, uses, expires_at, revoked_at, created_at
+         FROM group_invites
+         WHERE code = ?",
+        code
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(group_invite)
+}
+
+pub async fn get_group_invites(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Vec<GroupInvite>> {
+    let group_invites = sqlx::query_as!(
+        GroupInvite,
+        "SELECT id, code, group_id, created_by, max_uses, uses, expires_at, revoked_at, created_at
+         FROM group_invites
+         WHERE group_id = ?
+         ORDER BY created_at DESC",
+        group_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(group_invites)
+}
+
+pub async fn revoke_group_invite(
+    conn: &mut MySqlConnection,
+    invite_id: i32,
+    group_id: &str,
+) -> Result<MySqlQueryResult> {
+    let revoked_at = Utc::now().naive_utc();
+
+    let result = sqlx::query!(
+        "UPDATE group_invites
+         SET revoked_at = ?
+         WHERE id = ? AND group_id = ? AND revoked_at IS NULL",
+        revoked_at,
+        invite_id,
+        group_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn redeem_group_invite(
+    conn: &mut MySqlConnection,
+    code: &str,
+    user_id: &str,
+) -> Result<GroupMembership> {
+    let mut tx = conn.begin().await?;
+
+    // NOTE: Lock the invite row so concurrent redemptions can't exceed max_uses
+    let group_invite = match sqlx::query_as!(
+        GroupInvite,
+        "SELECT id, code, group_id, created_by, max_uses, uses, expires_at, revoked_at, created_at
+         FROM group_invites
+         WHERE code = ?
+         FOR UPDATE",
+        code
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(Some(group_invite)) => group_invite,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Invite not found"));
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get group invite: {err}"));
+        }
+    };
+
+    if !group_invite.is_redeemable(Utc::now().naive_utc()) {
+        tx.rollback().await?;
+        return Err(anyhow!("Invite is expired, revoked or used up"));
+    }
+
+    let group_membership = match create_group_membership(
+        &mut tx,
+        &NewGroupMembership {
+            group_id: group_invite.group_id.clone(),
+            user_id: user_id.to_owned(),
+            role: Role::Member,
+            invited_by: Some(group_invite.created_by.clone()),
+        },
+    )
+    .await
+    {
+        Ok(group_membership) => group_membership,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(err);
+        }
+    };
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE group_invites SET uses = uses + 1 WHERE id = ?",
+        group_invite.id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update group invite: {err}"));
+    }
+
+    tx.commit().await?;
+
+    Ok(group_membership)
+}
+
+// NOTE: Absences
+
+pub async fn create_member_absence(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    new_absence: &NewMemberAbsence,
+) -> Result<MemberAbsence> {
+    if let (Some(start_date), Some(end_date)) = (new_absence.start_date, new_absence.end_date) {
+        if start_date > end_date {
+            return Err(anyhow!("Absence can't end before it starts"));
+        }
+    }
+
+    let mut tx = conn.begin().await?;
+
+    if !check_group_membership_exists(&mut tx, &new_absence.user_id, group_id).await? {
+        tx.rollback().await?;
+        return Err(anyhow!("User does not belong to group"));
+    }
+
+    if let Some(restaurant_id) = new_absence.restaurant_id {
+        let restaurant_exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM restaurants WHERE id = ? AND group_id = ?)",
+            restaurant_id,
+            group_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if restaurant_exists == 0 {
+            tx.rollback().await?;
+            return Err(anyhow!("Restaurant does not exist in this group"));
+        }
+    }
+
+    let result = match sqlx::query!(
+        "INSERT INTO member_absences (group_id, user_id, restaurant_id, start_date, end_date, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        group_id,
+        new_absence.user_id,
+        new_absence.restaurant_id,
+        new_absence.start_date,
+        new_absence.end_date,
+        Utc::now().naive_utc()
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create absence: {err}"));
+        }
+    };
+
+    let absence = match get_member_absence(&mut tx, result.last_insert_id() as i32).await {
+        Ok(absence) => absence,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(err);
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(absence)
+}
+
+pub async fn get_member_absence(
+    conn: &mut MySqlConnection,
+    absence_id: i32,
+) -> Result<MemberAbsence> {
+    let absence = sqlx::query_as!(
+        MemberAbsence,
+        "SELECT id, group_id, user_id, restaurant_id, start_date, end_date, created_at
+         FROM member_absences
+         WHERE id = ?",
+        absence_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    absence.ok_or_else(|| anyhow!("Absence not found"))
+}
+
+pub async fn get_member_absences(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Vec<MemberAbsence>> {
+    let absences = sqlx::query_as!(
+        MemberAbsence,
+        "SELECT id, group_id, user_id, restaurant_id, start_date, end_date, created_at
+         FROM member_absences
+         WHERE group_id = ?
+         ORDER BY created_at DESC, id DESC",
+        group_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(absences)
+}
+
+pub async fn delete_member_absence(
+    conn: &mut MySqlConnection,
+    absence_id: i32,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM member_absences WHERE id = ?", absence_id)
+        .execute(conn)
+        .await?;
+
+    Ok(result)
+}
+
+// NOTE: Periods
+
+pub async fn update_group_period_scheme(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    period_scheme: &PeriodScheme,
+) -> Result<Group> {
+    let mut tx = conn.begin().await?;
+
+    let updated_at = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "UPDATE groups SET period_scheme = ?, updated_at = ? WHERE id = ?",
+        period_scheme,
+        updated_at,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not update group period scheme: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Err(anyhow!("Group not found"));
+    }
+
+    let group = match get_group(&mut tx, group_id).await {
+        Ok(group) => group,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db group: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(group)
+}
+
+pub async fn update_group_blind_ratings(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    blind_ratings: bool,
+) -> Result<Group> {
+    let mut tx = conn.begin().await?;
+
+    let updated_at = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "UPDATE groups SET blind_ratings = ?, updated_at = ? WHERE id = ?",
+        blind_ratings,
+        updated_at,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not update group blind ratings: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Err(anyhow!("Group not found"));
+    }
+
+    let group = match get_group(&mut tx, group_id).await {
+        Ok(group) => group,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db group: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(group)
+}
+
+// NOTE: Ratings are checked against the scale under a shared lock on the group row, so that
+// they can't be written on the previous scale while a scale change rescales the existing scores
+async fn get_group_rating_scale(conn: &mut MySqlConnection, group_id: &str) -> Result<RatingScale> {
+    let rating_scale = sqlx::query_as!(
+        RatingScale,
+        "SELECT scale_min AS min, scale_max AS max, scale_step AS step
+         FROM groups
+         WHERE id = ?
+         LOCK IN SHARE MODE",
+        group_id,
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    rating_scale.ok_or_else(|| anyhow!("Group not found"))
+}
+
+async fn lock_group_rating_scale(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<RatingScale> {
+    let rating_scale = sqlx::query_as!(
+        RatingScale,
+        "SELECT scale_min AS min, scale_max AS max, scale_step AS step
+         FROM groups
+         WHERE id = ?
+         FOR UPDATE",
+        group_id,
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    rating_scale.ok_or_else(|| anyhow!("Group not found"))
+}
+
+pub async fn update_group_rating_scale(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    rating_scale: &RatingScale,
+) -> Result<Group> {
+    rating_scale.validate()?;
+
+    let mut tx = conn.begin().await?;
+
+    let previous_scale = match lock_group_rating_scale(&mut tx, group_id).await {
+        Ok(rating_scale) => rating_scale,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get group rating scale: {err}"));
+        }
+    };
+
+    let updated_at = Utc::now().naive_utc();
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE groups SET scale_min = ?, scale_max = ?, scale_step = ?, updated_at = ? WHERE id = ?",
+        rating_scale.min,
+        rating_scale.max,
+        rating_scale.step,
+        updated_at,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update group rating scale: {err}"));
+    }
+
+    // NOTE: Existing scores are mapped onto the new scale so that historical averages stay comparable
+    let factor = (rating_scale.max - rating_scale.min) / (previous_scale.max - previous_scale.min);
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE ratings SET score = ? + (score - ?) * ? WHERE group_id = ?",
+        rating_scale.min,
+        previous_scale.min,
+        factor,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not rescale ratings: {err}"));
+    }
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE rating_scores rs
+         JOIN rating_dimensions d ON d.id = rs.dimension_id
+         SET rs.score = ? + (rs.score - ?) * ?
+         WHERE d.group_id = ?",
+        rating_scale.min,
+        previous_scale.min,
+        factor,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not rescale rating scores: {err}"));
+    }
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE dish_ratings dr
+         JOIN menu_items mi ON mi.id = dr.menu_item_id
+         JOIN restaurants r ON r.id = mi.restaurant_id
+         SET dr.score = ? + (dr.score - ?) * ?
+         WHERE r.group_id = ?",
+        rating_scale.min,
+        previous_scale.min,
+        factor,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not rescale dish ratings: {err}"));
+    }
+
+    let group = match get_group(&mut tx, group_id).await {
+        Ok(group) => group,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db group: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(group)
+}
+
+pub async fn update_group_rating_quorum(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    rating_quorum: i32,
+) -> Result<Group> {
+    if !(1..=100).contains(&rating_quorum) {
+        return Err(anyhow!("Rating quorum must be between 1 and 100 percent"));
+    }
+
+    let mut tx = conn.begin().await?;
+
+    let updated_at = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "UPDATE groups SET rating_quorum = ?, updated_at = ? WHERE id = ?",
+        rating_quorum,
+        updated_at,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not update group rating quorum: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Err(anyhow!("Group not found"));
+    }
+
+    let group = match get_group(&mut tx, group_id).await {
+        Ok(group) => group,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db group: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(group)
+}
+
+pub async fn create_group_round(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    new_group_round: &NewGroupRound,
+) -> Result<GroupRound> {
+    if new_group_round.end_date < new_group_round.start_date {
+        return Err(anyhow!("Round end_date must not be before start_date"));
+    }
+
+    let mut tx = conn.begin().await?;
+
+    let overlaps = match sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1
+            FROM group_rounds
+            WHERE group_id = ? AND start_date <= ? AND end_date >= ?
+         ) as round_overlaps",
+        group_id,
+        new_group_round.end_date,
+        new_group_round.start_date,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    {
+        Ok(overlaps) => overlaps,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not validate group round: {err}"));
+        }
+    };
+
+    if overlaps == 1 {
+        tx.rollback().await?;
+        return Err(anyhow!(
+            "Round overlaps with an existing round of this group"
+        ));
+    }
+
+    let created_at = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "INSERT INTO group_rounds (group_id, name, start_date, end_date, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+        group_id,
+        new_group_round.name,
+        new_group_round.start_date,
+        new_group_round.end_date,
+        created_at,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create group round: {err}"));
+        }
+    };
+
+    if result.rows_affected() == 1 {
+        tx.commit().await?;
+
+        Ok(GroupRound {
+            id: result.last_insert_id() as i32,
+            group_id: group_id.to_owned(),
+            name: new_group_round.name.clone(),
+            start_date: new_group_round.start_date,
+            end_date: new_group_round.end_date,
+            created_at,
+        })
+    } else {
+        tx.rollback().await?;
+        Err(anyhow!("Failed to create group round"))
+    }
+}
+
+pub async fn get_group_rounds(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Vec<GroupRound>> {
+    let group_rounds = sqlx::query_as!(
+        GroupRound,
+        "SELECT id, group_id, name, start_date, end_date, created_at
+         FROM group_rounds
+         WHERE group_id = ?
+         ORDER BY start_date",
+        group_id,
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(group_rounds)
+}
+
+pub async fn delete_group_round(
+    conn: &mut MySqlConnection,
+    round_id: i32,
+    group_id: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "DELETE FROM group_rounds WHERE id = ? AND group_id = ?",
+        round_id,
+        group_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+pub async fn get_group_period(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    date: NaiveDate,
+) -> Result<Option<Period>> {
+    let group = get_group(&mut *conn, group_id).await?;
+
+    let rounds = match group.period_scheme {
+        PeriodScheme::Custom => get_group_rounds(&mut *conn, group_id).await?,
+        _ => Vec::new(),
+    };
+
+    Ok(Period::containing(&group.period_scheme, &rounds, date))
+}
+
+pub async fn get_current_period(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Option<Period>> {
+    get_group_period(conn, group_id, Utc::now().date_naive()).await
+}
+
+/// The period a ratings path names, see `Period::parse_index`. Custom rounds have to start in
+/// the given year too.
+pub async fn get_period_by_path(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    year: i32,
+    period: &str,
+) -> Result<Period> {
+    let group = get_group(&mut *conn, group_id).await?;
+    let index = Period::parse_index(&group.period_scheme, period)?;
+
+    match group.period_scheme {
+        PeriodScheme::Custom => get_group_rounds(conn, group_id)
+            .await?
+            .iter()
+            .map(Period::from_round)
+            .find(|round| round.index == index && round.year() == year)
+            .ok_or_else(|| anyhow!("Round not found in {year}")),
+        period_scheme => Period::from_index(&period_scheme, year, index),
+    }
+}
+
+// NOTE: Rating Dimensions
+
+pub async fn get_rating_dimensions(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Vec<RatingDimension>> {
+    let rating_dimensions = sqlx::query_as!(
+        RatingDimension,
+        "SELECT id, group_id, name, weight, position, created_at
+         FROM rating_dimensions
+         WHERE group_id = ?
+         ORDER BY position, id",
+        group_id,
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rating_dimensions)
+}
+
+pub async fn create_rating_dimension(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    new_rating_dimension: &NewRatingDimension,
+) -> Result<RatingDimension> {
+    if new_rating_dimension.name.trim().is_empty() {
+        return Err(anyhow!("Dimension name must not be empty"));
+    }
+    if new_rating_dimension.weight <= 0.0 {
+        return Err(anyhow!("Dimension weight must be greater than 0"));
+    }
+
+    let mut tx = conn.begin().await?;
+
+    let position = match get_rating_dimensions(&mut tx, group_id).await {
+        Ok(rating_dimensions) => rating_dimensions.len() as i32,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get rating dimensions: {err}"));
+        }
+    };
+    let created_at = Utc::now().naive_utc();
+
+    let result = match sqlx::query!(
+        "INSERT INTO rating_dimensions (group_id, name, weight, position, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+        group_id,
+        new_rating_dimension.name,
+        new_rating_dimension.weight,
+        position,
+        created_at,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create rating dimension: {err}"));
+        }
+    };
+
+    let dimension_id = result.last_insert_id() as i32;
+
+    // NOTE: Existing ratings get their overall score for the new dimension,
+    // so their weighted overall score stays the same
+    if let Err(err) = sqlx::query!(
+        "INSERT INTO rating_scores (rating_id, dimension_id, score)
+         SELECT id, ?, score FROM ratings WHERE group_id = ?",
+        dimension_id,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not backfill rating scores: {err}"));
+    }
+
+    tx.commit().await?;
+
+    Ok(RatingDimension {
+        id: dimension_id,
+        group_id: group_id.to_owned(),
+        name: new_rating_dimension.name.clone(),
+        weight: new_rating_dimension.weight,
+        position,
+        created_at,
+    })
+}
+
+pub async fn update_rating_dimension(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    dimension_id: i32,
+    updated_rating_dimension: &NewRatingDimension,
+) -> Result<RatingDimension> {
+    if updated_rating_dimension.name.trim().is_empty() {
+        return Err(anyhow!("Dimension name must not be empty"));
+    }
+    if updated_rating_dimension.weight <= 0.0 {
+        return Err(anyhow!("Dimension weight must be greater than 0"));
+    }
+
+    let mut tx = conn.begin().await?;
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE rating_dimensions SET name = ?, weight = ? WHERE id = ? AND group_id = ?",
+        updated_rating_dimension.name,
+        updated_rating_dimension.weight,
+        dimension_id,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update rating dimension: {err}"));
+    }
+
+    let rating_dimension = match get_rating_dimensions(&mut tx, group_id)
+        .await
+        .map(|rating_dimensions| rating_dimensions.into_iter().find(|d| d.id == dimension_id))
+    {
+        Ok(Some(rating_dimension)) => rating_dimension,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Rating dimension not found"));
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get rating dimension: {err}"));
+        }
+    };
+
+    if let Err(err) = recalculate_rating_scores(&mut tx, group_id).await {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not recalculate rating scores: {err}"));
+    }
+
+    tx.commit().await?;
+
+    Ok(rating_dimension)
+}
+
+pub async fn delete_rating_dimension(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    dimension_id: i32,
+) -> Result<MySqlQueryResult> {
+    let mut tx = conn.begin().await?;
+
+    let rating_dimensions = match get_rating_dimensions(&mut tx, group_id).await {
+        Ok(rating_dimensions) => rating_dimensions,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get rating dimensions: {err}"));
+        }
+    };
+
+    if !rating_dimensions.iter().any(|d| d.id == dimension_id) {
+        tx.rollback().await?;
+        return Err(anyhow!("Rating dimension not found"));
+    }
+    if rating_dimensions.len() == 1 {
+        tx.rollback().await?;
+        return Err(anyhow!(
+            "The last rating dimension of a group cannot be deleted"
+        ));
+    }
+
+    let result = match sqlx::query!(
+        "DELETE FROM rating_dimensions WHERE id = ? AND group_id = ?",
+        dimension_id,
+        group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not delete rating dimension: {err}"));
+        }
+    };
+
+    if let Err(err) = recalculate_rating_scores(&mut tx, group_id).await {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not recalculate rating scores: {err}"));
+    }
+
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+/// Recalculates the stored weighted overall score of every rating of a group
+/// after its dimensions or their weights have changed.
+async fn recalculate_rating_scores(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "UPDATE ratings r
+         JOIN (
+            SELECT rs.rating_id, SUM(rs.score * d.weight) / SUM(d.weight) AS overall_score
+            FROM rating_scores rs
+            JOIN rating_dimensions d ON d.id = rs.dimension_id
+            WHERE d.group_id = ?
+            GROUP BY rs.rating_id
+         ) scores ON scores.rating_id = r.id
+         SET r.score = scores.overall_score
+         WHERE r.group_id = ?",
+        group_id,
+        group_id,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+async fn set_rating_scores(
+    conn: &mut MySqlConnection,
+    rating_id: i32,
+    scores: &[NewDimensionScore],
+) -> Result<()> {
+    sqlx::query!("DELETE FROM rating_scores WHERE rating_id = ?", rating_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for score in scores {
+        sqlx::query!(
+            "INSERT INTO rating_scores (rating_id, dimension_id, score) VALUES (?, ?, ?)",
+            rating_id,
+            score.dimension_id,
+            score.score,
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn with_rating_scores(
+    conn: &mut MySqlConnection,
+    ratings: Vec<Rating>,
+) -> Result<Vec<Rating>> {
+    let mut ratings_with_scores = Vec::with_capacity(ratings.len());
+
+    for rating in ratings {
+        let scores = get_rating_scores(&mut *conn, rating.id).await?;
+        ratings_with_scores.push(rating.with_scores(scores));
+    }
+
+    Ok(ratings_with_scores)
+}
+
+pub async fn get_rating_scores(
+    conn: &mut MySqlConnection,
+    rating_id: i32,
+) -> Result<Vec<DimensionScore>> {
+    let scores = sqlx::query_as!(
+        DbDimensionScore,
+        "SELECT rs.rating_id, rs.dimension_id, d.name, rs.score
+         FROM rating_scores rs
+         JOIN rating_dimensions d ON d.id = rs.dimension_id
+         WHERE rs.rating_id = ?
+         ORDER BY d.position, d.id",
+        rating_id,
+    )
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|db_score| DimensionScore {
+        dimension_id: db_score.dimension_id,
+        name: db_score.name,
+        score: db_score.score,
+    })
+    .collect();
+
+    Ok(scores)
+}
+
+pub async fn get_dimension_averages_per_period(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    restaurant_id: i32,
+    period: &Period,
+) -> Result<Vec<DimensionAverage>> {
+    let date_range = period.to_date_range();
+
+    let dimension_averages = sqlx::query_as!(
+        DbDimensionAverage,
+        "SELECT r.restaurant_id, d.id as dimension_id, d.name, d.weight,
+             IFNULL(AVG(rs.score), 0) as average_score
+         FROM rating_scores rs
+         JOIN ratings r ON r.id = rs.rating_id
+         JOIN rating_dimensions d ON d.id = rs.dimension_id
+         WHERE r.group_id = ? AND r.restaurant_id = ? AND r.created_at >= ? AND DATE(r.created_at) <= ?
+         GROUP BY r.restaurant_id, d.id, d.name, d.weight, d.position
+         ORDER BY d.position, d.id",
+        group_id,
+        restaurant_id,
+        date_range.0,
+        date_range.1
+    )
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|db_average| DimensionAverage {
+        dimension_id: db_average.dimension_id,
+        name: db_average.name,
+        weight: db_average.weight,
+        average_score: db_average.average_score,
+    })
+    .collect();
+
+    Ok(dimension_averages)
+}
+
+// NOTE: Venues
+
+pub async fn get_venues(conn: &mut MySqlConnection) -> Result<Vec<Venue>> {
+    let venues = sqlx::query_as!(
+        Venue,
+        "SELECT id, name, cuisine FROM venues ORDER BY name, id"
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(venues)
+}
+
+pub async fn get_venue(conn: &mut MySqlConnection, venue_id: i32) -> Result<Option<Venue>> {
+    let venue = sqlx::query_as!(
+        Venue,
+        "SELECT id, name, cuisine FROM venues WHERE id = ?",
+        venue_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(venue)
+}
+
+pub async fn get_venue_by_name(conn: &mut MySqlConnection, name: &str) -> Result<Option<Venue>> {
+    let venue = sqlx::query_as!(
+        Venue,
+        "SELECT id, name, cuisine FROM venues WHERE name = ?",
+        name
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(venue)
+}
+
+pub async fn create_venue(conn: &mut MySqlConnection, venue: &NewVenue) -> Result<Venue> {
+    let mut tx = conn.begin().await?;
+
+    let result = match sqlx::query!(
+        "INSERT INTO venues (name, cuisine) VALUES (?, ?)",
+        venue.name,
+        venue.cuisine
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create venue: {err}"));
+        }
+    };
+
+    let venue = match get_venue(&mut tx, result.last_insert_id() as i32).await {
+        Ok(Some(venue)) => venue,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Venue not found"));
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get venue: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(venue)
+}
+
+/// Restaurants that don't override the cuisine follow the venue when it changes, their names
+/// are their group's own and stay as they are.
+pub async fn update_venue(
+    conn: &mut MySqlConnection,
+    venue_id: i32,
+    venue: &NewVenue,
+) -> Result<MySqlQueryResult> {
+    let mut tx = conn.begin().await?;
+
+    // NOTE: Overrides that match the new cuisine are dropped, so they follow the venue from now on
+    if let Err(err) = sqlx::query!(
+        "UPDATE restaurants SET cuisine = NULL WHERE venue_id = ? AND cuisine = ?",
+        venue_id,
+        venue.cuisine
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update venue restaurants: {err}"));
+    }
+
+    let result = match sqlx::query!(
+        "UPDATE venues SET name = ?, cuisine = ? WHERE id = ?",
+        venue.name,
+        venue.cuisine,
+        venue_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not update venue: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+// NOTE: Restaurants
+
+/// Adds a venue to the restaurants of a group, with the profile of `restaurant`. The cuisine is
+/// only stored when it differs from the one of the venue, otherwise the restaurant keeps
+/// following the catalogue.
+async fn insert_restaurant(
+    conn: &mut MySqlConnection,
+    venue_id: i32,
+    cuisine: Option<&str>,
+    restaurant: &Restaurant,
+) -> Result<i32> {
+    let result = sqlx::query!(
+        "INSERT INTO restaurants (venue_id, group_id, restaurant_code, cuisine, notes, address,
+            latitude, longitude, price_tier, website, menu_url, phone, opening_hours)
+         SELECT id, ?, ?, NULLIF(?, cuisine), ?, ?, ?, ?, ?, ?, ?, ?, ? FROM venues WHERE id = ?",
+        restaurant.group_id,
+        restaurant.restaurant_code,
+        cuisine,
+        restaurant.notes,
+        restaurant.address,
+        restaurant.latitude,
+        restaurant.longitude,
+        restaurant.price_tier,
+        restaurant.website,
+        restaurant.menu_url,
+        restaurant.phone,
+        restaurant.opening_hours,
+        venue_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() != 1 {
+        return Err(anyhow!("Venue not found"));
+    }
+
+    let restaurant_id = result.last_insert_id() as i32;
+    set_restaurant_tags(conn, restaurant_id, &restaurant.tags).await?;
+
+    Ok(restaurant_id)
+}
+
+async fn set_restaurant_tags(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    tags: &[String],
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM restaurant_tags WHERE restaurant_id = ?",
+        restaurant_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    for tag in tags {
+        sqlx::query!(
+            "INSERT INTO restaurant_tags (restaurant_id, tag) VALUES (?, ?)",
+            restaurant_id,
+            tag
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// The tags of every restaurant of a group, by restaurant.
+async fn get_restaurant_tags(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<HashMap<i32, Vec<String>>> {
+    let rows = sqlx::query!(
+        "SELECT t.restaurant_id, t.tag
+         FROM restaurant_tags t
+         INNER JOIN restaurants r ON r.id = t.restaurant_id
+         WHERE r.group_id = ?
+         ORDER BY t.restaurant_id, t.tag",
+        group_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+    for row in rows {
+        tags.entry(row.restaurant_id).or_default().push(row.tag);
+    }
+
+    Ok(tags)
+}
+
+/// Creates a restaurant adopted from the venue of the same name, which is added to the
+/// catalogue if it isn't there yet.
+pub async fn create_restaurant(
+    conn: &mut MySqlConnection,
+    restaurant: &Restaurant,
+) -> Result<Restaurant> {
+    let mut tx = conn.begin().await?;
+
+    // NOTE: On a duplicate name LAST_INSERT_ID(id) makes the existing venue the inserted one
+    let venue_id = match sqlx::query!(
+        "INSERT INTO venues (name, cuisine) VALUES (?, ?)
+         ON DUPLICATE KEY UPDATE id = LAST_INSERT_ID(id)",
+        restaurant.restaurant_code,
+        restaurant.cuisine
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result.last_insert_id() as i32,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create venue: {err}"));
+        }
+    };
+
+    let restaurant_id =
+        match insert_restaurant(&mut tx, venue_id, Some(&restaurant.cuisine), restaurant).await {
+            Ok(restaurant_id) => restaurant_id,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not create restaurant: {err}"));
+            }
+        };
+
+    let db_restaurant = match get_restaurant(&mut tx, restaurant_id).await {
+        Ok(restaurant) => restaurant,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db restaurant: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(db_restaurant)
+}
+
+pub async fn adopt_venue(
+    conn: &mut MySqlConnection,
+    venue: &Venue,
+    adopt: &AdoptVenue,
+) -> Result<Restaurant> {
+    let mut tx = conn.begin().await?;
+
+    let restaurant = Restaurant {
+        restaurant_code: venue.name.clone(),
+        group_id: adopt.group_id.clone(),
+        notes: adopt.notes.clone(),
+        ..Default::default()
+    };
+    let restaurant_id =
+        match insert_restaurant(&mut tx, venue.id, adopt.cuisine.as_deref(), &restaurant).await {
+            Ok(restaurant_id) => restaurant_id,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not adopt venue: {err}"));
+            }
+        };
+
+    let restaurant = match get_restaurant(&mut tx, restaurant_id).await {
+        Ok(restaurant) => restaurant,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get db restaurant: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(restaurant)
+}
+
+/// Copies restaurants of one group to another, with their names, cuisine overrides, profiles and
+/// notes. Ratings, visits and everything else stay with the source group. Archived restaurants
+/// and those whose venue or name the target group already has are skipped, the copies are
+/// returned.
+pub async fn import_restaurants(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    import: &ImportRestaurants,
+) -> Result<Vec<Restaurant>> {
+    let mut tx = conn.begin().await?;
+
+    let source_restaurants = match get_restaurants(&mut tx, &import.source_group_id).await {
+        Ok(restaurants) => restaurants,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get restaurants to import: {err}"));
+        }
+    };
+
+    let mut restaurants = match get_restaurants(&mut tx, group_id).await {
+        Ok(restaurants) => restaurants,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(err);
+        }
+    };
+
+    let mut imported_restaurants = Vec::new();
+
+    for source_restaurant in source_restaurants {
+        if !source_restaurant.active {
+            continue;
+        }
+        if import
+            .restaurant_ids
+            .as_ref()
+            .is_some_and(|restaurant_ids| !restaurant_ids.contains(&source_restaurant.id))
+        {
+            continue;
+        }
+        if restaurants.iter().any(|restaurant| {
+            restaurant.venue_id == source_restaurant.venue_id
+                || restaurant
+                    .restaurant_code
+                    .eq_ignore_ascii_case(&source_restaurant.restaurant_code)
+        }) {
+            continue;
+        }
+
+        // NOTE: The cuisine is the effective one, it's only kept if it overrides the venue's
+        let restaurant = Restaurant {
+            group_id: group_id.to_string(),
+            ..source_restaurant
+        };
+        let restaurant_id = match insert_restaurant(
+            &mut tx,
+            restaurant.venue_id,
+            Some(&restaurant.cuisine),
+            &restaurant,
+        )
+        .await
+        {
+            Ok(restaurant_id) => restaurant_id,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not import restaurant: {err}"));
+            }
+        };
+
+        let restaurant = match get_restaurant(&mut tx, restaurant_id).await {
+            Ok(restaurant) => restaurant,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get db restaurant: {err}"));
+            }
+        };
+
+        restaurants.push(restaurant.clone());
+        imported_restaurants.push(restaurant);
+    }
+
+    tx.commit().await?;
+
+    Ok(imported_restaurants)
+}
+
+pub async fn get_restaurants(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Vec<Restaurant>> {
+    let db_restaurants = sqlx::query_as!(
+        DbRestaurant,
+        r#"SELECT r.id, r.venue_id, r.restaurant_code, r.group_id,
+            COALESCE(r.cuisine, v.cuisine) AS "cuisine!", r.notes, r.address, r.latitude,
+            r.longitude, r.price_tier, r.website, r.menu_url, r.phone, r.opening_hours,
+            r.active AS "active: bool"
+         FROM restaurants r
+         INNER JOIN venues v ON v.id = r.venue_id
+         WHERE r.group_id = ? ORDER BY r.id"#,
+        group_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut tags = get_restaurant_tags(conn, group_id).await?;
+
+    let restaurants = db_restaurants
+        .into_iter()
+        .map(|db_restaurant| {
+            let tags = tags.remove(&db_restaurant.id).unwrap_or_default();
+            Restaurant::from_db(db_restaurant, tags)
+        })
+        .collect();
+
+    Ok(restaurants)
+}
+
+/// The restaurants of a group within the radius of a search, nearest first. Candidates are
+/// looked up within its bounding box, restaurants without coordinates are never found.
+pub async fn get_restaurants_near(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    near: &GeoSearch,
+) -> Result<Vec<NearbyRestaurant>> {
+    let ((min_latitude, max_latitude), (min_longitude, max_longitude)) = near.bounding_box();
+
+    let db_restaurants = sqlx::query_as!(
+        DbRestaurant,
+        r#"SELECT r.id, r.venue_id, r.restaurant_code, r.group_id,
+            COALESCE(r.cuisine, v.cuisine) AS "cuisine!", r.notes, r.address, r.latitude,
+            r.longitude, r.price_tier, r.website, r.menu_url, r.phone, r.opening_hours,
+            r.active AS "active: bool"
+         FROM restaurants r
+         INNER JOIN venues v ON v.id = r.venue_id
+         WHERE r.group_id = ?
+            AND r.latitude BETWEEN ? AND ?
+            AND r.longitude BETWEEN ? AND ?"#,
+        group_id,
+        min_latitude,
+        max_latitude,
+        min_longitude,
+        max_longitude
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut tags = get_restaurant_tags(conn, group_id).await?;
+
+    let mut restaurants: Vec<NearbyRestaurant> = db_restaurants
+        .into_iter()
+        .filter_map(|db_restaurant| {
+            let distance_meters =
+                near.distance_meters(db_restaurant.latitude?, db_restaurant.longitude?);
+            if distance_meters > near.radius_meters {
+                return None;
+            }
+
+            let tags = tags.remove(&db_restaurant.id).unwrap_or_default();
+            Some(NearbyRestaurant {
+                restaurant: Restaurant::from_db(db_restaurant, tags),
+                distance_meters,
+            })
+        })
+        .collect();
+    restaurants.sort_by(|a, b| {
+        a.distance_meters
+            .total_cmp(&b.distance_meters)
+            .then(a.restaurant.id.cmp(&b.restaurant.id))
+    });
+
+    Ok(restaurants)
+}
+
+pub async fn get_restaurant(conn: &mut MySqlConnection, restaurant_id: i32) -> Result<Restaurant> {
+    let mut tx = Acquire::begin(conn).await?;
+
+    let query = sqlx::query_as!(
+        DbRestaurant,
+        r#"SELECT r.id, r.venue_id, r.restaurant_code, r.group_id,
+            COALESCE(r.cuisine, v.cuisine) AS "cuisine!", r.notes, r.address, r.latitude,
+            r.longitude, r.price_tier, r.website, r.menu_url, r.phone, r.opening_hours,
+            r.active AS "active: bool"
+         FROM restaurants r
+         INNER JOIN venues v ON v.id = r.venue_id
+         WHERE r.id = ?"#,
+        restaurant_id
+    );
+    let db_restaurant_result = match query.fetch_optional(&mut *tx).await {
+        Ok(query_result) => query_result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Restaurant not found: {err}"));
+        }
+    };
+
+    let Some(db_restaurant) = db_restaurant_result else {
+        return Err(anyhow!("Restaurant not found"));
+    };
+
+    let tags = match sqlx::query_scalar!(
+        "SELECT tag FROM restaurant_tags WHERE restaurant_id = ? ORDER BY tag",
+        restaurant_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    {
+        Ok(tags) => tags,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get restaurant tags: {err}"));
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(Restaurant::from_db(db_restaurant, tags))
+}
+
+pub async fn update_restaurant(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    restaurant: &Restaurant,
+) -> Result<MySqlQueryResult> {
+    let mut tx = conn.begin().await?;
+
+    // NOTE: Setting the cuisine of the venue again drops the override
+    let result = match sqlx::query!(
+        "UPDATE restaurants r
+         INNER JOIN venues v ON v.id = r.venue_id
+         SET r.cuisine = NULLIF(?, v.cuisine), r.restaurant_code = ?, r.notes = ?, r.address = ?,
+            r.latitude = ?, r.longitude = ?, r.price_tier = ?, r.website = ?, r.menu_url = ?,
+            r.phone = ?, r.opening_hours = ?
+         WHERE r.id = ? AND r.group_id = ?;",
+        restaurant.cuisine,
+        restaurant.restaurant_code,
+        restaurant.notes,
+        restaurant.address,
+        restaurant.latitude,
+        restaurant.longitude,
+        restaurant.price_tier,
+        restaurant.website,
+        restaurant.menu_url,
+        restaurant.phone,
+        restaurant.opening_hours,
+        restaurant_id,
+        restaurant.group_id,
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not update restaurant: {err}"));
+        }
+    };
+
+    // NOTE: Tags of restaurants of another group are left alone
+    let in_group = sqlx::query_scalar!(
+        "SELECT id FROM restaurants WHERE id = ? AND group_id = ?",
+        restaurant_id,
+        restaurant.group_id
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+    match in_group {
+        Ok(Some(_)) => {
+            if let Err(err) = set_restaurant_tags(&mut tx, restaurant_id, &restaurant.tags).await {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not update restaurant tags: {err}"));
+            }
+        }
+        Ok(None) => {}
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not update restaurant: {err}"));
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+pub async fn update_restaurant_active(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    group_id: &str,
+    active: bool,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "UPDATE restaurants SET active = ? WHERE id = ? AND group_id = ?",
+        active,
+        restaurant_id,
+        group_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+/// Whether a restaurant has been rated or visited, which deleting it would wipe out.
+pub async fn restaurant_has_history(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+) -> Result<bool> {
+    let has_history = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM ratings WHERE restaurant_id = ?)
+            OR EXISTS(SELECT 1 FROM visits WHERE restaurant_id = ?)",
+        restaurant_id,
+        restaurant_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(has_history != 0)
+}
+
+pub async fn delete_restaurant(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    group_id: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "DELETE FROM restaurants WHERE id = ? AND group_id = ?",
+        restaurant_id,
+        group_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+/// Whether enough of the members expected to rate a restaurant this period have done so,
+/// according to the quorum of the group. Restaurants visited this period are rated by the
+/// attendees of those visits, otherwise by every member who isn't marked absent.
+async fn restaurant_rating_complete(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    group_id: &str,
+    period: &Period,
+) -> Result<bool> {
+    let date_range = period.to_date_range();
+
+    let visit_ids = sqlx::query_scalar!(
+        "SELECT id FROM visits
+         WHERE group_id = ? AND restaurant_id = ? AND visit_date >= ? AND visit_date <= ?",
+        group_id,
+        restaurant_id,
+        date_range.0,
+        date_range.1
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !visit_ids.is_empty() {
+        for visit_id in visit_ids {
+            if !is_visit_rating_complete(conn, visit_id).await? {
+                return Ok(false);
+            }
+        }
+
+        return Ok(true);
+    }
+
+    // NOTE: Members marked absent for this restaurant or period aren't expected to rate it
+    let completeness = sqlx::query!(
+        "SELECT COUNT(*) AS expected, COUNT(rated.user_id) AS rated
          FROM group_memberships gm
-         JOIN groups g on g.id = gm.group_id
-         JOIN push_subscriptions ps ON ps.user_id = gm.user_id
-         WHERE gm.group_id = ?",
+         LEFT JOIN (
+            SELECT DISTINCT user_id FROM ratings
+            WHERE group_id = ? AND restaurant_id = ? AND created_at >= ? AND DATE(created_at) <= ?
+         ) rated ON rated.user_id = gm.user_id
+         WHERE gm.group_id = ? AND NOT EXISTS (
+            SELECT 1 FROM member_absences ma
+            WHERE ma.group_id = gm.group_id AND ma.user_id = gm.user_id
+            AND (ma.restaurant_id IS NULL OR ma.restaurant_id = ?)
+            AND (ma.start_date IS NULL OR ma.start_date <= ?)
+            AND (ma.end_date IS NULL OR ma.end_date >= ?)
+         )",
         group_id,
-    );
+        restaurant_id,
+        date_range.0,
+        date_range.1,
+        group_id,
+        restaurant_id,
+        date_range.1,
+        date_range.0
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let rating_quorum = get_group(conn, group_id).await?.rating_quorum;
+
+    Ok(is_quorum_reached(
+        rating_quorum,
+        completeness.rated,
+        completeness.expected,
+    ))
+}
 
+pub async fn is_restaurant_rating_complete(
+    pool: &MySqlPool,
+    push_client: Option<&PushClient>,
+    restaurant_id: i32,
+    group_id: &str,
+) -> Result<bool> {
     let mut conn = get_connection(pool).await.unwrap();
-    let push_subscriptions = match push_subscriptions_query.fetch_all(&mut *conn).await {
-        Ok(result) => result,
-        Err(err) => return Err(anyhow!("Could not get push_subscriptions: {err}")),
+    let mut tx = conn.begin().await?;
+
+    let period = match get_current_period(&mut tx, group_id).await {
+        Ok(Some(period)) => period,
+        Ok(None) => {
+            tx.commit().await?;
+            return Ok(false);
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get current period: {err}"));
+        }
     };
 
-    let futures = push_subscriptions.into_iter().map(|push_subscription| {
-        let push_client = push_client.clone();
-        let subscription_info = SubscriptionInfo::new(
-            push_subscription.endpoint,
-            push_subscription.p256dh,
-            push_subscription.auth,
-        );
-        let body = format!("{}: {}", push_subscription.group_name, message);
+    let is_complete =
+        match restaurant_rating_complete(&mut tx, restaurant_id, group_id, &period).await {
+            Ok(is_complete) => is_complete,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!(
+                    "Could not check if restaurant rating is complete: {err}"
+                ));
+            }
+        };
 
-        let new_pool = pool.clone();
-        actix_web::rt::spawn(async move {
-            let mut conn = get_connection(&new_pool).await.unwrap();
+    // NOTE: Archived restaurants aren't rated anymore, so the group isn't notified about them
+    let is_active = match sqlx::query_scalar!(
+        r#"SELECT active AS "active: bool" FROM restaurants WHERE id = ?"#,
+        restaurant_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(is_active) => is_active.unwrap_or(false),
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get restaurant: {err}"));
+        }
+    };
 
-            if let Err(err) =
-                send_notification(&mut conn, &push_client, &subscription_info, &body).await
-            {
-                eprintln!(
-                    "ERROR: failed sending notification to {}: {err}",
-                    subscription_info.endpoint
-                );
+    if is_complete {
+        if let Some(push_client) = push_client.filter(|_| is_active) {
+            let notification_sent =
+                match rating_notification_sent(&mut tx, restaurant_id, group_id).await {
+                    Ok(notification_sent) => notification_sent,
+                    Err(err) => {
+                        tx.rollback().await?;
+                        return Err(err);
+                    }
+                };
+
+            if !notification_sent {
+                if let Err(err) = create_rating_notification(&mut tx, restaurant_id, group_id).await
+                {
+                    tx.rollback().await?;
+                    return Err(err);
+                }
+
+                tx.commit().await?;
+
+                let push_client = push_client.clone();
+                let group_id = group_id.to_string();
+                let restaurant_id = restaurant_id.to_string();
+                let new_pool = pool.clone();
+
+                actix_web::rt::spawn(async move {
+                    if let Err(err) = send_notification_to_group(
+                        &push_client,
+                        &new_pool,
+                        &group_id,
+                        &format!("{restaurant_id} ratings are complete!"),
+                    )
+                    .await
+                    {
+                        eprintln!(
+                            "ERROR: Failed to send notification to group {group_id} for restaurant {restaurant_id}: {err}"
+                        );
+                    }
+                });
+            } else {
+                tx.commit().await?;
             }
-        })
-    });
+        } else {
+            tx.commit().await?;
+        }
+    } else {
+        tx.commit().await?;
+    }
 
-    futures::future::join_all(futures).await;
+    Ok(is_complete)
+}
 
-    Ok(())
+/// Whether the individual ratings of a restaurant are still hidden from the group,
+/// which is the case for blind groups until every member has rated it this period.
+/// Archived restaurants can't be rated anymore, so their ratings are revealed.
+pub async fn are_ratings_hidden(
+    pool: &MySqlPool,
+    restaurant_id: i32,
+    group_id: &str,
+) -> Result<bool> {
+    let mut conn = get_connection(pool).await.unwrap();
+
+    if !get_group(&mut conn, group_id).await?.blind_ratings {
+        return Ok(false);
+    }
+
+    let restaurant_active = sqlx::query_scalar!(
+        r#"SELECT active AS "active: bool" FROM restaurants WHERE id = ? AND group_id = ?"#,
+        restaurant_id,
+        group_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    if restaurant_active == Some(false) {
+        return Ok(false);
+    }
+
+    Ok(!is_restaurant_rating_complete(pool, None, restaurant_id, group_id).await?)
 }
 
-pub async fn send_notification(
-    conn: &mut MySqlConnection,
-    push_client: &PushClient,
-    subscription_info: &SubscriptionInfo,
-    body: &str,
-) -> Result<()> {
-    let vapid_builder = VapidSignatureBuilder::from_base64_no_sub(&push_client.vapid_private_key)?;
+pub async fn get_avg_rating(
+    pool: &MySqlPool,
+    restaurant_id: i32,
+    group_id: &str,
+) -> Result<Option<f64>> {
+    if is_restaurant_rating_complete(pool, None, restaurant_id, group_id).await? {
+        let mut conn = get_connection(pool).await.unwrap();
 
-    let vapid_signature = vapid_builder
-        .clone()
-        .add_sub_info(subscription_info)
-        .build()?;
+        let date_range = match get_current_period(&mut conn, group_id).await? {
+            Some(period) => period.to_date_range(),
+            None => return Ok(None),
+        };
 
-    let mut push_builder = WebPushMessageBuilder::new(subscription_info);
+        let avg_rating: Option<f64> = sqlx::query_scalar!(
+            "SELECT AVG(score)
+             FROM ratings
+             WHERE group_id = ? and restaurant_id = ? AND created_at >= ? AND DATE(created_at) <= ?",
+            group_id,
+            restaurant_id,
+            date_range.0,
+            date_range.1
+        )
+        .fetch_one(&mut *conn)
+        .await?;
 
-    push_builder.set_vapid_signature(vapid_signature.clone());
+        Ok(avg_rating)
+    } else {
+        Ok(None)
+    }
+}
 
-    let payload = json!({
-        "body": body
-    })
-    .to_string();
-    push_builder.set_payload(web_push::ContentEncoding::Aes128Gcm, payload.as_bytes());
+pub async fn get_restaurants_with_avg_rating(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+) -> Result<Vec<(Restaurant, f64, Vec<DimensionAverage>)>> {
+    let mut tx = Acquire::begin(conn).await?;
 
-    let response = push_client.client.send(push_builder.build()?).await;
+    let period = match get_current_period(&mut tx, group_id).await {
+        Ok(Some(period)) => period,
+        Ok(None) => {
+            let restaurants = match get_restaurants(&mut tx, group_id).await {
+                Ok(restaurants) => restaurants,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(err);
+                }
+            };
 
-    match response {
-        Ok(_) => Ok(()),
+            tx.commit().await?;
+
+            return Ok(restaurants
+                .into_iter()
+                .filter(|restaurant| restaurant.active)
+                .map(|restaurant| (restaurant, 0.0, Vec::new()))
+                .collect());
+        }
         Err(err) => {
-            let should_remove = matches!(
-                err,
-                WebPushError::EndpointNotValid(_) | WebPushError::EndpointNotFound(_)
-            );
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get current period: {err}"));
+        }
+    };
+    let date_range = period.to_date_range();
 
-            if should_remove {
-                delete_push_subscription(conn, &subscription_info.endpoint).await?;
+    let db_restaurants_with_avg_rating_result = sqlx::query!(
+        r#"SELECT r.id, r.venue_id, r.restaurant_code, r.group_id,
+            COALESCE(r.cuisine, v.cuisine) AS "cuisine!", r.notes, r.address, r.latitude,
+            r.longitude, r.price_tier, r.website, r.menu_url, r.phone, r.opening_hours,
+            r.active AS "active: bool", AVG(ra.score) AS avg_rating,
+            COUNT(ra.score) AS num_ratings
+         FROM restaurants r
+         INNER JOIN venues v ON v.id = r.venue_id
+         LEFT JOIN ratings ra ON ra.group_id = ? AND ra.restaurant_id = r.id AND ra.created_at >= ? AND DATE(ra.created_at) <= ?
+         WHERE r.group_id = ? AND r.active
+         GROUP BY r.id"#,
+        group_id,
+        date_range.0,
+        date_range.1,
+        group_id
+    )
+    .fetch_all(&mut *tx)
+    .await;
+
+    let rows = match db_restaurants_with_avg_rating_result {
+        Ok(rows) => rows,
+        Err(e) => {
+            tx.rollback().await?;
+            return Err(e.into());
+        }
+    };
+
+    let mut db_restaurants_with_avg_rating = Vec::new();
+
+    for row in rows {
+        // NOTE: The average is only revealed once everyone expected to rate the restaurant has
+        let is_complete = match restaurant_rating_complete(&mut tx, row.id, group_id, &period).await
+        {
+            Ok(is_complete) => is_complete,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
             }
+        };
+
+        db_restaurants_with_avg_rating.push((
+            DbRestaurant {
+                id: row.id,
+                venue_id: row.venue_id,
+                restaurant_code: row.restaurant_code,
+                group_id: row.group_id,
+                cuisine: row.cuisine,
+                notes: row.notes,
+                address: row.address,
+                latitude: row.latitude,
+                longitude: row.longitude,
+                price_tier: row.price_tier,
+                website: row.website,
+                menu_url: row.menu_url,
+                phone: row.phone,
+                opening_hours: row.opening_hours,
+                active: row.active,
+            },
+            row.avg_rating.filter(|_| is_complete),
+            row.num_ratings,
+        ));
+    }
 
-            Err(anyhow!("Error sending notification: {err}"))
+    db_restaurants_with_avg_rating.sort_by(|a, b| {
+        a.1.is_none()
+            .cmp(&b.1.is_none())
+            .then(b.2.cmp(&a.2))
+            .then(b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal))
+            .then(a.0.id.cmp(&b.0.id))
+    });
+
+    let mut tags = match get_restaurant_tags(&mut tx, group_id).await {
+        Ok(tags) => tags,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get restaurant tags: {err}"));
         }
+    };
+
+    let mut results = Vec::new();
+
+    for (db_restaurant, avg_rating, _) in db_restaurants_with_avg_rating {
+        let tags = tags.remove(&db_restaurant.id).unwrap_or_default();
+        // NOTE: Like the overall score, dimension averages are only revealed once the round is complete
+        let dimension_averages = match avg_rating {
+            Some(_) => match get_dimension_averages_per_period(
+                &mut tx,
+                group_id,
+                db_restaurant.id,
+                &period,
+            )
+            .await
+            {
+                Ok(dimension_averages) => dimension_averages,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(err);
+                }
+            },
+            None => Vec::new(),
+        };
+
+        results.push((
+            Restaurant::from_db(db_restaurant, tags),
+            avg_rating.unwrap_or(0.0),
+            dimension_averages,
+        ));
     }
+
+    tx.commit().await?;
+
+    Ok(results)
 }
 
-// NOTE: Groups
+// NOTE: Visits
 
-pub async fn create_group(
+pub async fn create_visit(
     conn: &mut MySqlConnection,
-    new_group: &NewGroup,
-) -> Result<GroupMembership> {
+    group_id: &str,
+    restaurant_id: i32,
+    created_by: &str,
+    new_visit: &NewVisit,
+) -> Result<Visit> {
     let mut tx = conn.begin().await?;
 
-    let id = Uuid::new_v4().to_string();
-    let created_at = Utc::now().naive_utc();
-    let updated_at = created_at;
-
-    let group_query = sqlx::query_as!(
-        Group,
-        "INSERT INTO groups (id, name, description, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
-        id,
-        new_group.name,
-        new_group.description,
-        created_at,
-        updated_at,
-    );
-
-    let result = match group_query.execute(&mut *tx).await {
-        Ok(query_result) => query_result,
-        Err(err) => {
+    let restaurant_active = sqlx::query_scalar!(
+        r#"SELECT active AS "active: bool" FROM restaurants WHERE id = ? AND group_id = ?"#,
+        restaurant_id,
+        group_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    match restaurant_active {
+        Some(true) => {}
+        Some(false) => {
             tx.rollback().await?;
-            return Err(anyhow!("Could not create group: {err}"));
+            return Err(anyhow!("Restaurant is archived"));
+        }
+        None => {
+            tx.rollback().await?;
+            return Err(anyhow!("Restaurant does not exist in this group"));
         }
+    }
+
+    // NOTE: Whoever logs the visit attended it, unless they list the attendees explicitly
+    let mut attendee_ids = match new_visit.attendee_ids.is_empty() {
+        true => vec![created_by.to_string()],
+        false => new_visit.attendee_ids.clone(),
     };
+    attendee_ids.sort();
+    attendee_ids.dedup();
 
-    if result.rows_affected() == 1 {
-        let db_group = match get_group(&mut tx, &id).await {
-            Ok(group) => group,
+    for attendee_id in &attendee_ids {
+        match check_group_membership_exists(&mut tx, attendee_id, group_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Attendee {attendee_id} does not belong to group"));
+            }
             Err(err) => {
                 tx.rollback().await?;
-                return Err(anyhow!("Could not get db group: {err}"));
+                return Err(err);
             }
-        };
+        }
+    }
 
-        let db_group_membership = match create_group_membership(
-            &mut tx,
-            &NewGroupMembership {
-                group_id: db_group.id.clone(),
-                user_id: new_group.creator_id.to_owned(),
-                role: Role::Admin,
-            },
+    let result = match sqlx::query!(
+        "INSERT INTO visits (group_id, restaurant_id, visit_date, bill_total, created_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        group_id,
+        restaurant_id,
+        new_visit.visit_date,
+        new_visit.bill_total,
+        created_by,
+        Utc::now().naive_utc()
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not create visit: {err}"));
+        }
+    };
+    let visit_id = result.last_insert_id() as i32;
+
+    for attendee_id in &attendee_ids {
+        if let Err(err) = sqlx::query!(
+            "INSERT INTO visit_attendees (visit_id, user_id) VALUES (?, ?)",
+            visit_id,
+            attendee_id
         )
+        .execute(&mut *tx)
         .await
         {
-            Ok(mut group_membership) => {
-                group_membership.group = db_group;
-                group_membership
-            }
-            Err(err) => {
-                tx.rollback().await?;
-                return Err(anyhow!("Could not get db group membership: {err}"));
-            }
-        };
+            tx.rollback().await?;
+            return Err(anyhow!("Could not add visit attendee: {err}"));
+        }
+    }
 
-        tx.commit().await?;
+    let visit = match get_visit(&mut tx, visit_id).await {
+        Ok(visit) => visit,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(err);
+        }
+    };
 
-        Ok(db_group_membership)
-    } else {
-        Err(anyhow::anyhow!("Failed to create group"))
-    }
+    tx.commit().await?;
+
+    Ok(visit)
 }
 
-pub async fn get_group(conn: &mut MySqlConnection, id: &str) -> Result<Group> {
-    let query = sqlx::query_as!(
-        Group,
-        "SELECT id, name, description, created_at, updated_at
-         FROM groups
+pub async fn get_visit(conn: &mut MySqlConnection, visit_id: i32) -> Result<Visit> {
+    let db_visit = sqlx::query_as!(
+        DbVisit,
+        "SELECT id, group_id, restaurant_id, visit_date, bill_total, created_by, created_at
+         FROM visits
          WHERE id = ?",
-        id,
-    );
-    let group = query.fetch_optional(conn).await?;
+        visit_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| anyhow!("Visit not found"))?;
 
-    match group {
-        Some(group) => Ok(group),
-        None => Err(anyhow!("Group not found")),
-    }
+    with_visit_attendees(conn, db_visit).await
 }
 
-pub async fn get_group_by_membership(
+pub async fn get_visits(
     conn: &mut MySqlConnection,
-    membership_id: &i32,
-) -> Result<Group> {
-    let query = sqlx::query_as!(
-        Group,
-        "SELECT g.id, g.name, g.description, g.created_at, g.updated_at
-         FROM groups g
-         JOIN group_memberships gm ON gm.group_id = g.id
-         WHERE gm.id = ?",
-        membership_id,
-    );
-    let group = query.fetch_optional(conn).await?;
+    group_id: &str,
+    restaurant_id: i32,
+) -> Result<Vec<Visit>> {
+    let db_visits = sqlx::query_as!(
+        DbVisit,
+        "SELECT id, group_id, restaurant_id, visit_date, bill_total, created_by, created_at
+         FROM visits
+         WHERE group_id = ? AND restaurant_id = ?
+         ORDER BY visit_date DESC, id DESC",
+        group_id,
+        restaurant_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
 
-    match group {
-        Some(group) => Ok(group),
-        None => Err(anyhow!("Group not found")),
+    let mut visits = Vec::new();
+    for db_visit in db_visits {
+        visits.push(with_visit_attendees(conn, db_visit).await?);
     }
+
+    Ok(visits)
 }
 
-pub async fn delete_group(conn: &mut MySqlConnection, id: &str) -> Result<MySqlQueryResult> {
-    let result = sqlx::query!("DELETE FROM groups WHERE id = ?", id)
+async fn with_visit_attendees(conn: &mut MySqlConnection, db_visit: DbVisit) -> Result<Visit> {
+    let attendees = sqlx::query_scalar!(
+        "SELECT user_id FROM visit_attendees WHERE visit_id = ? ORDER BY user_id",
+        db_visit.id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let is_complete = is_visit_rating_complete(conn, db_visit.id).await?;
+
+    Ok(Visit::from_db(db_visit, attendees, is_complete))
+}
+
+/// A visit is completely rated once the quorum of its attendees has rated it.
+pub async fn is_visit_rating_complete(conn: &mut MySqlConnection, visit_id: i32) -> Result<bool> {
+    let completeness = sqlx::query!(
+        "SELECT COUNT(*) AS expected, COUNT(r.id) AS rated, g.rating_quorum
+         FROM visit_attendees va
+         JOIN visits v ON v.id = va.visit_id
+         JOIN groups g ON g.id = v.group_id
+         LEFT JOIN ratings r ON r.visit_id = va.visit_id AND r.user_id = va.user_id
+         WHERE va.visit_id = ?
+         GROUP BY g.rating_quorum",
+        visit_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(completeness.is_some_and(|completeness| {
+        is_quorum_reached(
+            completeness.rating_quorum,
+            completeness.rated,
+            completeness.expected,
+        )
+    }))
+}
+
+pub async fn is_visit_rated_by_user(
+    conn: &mut MySqlConnection,
+    visit_id: i32,
+    user_id: &str,
+) -> Result<bool> {
+    let rated = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM ratings WHERE visit_id = ? AND user_id = ?)",
+        visit_id,
+        user_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(rated == 1)
+}
+
+pub async fn delete_visit(conn: &mut MySqlConnection, visit_id: i32) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM visits WHERE id = ?", visit_id)
         .execute(conn)
         .await?;
 
     Ok(result)
 }
 
-pub async fn create_group_membership(
-    conn: &mut MySqlConnection,
-    new_group_membership: &NewGroupMembership,
-) -> Result<GroupMembership> {
+// NOTE: Ratings
+
+pub async fn create_rating(conn: &mut MySqlConnection, rating: &NewRating) -> Result<Rating> {
     let mut tx = conn.begin().await?;
 
     let created_at = Utc::now().naive_utc();
     let updated_at = created_at;
 
-    if check_group_membership_exists(
-        &mut tx,
-        &new_group_membership.user_id,
-        &new_group_membership.group_id,
-    )
-    .await?
-    {
+    if !check_group_membership_exists(&mut tx, &rating.user_id, &rating.group_id).await? {
         tx.rollback().await?;
-        return Err(anyhow!("Group membership already exists"));
+        return Err(anyhow!("User does not belong to group"));
     }
 
-    let membership_query = sqlx::query_as!(
-        GroupMembership,
-        "INSERT INTO group_memberships (group_id, user_id, role, created_at, updated_at)
-         VALUES (?, ?, ?, ?, ?)",
-        new_group_membership.group_id,
-        new_group_membership.user_id,
-        new_group_membership.role,
-        created_at,
-        updated_at
-    );
+    let restaurant_active = sqlx::query_scalar!(
+        r#"SELECT active AS "active: bool" FROM restaurants WHERE id = ? AND group_id = ?"#,
+        rating.restaurant_id,
+        rating.group_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    match restaurant_active {
+        Some(true) => {}
+        Some(false) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Restaurant is archived"));
+        }
+        None => {
+            tx.rollback().await?;
+            return Err(anyhow!("Restaurant does not exist in this group"));
+        }
+    }
 
-    let result = match membership_query.execute(&mut *tx).await {
-        Ok(query_result) => query_result,
+    if let Some(visit_id) = rating.visit_id {
+        let is_attendee = sqlx::query_scalar!(
+            "SELECT EXISTS(
+                SELECT 1 FROM visits v
+                JOIN visit_attendees va ON va.visit_id = v.id
+                WHERE v.id = ? AND v.group_id = ? AND v.restaurant_id = ? AND va.user_id = ?
+            )",
+            visit_id,
+            rating.group_id,
+            rating.restaurant_id,
+            rating.user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if is_attendee == 0 {
+            tx.rollback().await?;
+            return Err(anyhow!("User did not attend this visit"));
+        }
+    }
+
+    let period = match get_current_period(&mut tx, &rating.group_id).await {
+        Ok(Some(period)) => period,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Err(anyhow!("There is no active rating period for this group"));
+        }
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!("Could not create group membership: {err}"));
+            return Err(anyhow!("Could not get current period: {err}"));
         }
     };
 
-    if result.rows_affected() == 1 {
-        let membership_id = result.last_insert_id();
-
-        let db_group_membership = match get_group_membership(&mut tx, &(membership_id as i32)).await
-        {
-            Ok(group_membership) => group_membership,
-            Err(err) => {
-                tx.rollback().await?;
-                return Err(anyhow!("Could not get db group membership: {err}"));
-            }
-        };
-
-        tx.commit().await?;
-
-        Ok(db_group_membership)
-    } else {
-        Err(anyhow::anyhow!("Failed to create group membership"))
+    let rating_scale = match get_group_rating_scale(&mut tx, &rating.group_id).await {
+        Ok(rating_scale) => rating_scale,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get group rating scale: {err}"));
+        }
+    };
+    if let Err(err) = rating_scale.check_rating(rating) {
+        tx.rollback().await?;
+        return Err(anyhow!(err.to_string()));
     }
-}
 
-pub async fn check_group_membership_exists(
-    conn: &mut MySqlConnection,
-    user_id: &str,
-    group_id: &str,
-) -> Result<bool> {
-    let group_membership_exists_query = sqlx::query_scalar!(
-        "SELECT EXISTS (
-            SELECT 1
-            FROM group_memberships
-            WHERE user_id = ? AND group_id = ?
-         ) as group_membership_exists;",
-        user_id,
-        group_id
+    let (score, scores) = match get_rating_dimensions(&mut tx, &rating.group_id)
+        .await
+        .and_then(|dimensions| {
+            RatingDimension::resolve_scores(&dimensions, rating.score, &rating.scores)
+        }) {
+        Ok(resolved_scores) => resolved_scores,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(err);
+        }
+    };
+
+    let query = sqlx::query_as!(
+        Rating,
+        "INSERT INTO ratings (group_id, restaurant_id, visit_id, user_id, username, score, review, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        rating.group_id,
+        rating.restaurant_id,
+        rating.visit_id,
+        rating.user_id,
+        rating.username,
+        score,
+        rating.review(),
+        created_at,
+        updated_at
     );
-    let group_membership_exists = match group_membership_exists_query.fetch_one(&mut *conn).await {
+    let result = match query.execute(&mut *tx).await {
         Ok(query_result) => query_result,
         Err(err) => {
-            return Err(anyhow!("Could not validate group membership: {err}"));
+            tx.rollback().await?;
+            return Err(anyhow::anyhow!(err));
         }
     };
 
-    if group_membership_exists == 1 {
-        return Ok(true);
-    }
-
-    Ok(false)
-}
+    if result.rows_affected() == 1 {
+        let last_insert_id = result.last_insert_id();
 
-pub async fn get_group_membership(conn: &mut MySqlConnection, id: &i32) -> Result<GroupMembership> {
-    let mut tx = conn.begin().await?;
+        if let Err(err) = set_rating_scores(&mut tx, last_insert_id as i32, &scores).await {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not save rating scores: {err}"));
+        }
 
-    let query = sqlx::query_as!(
-        DbGroupMembership,
-        "SELECT id, group_id, user_id, role, created_at, updated_at
-         FROM group_memberships
-         WHERE id = ?",
-        id,
-    );
-    let group_membership = query.fetch_optional(&mut *tx).await?;
+        let scores = match get_rating_scores(&mut tx, last_insert_id as i32).await {
+            Ok(scores) => scores,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Could not get rating scores: {err}"));
+            }
+        };
 
-    match group_membership {
-        Some(group_membership) => {
-            let db_group = match get_group_by_membership(&mut tx, &group_membership.id).await {
-                Ok(group) => group,
-                Err(err) => {
-                    tx.rollback().await?;
-                    return Err(anyhow!("Could not get db group from membership: {err}"));
-                }
-            };
+        // Get restaurant_code from the restaurants table
+        let restaurant_code = sqlx::query_scalar!(
+            "SELECT restaurant_code FROM restaurants WHERE id = ?",
+            rating.restaurant_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or_default();
 
-            tx.commit().await?;
+        tx.commit().await?;
 
-            Ok(GroupMembership::from_db(&group_membership, &db_group))
-        }
-        None => Err(anyhow!("Group membership not found")),
+        Ok(Rating::new(
+            last_insert_id as i32,
+            rating.group_id.clone(),
+            rating.restaurant_id,
+            restaurant_code,
+            rating.user_id.clone(),
+            rating.username.clone(),
+            score,
+            created_at,
+            updated_at,
+            None,
+            rating.review().map(str::to_owned),
+            rating.visit_id,
+        )
+        .with_period(Some(&period))
+        .with_scores(scores))
+    } else {
+        tx.rollback().await?;
+        Err(anyhow::anyhow!("Failed to create rating."))
     }
 }
 
-pub async fn get_group_memberships_by_user(
+pub async fn get_ratings_by_user(
     conn: &mut MySqlConnection,
     user_id: &str,
-) -> Result<Vec<GroupMembership>> {
+) -> Result<RatingsByPeriod> {
     let mut tx = conn.begin().await?;
 
-    let db_group_memberships = match sqlx::query_as!(
-        DbGroupMembership,
-        "SELECT id, group_id, user_id, role, created_at, updated_at
-         FROM group_memberships
-         WHERE user_id = ?",
-        user_id,
-    )
-    .fetch_all(&mut *tx)
-    .await
-    {
-        Ok(result) => result,
+    let group_memberships = match get_group_memberships_by_user(&mut tx, user_id).await {
+        Ok(group_memberships) => group_memberships,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!("Group membership not found: {err}"));
+            return Err(anyhow::anyhow!(err));
         }
     };
 
-    let mut group_memberships = Vec::new();
-
-    for db_group_membership in db_group_memberships {
-        let db_group = match get_group_by_membership(&mut tx, &db_group_membership.id).await {
-            Ok(group) => group,
+    // NOTE: Every group has its own period scheme, so the current ratings are collected per group
+    let mut current_period_ratings = Vec::new();
+    let mut current_periods: Vec<Period> = Vec::new();
+    for group_membership in group_memberships {
+        let period = match get_current_period(&mut tx, &group_membership.group_id).await {
+            Ok(Some(period)) => period,
+            Ok(None) => continue,
             Err(err) => {
                 tx.rollback().await?;
-                return Err(anyhow!("Could not get db group from membership: {err}"));
+                return Err(anyhow::anyhow!(err));
             }
         };
+        if !current_periods.contains(&period) {
+            current_periods.push(period.clone());
+        }
 
-        group_memberships.push(GroupMembership::from_db(&db_group_membership, &db_group));
+        match get_ratings_by_user_and_group_per_period(
+            &mut tx,
+            user_id,
+            &group_membership.group_id,
+            &period,
+        )
+        .await
+        {
+            Ok(ratings) => current_period_ratings.extend(ratings),
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(anyhow::anyhow!(err));
+            }
+        }
     }
 
-    tx.commit().await?;
-
-    Ok(group_memberships)
-}
-
-pub async fn delete_group_membership(
-    conn: &mut MySqlConnection,
-    id: &i32,
-) -> Result<MySqlQueryResult> {
-    let result = sqlx::query!("DELETE FROM group_memberships WHERE id = ?", id)
-        .execute(conn)
-        .await?;
-
-    Ok(result)
-}
-
-// NOTE: Restaurants
-
-pub async fn create_restaurant(
-    conn: &mut MySqlConnection,
-    restaurant: &Restaurant,
-) -> Result<Restaurant> {
-    let mut tx = conn.begin().await?;
-
-    // After migration, id is auto-increment, so don't specify it
-    let query = sqlx::query_as!(
-        Restaurant,
-        "INSERT INTO restaurants (group_id, cuisine, restaurant_code) VALUES (?, ?, ?)",
-        restaurant.group_id,
-        restaurant.cuisine,
-        restaurant.restaurant_code
+    let historical_average_ratings_query = sqlx::query_as!(
+        DbAverageRatingPerPeriod,
+        "SELECT
+             r.restaurant_id,
+             rest.restaurant_code,
+             g.period_scheme as `period_scheme: PeriodScheme`,
+             IFNULL(YEAR(r.created_at), 0) as year,
+             IFNULL(
+                 CASE g.period_scheme
+                     WHEN 'monthly' THEN MONTH(r.created_at) - 1
+                     WHEN 'quarterly' THEN QUARTER(r.created_at) - 1
+                     WHEN 'yearly' THEN 0
+                     ELSE gr.id
+                 END,
+                 0
+             ) as period_index,
+             gr.name as round_name,
+             gr.start_date as round_start_date,
+             gr.end_date as round_end_date,
+             IFNULL(AVG(r.score), 0) as average_score
+         FROM ratings r
+         JOIN restaurants rest on r.restaurant_id = rest.id
+         JOIN groups g on g.id = r.group_id
+         LEFT JOIN group_rounds gr on g.period_scheme = 'custom' AND gr.group_id = r.group_id
+             AND DATE(r.created_at) BETWEEN gr.start_date AND gr.end_date
+         WHERE r.user_id = ? AND (g.period_scheme <> 'custom' OR gr.id IS NOT NULL)
+         GROUP BY r.restaurant_id, rest.restaurant_code, g.period_scheme, year, period_index, gr.id, gr.name, gr.start_date, gr.end_date
+         ORDER BY year ASC, period_index ASC",
+        user_id,
     );
-    let result = match query.execute(&mut *tx).await {
-        Ok(query_result) => query_result,
+    let historical_ratings = match historical_average_ratings_query.fetch_all(&mut *tx).await {
+        Ok(rows) => rows,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!("Could not create restaurant: {err}"));
+            return Err(anyhow::anyhow!(err));
         }
     };
 
-    if result.rows_affected() == 1 {
-        // Get the auto-generated id
-        let last_id = result.last_insert_id() as i32;
+    // NOTE: Groups whose schemes disagree on the current period have no common one, each
+    // rating still carries the period of its own group
+    let current_period = match current_periods.len() {
+        1 => current_periods.pop(),
+        _ => None,
+    };
 
-        // Get the inserted restaurant
-        let db_restaurant = match get_restaurant(&mut tx, last_id).await {
-            Ok(restaurant) => restaurant,
-            Err(err) => {
-                tx.rollback().await?;
-                return Err(anyhow!("Could not get db restaurant: {err}"));
-            }
-        };
+    let ratings_by_period = RatingsByPeriod {
+        current_year: current_period
+            .as_ref()
+            .map_or_else(|| Utc::now().year(), Period::year),
+        current_period,
+        submitted_ratings: current_period_ratings.len(),
+        ratings_hidden: false,
+        current_period_ratings,
+        current_dimension_averages: Vec::new(),
+        historical_ratings: AverageRatingPerPeriod::from_db_rows(&historical_ratings),
+    };
 
-        tx.commit().await?;
+    tx.commit().await?;
 
-        Ok(db_restaurant)
-    } else {
-        Err(anyhow::anyhow!("Failed to create restaurant."))
-    }
+    Ok(ratings_by_period)
 }
 
-pub async fn get_restaurants(
+pub async fn get_ratings_by_user_and_group(
     conn: &mut MySqlConnection,
+    user_id: &str,
     group_id: &str,
-) -> Result<Vec<Restaurant>> {
-    let query = sqlx::query!(
-        "SELECT id, restaurant_code, group_id, cuisine FROM restaurants WHERE group_id = ? ORDER BY id",
-        group_id
-    );
-    let rows = query.fetch_all(conn).await?;
-
-    let restaurants = rows
-        .into_iter()
-        .map(|row| Restaurant {
-            id: row.id,
-            restaurant_code: row.restaurant_code,
-            group_id: row.group_id,
-            cuisine: row.cuisine,
-        })
-        .collect();
+) -> Result<RatingsByPeriod> {
+    let mut tx = conn.begin().await?;
 
-    Ok(restaurants)
-}
+    let current_period = match get_current_period(&mut tx, group_id).await {
+        Ok(current_period) => current_period,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow::anyhow!(err));
+        }
+    };
 
-pub async fn get_restaurant(conn: &mut MySqlConnection, restaurant_id: i32) -> Result<Restaurant> {
-    let mut tx = Acquire::begin(conn).await?;
+    let current_period_ratings = match &current_period {
+        Some(period) => {
+            match get_ratings_by_user_and_group_per_period(&mut tx, user_id, group_id, period).await
+            {
+                Ok(current_period_ratings) => current_period_ratings,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(anyhow::anyhow!(err));
+                }
+            }
+        }
+        None => Vec::new(),
+    };
 
-    let query = sqlx::query_as!(
-        DbRestaurant,
-        "SELECT id, restaurant_code, group_id, cuisine FROM restaurants WHERE id = ?",
-        restaurant_id
+    let historical_average_ratings_query = sqlx::query_as!(
+        DbAverageRatingPerPeriod,
+        "SELECT
+             r.restaurant_id,
+             rest.restaurant_code,
+             g.period_scheme as `period_scheme: PeriodScheme`,
+             IFNULL(YEAR(r.created_at), 0) as year,
+             IFNULL(
+                 CASE g.period_scheme
+                     WHEN 'monthly' THEN MONTH(r.created_at) - 1
+                     WHEN 'quarterly' THEN QUARTER(r.created_at) - 1
+                     WHEN 'yearly' THEN 0
+                     ELSE gr.id
+                 END,
+                 0
+             ) as period_index,
+             gr.name as round_name,
+             gr.start_date as round_start_date,
+             gr.end_date as round_end_date,
+             IFNULL(AVG(r.score), 0) as average_score
+         FROM ratings r
+         JOIN restaurants rest on r.restaurant_id = rest.id
+         JOIN groups g on g.id = r.group_id
+         LEFT JOIN group_rounds gr on g.period_scheme = 'custom' AND gr.group_id = r.group_id
+             AND DATE(r.created_at) BETWEEN gr.start_date AND gr.end_date
+         WHERE r.user_id = ? AND r.group_id = ? AND (g.period_scheme <> 'custom' OR gr.id IS NOT NULL)
+         GROUP BY r.restaurant_id, rest.restaurant_code, g.period_scheme, year, period_index, gr.id, gr.name, gr.start_date, gr.end_date
+         ORDER BY year ASC, period_index ASC",
+        user_id,
+        group_id,
     );
-    let db_restaurant_result = match query.fetch_optional(&mut *tx).await {
-        Ok(query_result) => query_result,
+    let historical_ratings = match historical_average_ratings_query.fetch_all(&mut *tx).await {
+        Ok(rows) => rows,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!("Restaurant not found: {err}"));
+            return Err(anyhow::anyhow!(err));
         }
     };
 
-    match db_restaurant_result {
-        Some(db_restaurant) => Ok(Restaurant {
-            id: db_restaurant.id,
-            restaurant_code: db_restaurant.restaurant_code,
-            group_id: db_restaurant.group_id,
-            cuisine: db_restaurant.cuisine,
-        }),
-        None => Err(anyhow!("Restaurant not found")),
-    }
-}
+    let ratings_by_period = RatingsByPeriod {
+        current_year: current_period
+            .as_ref()
+            .map_or_else(|| Utc::now().year(), Period::year),
+        current_period,
+        submitted_ratings: current_period_ratings.len(),
+        ratings_hidden: false,
+        current_period_ratings,
+        current_dimension_averages: Vec::new(),
+        historical_ratings: AverageRatingPerPeriod::from_db_rows(&historical_ratings),
+    };
 
-pub async fn update_restaurant(
-    conn: &mut MySqlConnection,
-    restaurant_id: i32,
-    restaurant: &Restaurant,
-) -> Result<MySqlQueryResult> {
-    let result = sqlx::query!(
-        "UPDATE restaurants
-         SET cuisine = ?, restaurant_code = ?
-         WHERE id = ? AND group_id = ?;",
-        restaurant.cuisine,
-        restaurant.restaurant_code,
-        restaurant_id,
-        restaurant.group_id,
-    )
-    .execute(conn)
-    .await?;
+    tx.commit().await?;
 
-    Ok(result)
+    Ok(ratings_by_period)
 }
 
-pub async fn delete_restaurant(
+pub async fn get_ratings_by_user_and_group_per_period(
     conn: &mut MySqlConnection,
-    restaurant_id: i32,
+    user_id: &str,
     group_id: &str,
-) -> Result<MySqlQueryResult> {
-    let result = sqlx::query!(
-        "DELETE FROM restaurants WHERE id = ? AND group_id = ?",
-        restaurant_id,
-        group_id
-    )
-    .execute(conn)
-    .await?;
+    period: &Period,
+) -> Result<Vec<Rating>> {
+    let date_range = period.to_date_range();
+
+    let query = sqlx::query_as!(
+        DbRating,
+        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color, r.review, r.visit_id
+         FROM ratings r
+         JOIN users u on u.id = r.user_id
+         JOIN restaurants rest on r.restaurant_id = rest.id
+         WHERE r.user_id = ? and r.group_id = ? AND r.created_at >= ? AND DATE(r.created_at) <= ?",
+        user_id,
+        group_id,
+        date_range.0,
+        date_range.1
+    );
+    let ratings = query
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|db_rating| Rating::from_db(db_rating).with_period(Some(period)))
+        .collect();
 
-    Ok(result)
+    with_rating_scores(conn, ratings).await
 }
 
-pub async fn is_restaurant_rating_complete(
-    pool: &MySqlPool,
-    push_client: Option<&PushClient>,
+pub async fn get_ratings_by_restaurant(
+    conn: &mut MySqlConnection,
     restaurant_id: i32,
     group_id: &str,
-) -> Result<bool> {
-    let mut conn = get_connection(pool).await.unwrap();
+) -> Result<RatingsByPeriod> {
     let mut tx = conn.begin().await?;
 
-    let date_range = Period::current_period_date_range()?;
+    let current_period = match get_current_period(&mut tx, group_id).await {
+        Ok(current_period) => current_period,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow::anyhow!(err));
+        }
+    };
+
+    let current_period_ratings = match &current_period {
+        Some(period) => {
+            match get_ratings_by_restaurant_per_period(&mut tx, group_id, restaurant_id, period)
+                .await
+            {
+                Ok(current_period_ratings) => current_period_ratings,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(anyhow::anyhow!(err));
+                }
+            }
+        }
+        None => Vec::new(),
+    };
+
+    let current_dimension_averages = match &current_period {
+        Some(period) => {
+            match get_dimension_averages_per_period(&mut tx, group_id, restaurant_id, period).await
+            {
+                Ok(current_dimension_averages) => current_dimension_averages,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(anyhow::anyhow!(err));
+                }
+            }
+        }
+        None => Vec::new(),
+    };
 
-    let result = match sqlx::query_scalar!(
+    let historical_average_ratings_query = sqlx::query_as!(
+        DbAverageRatingPerPeriod,
         "SELECT
-            (
-                SELECT COUNT(*)
-                FROM group_memberships
-                WHERE group_id = ?
-            ) = (
-                SELECT COUNT(*)
-                FROM ratings
-                WHERE group_id = ? AND restaurant_id = ? AND created_at >= ? AND DATE(created_at) <= ?
-            ) AS is_complete;",
-        group_id,
+             r.restaurant_id,
+             rest.restaurant_code,
+             g.period_scheme as `period_scheme: PeriodScheme`,
+             IFNULL(YEAR(r.created_at), 0) as year,
+             IFNULL(
+                 CASE g.period_scheme
+                     WHEN 'monthly' THEN MONTH(r.created_at) - 1
+                     WHEN 'quarterly' THEN QUARTER(r.created_at) - 1
+                     WHEN 'yearly' THEN 0
+                     ELSE gr.id
+                 END,
+                 0
+             ) as period_index,
+             gr.name as round_name,
+             gr.start_date as round_start_date,
+             gr.end_date as round_end_date,
+             IFNULL(AVG(r.score), 0) as average_score
+         FROM ratings r
+         JOIN restaurants rest on r.restaurant_id = rest.id
+         JOIN groups g on g.id = r.group_id
+         LEFT JOIN group_rounds gr on g.period_scheme = 'custom' AND gr.group_id = r.group_id
+             AND DATE(r.created_at) BETWEEN gr.start_date AND gr.end_date
+         WHERE r.group_id = ? AND r.restaurant_id = ? AND (g.period_scheme <> 'custom' OR gr.id IS NOT NULL)
+         GROUP BY r.restaurant_id, rest.restaurant_code, g.period_scheme, year, period_index, gr.id, gr.name, gr.start_date, gr.end_date
+         ORDER BY year ASC, period_index ASC",
         group_id,
         restaurant_id,
-        date_range.0,
-        date_range.1
-    )
-    .fetch_one(&mut *tx)
-    .await
-    {
-        Ok(result) => result,
+    );
+    let historical_ratings = match historical_average_ratings_query.fetch_all(&mut *tx).await {
+        Ok(rows) => rows,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!(
-                "Could not check if restaurant rating is complete: {err}"
-            ));
+            return Err(anyhow::anyhow!(err));
         }
     };
 
-    let is_complete = match result {
-        Some(is_complete) => is_complete == 1,
-        None => {
+    let historical_dimension_averages_query = sqlx::query_as!(
+        DbDimensionAveragePerPeriod,
+        "SELECT
+             r.restaurant_id,
+             g.period_scheme as `period_scheme: PeriodScheme`,
+             IFNULL(YEAR(r.created_at), 0) as year,
+             IFNULL(
+                 CASE g.period_scheme
+                     WHEN 'monthly' THEN MONTH(r.created_at) - 1
+                     WHEN 'quarterly' THEN QUARTER(r.created_at) - 1
+                     WHEN 'yearly' THEN 0
+                     ELSE gr.id
+                 END,
+                 0
+             ) as period_index,
+             gr.name as round_name,
+             gr.start_date as round_start_date,
+             gr.end_date as round_end_date,
+             d.id as dimension_id,
+             d.name,
+             d.weight,
+             IFNULL(AVG(rs.score), 0) as average_score
+         FROM ratings r
+         JOIN rating_scores rs on rs.rating_id = r.id
+         JOIN rating_dimensions d on d.id = rs.dimension_id
+         JOIN groups g on g.id = r.group_id
+         LEFT JOIN group_rounds gr on g.period_scheme = 'custom' AND gr.group_id = r.group_id
+             AND DATE(r.created_at) BETWEEN gr.start_date AND gr.end_date
+         WHERE r.group_id = ? AND r.restaurant_id = ? AND (g.period_scheme <> 'custom' OR gr.id IS NOT NULL)
+         GROUP BY r.restaurant_id, g.period_scheme, year, period_index, gr.id, gr.name, gr.start_date, gr.end_date, d.id, d.name, d.weight, d.position
+         ORDER BY year ASC, period_index ASC, d.position ASC, d.id ASC",
+        group_id,
+        restaurant_id,
+    );
+    let historical_dimension_averages = match historical_dimension_averages_query
+        .fetch_all(&mut *tx)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!("Failed to check if restaurant rating is complete."));
+            return Err(anyhow::anyhow!(err));
         }
     };
 
-    if is_complete {
-        if let Some(push_client) = push_client {
-            let notification_sent =
-                match rating_notification_sent(&mut tx, restaurant_id, group_id).await {
-                    Ok(notification_sent) => notification_sent,
-                    Err(err) => {
-                        tx.rollback().await?;
-                        return Err(err);
-                    }
-                };
+    let ratings_by_period = RatingsByPeriod {
+        current_year: current_period
+            .as_ref()
+            .map_or_else(|| Utc::now().year(), Period::year),
+        current_period,
+        submitted_ratings: current_period_ratings.len(),
+        ratings_hidden: false,
+        current_period_ratings,
+        current_dimension_averages,
+        historical_ratings: AverageRatingPerPeriod::with_dimension_averages(
+            AverageRatingPerPeriod::from_db_rows(&historical_ratings),
+            &historical_dimension_averages,
+        ),
+    };
 
-            if !notification_sent {
-                if let Err(err) = create_rating_notification(&mut tx, restaurant_id, group_id).await
-                {
-                    tx.rollback().await?;
-                    return Err(err);
-                }
+    tx.commit().await?;
 
-                tx.commit().await?;
+    Ok(ratings_by_period)
+}
 
-                let push_client = push_client.clone();
-                let group_id = group_id.to_string();
-                let restaurant_id = restaurant_id.to_string();
-                let new_pool = pool.clone();
+pub async fn get_ratings_by_restaurant_per_period(
+    conn: &mut MySqlConnection,
+    group_id: &str,
+    restaurant_id: i32,
+    period: &Period,
+) -> Result<Vec<Rating>> {
+    let date_range = period.to_date_range();
 
-                actix_web::rt::spawn(async move {
-                    if let Err(err) = send_notification_to_group(
-                        &push_client,
-                        &new_pool,
-                        &group_id,
-                        &format!("{restaurant_id} ratings are complete!"),
-                    )
-                    .await
-                    {
-                        eprintln!(
-                            "ERROR: Failed to send notification to group {group_id} for restaurant {restaurant_id}: {err}"
-                        );
-                    }
-                });
-            } else {
-                tx.commit().await?;
-            }
-        } else {
-            tx.commit().await?;
-        }
-    } else {
-        tx.commit().await?;
-    }
+    let query = sqlx::query_as!(
+        DbRating,
+        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color, r.review, r.visit_id
+         FROM ratings r
+         JOIN users u on u.id = r.user_id
+         JOIN restaurants rest on r.restaurant_id = rest.id
+         WHERE r.group_id = ? and r.restaurant_id = ? AND r.created_at >= ? AND DATE(r.created_at) <= ?",
+        group_id,
+        restaurant_id,
+        date_range.0,
+        date_range.1
+    );
+    let ratings = query
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|db_rating| Rating::from_db(db_rating).with_period(Some(period)))
+        .collect();
 
-    Ok(is_complete)
+    with_rating_scores(conn, ratings).await
 }
 
-pub async fn get_avg_rating(
-    pool: &MySqlPool,
-    restaurant_id: i32,
+pub async fn get_rating_by_restaurant(
+    conn: &mut MySqlConnection,
+    user_id: &str,
     group_id: &str,
-) -> Result<Option<f64>> {
-    if is_restaurant_rating_complete(pool, None, restaurant_id, group_id).await? {
-        let mut conn = get_connection(pool).await.unwrap();
+    restaurant_id: i32,
+) -> Result<Rating> {
+    let period = get_current_period(&mut *conn, group_id)
+        .await?
+        .ok_or_else(|| anyhow!("There is no active rating period for this group"))?;
+    let date_range = period.to_date_range();
 
-        let date_range = Period::current_period_date_range()?;
+    let query = sqlx::query_as!(
+        DbRating,
+        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color, r.review, r.visit_id
+         FROM ratings r
+         JOIN users u on u.id = r.user_id
+         JOIN restaurants rest on r.restaurant_id = rest.id
+         WHERE r.user_id = ? AND r.group_id = ? AND r.restaurant_id = ? AND r.created_at >= ? AND DATE(r.created_at) <= ?",
+        user_id,
+        group_id,
+        restaurant_id,
+        date_range.0,
+        date_range.1
+    );
+    let rating = query.fetch_optional(&mut *conn).await?;
 
-        let avg_rating: Option<f64> = sqlx::query_scalar!(
-            "SELECT AVG(score)
-             FROM ratings
-             WHERE group_id = ? and restaurant_id = ? AND created_at >= ? AND DATE(created_at) <= ?",
-            group_id,
-            restaurant_id,
-            date_range.0,
-            date_range.1
-        )
-        .fetch_one(&mut *conn)
-        .await?;
+    match rating {
+        Some(rating) => {
+            let scores = get_rating_scores(conn, rating.id).await?;
 
-        Ok(avg_rating)
-    } else {
-        Ok(None)
+            Ok(Rating::from_db(&rating)
+                .with_period(Some(&period))
+                .with_scores(scores))
+        }
+        None => Err(anyhow!("Rating not found")),
     }
 }
 
-pub async fn get_restaurants_with_avg_rating(
+pub async fn is_restaurant_rated_by_user(
     conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    user_id: &str,
     group_id: &str,
-) -> Result<Vec<(Restaurant, f64)>> {
-    let mut tx = Acquire::begin(conn).await?;
-
-    let date_range = Period::current_period_date_range()?;
+) -> Result<bool> {
+    let date_range = match get_current_period(&mut *conn, group_id).await? {
+        Some(period) => period.to_date_range(),
+        None => return Ok(false),
+    };
 
-    let db_restaurants_with_avg_rating_result = sqlx::query!(
-        "SELECT *
-         FROM (
-            SELECT r.id, r.restaurant_code, r.group_id, r.cuisine,
-                IF(
-                    (
-                        SELECT COUNT(*) FROM group_memberships gm
-                        WHERE gm.group_id = ?
-                    ) = (
-                        SELECT COUNT(*) FROM ratings ra2
-                        WHERE ra2.group_id = ? AND ra2.restaurant_id = r.id AND ra2.created_at >= ? AND DATE(ra2.created_at) <= ?
-                    ),
-                    AVG(ra.score),
-                    NULL
-                ) AS avg_rating,
-                COUNT(ra.score) AS num_ratings,
-                (
-                    SELECT COUNT(*) FROM ratings ra3
-                    WHERE ra3.group_id = ? AND ra3.restaurant_id = r.id AND ra3.created_at >= ? AND DATE(ra3.created_at) <= ?
-                ) AS has_any_rating
-             FROM restaurants r
-             LEFT JOIN ratings ra ON ra.group_id = ? AND ra.restaurant_id = r.id AND ra.created_at >= ? AND DATE(ra.created_at) <= ?
-             WHERE r.group_id = ?
-             GROUP BY r.id
-         ) AS subquery
-         ORDER BY
-            avg_rating IS NULL ASC,
-            has_any_rating DESC,
-            avg_rating DESC,
-            id",
-        group_id,
-        group_id,
-        date_range.0,
-        date_range.1,
-        group_id,
-        date_range.0,
-        date_range.1,
+    let query = sqlx::query_as!(
+        DbRating,
+        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color, r.review, r.visit_id
+         FROM ratings r
+         JOIN users u on u.id = r.user_id
+         JOIN restaurants rest on r.restaurant_id = rest.id
+         WHERE r.restaurant_id = ? AND r.user_id = ? AND r.group_id = ? AND r.visit_id IS NULL
+         AND r.created_at >= ? AND DATE(r.created_at) <= ?",
+        restaurant_id,
+        user_id,
         group_id,
         date_range.0,
-        date_range.1,
-        group_id
-    )
-    .fetch_all(&mut *tx)
-    .await;
-
-    let db_restaurants_with_avg_rating_result = match db_restaurants_with_avg_rating_result {
-        Ok(rows) => {
-            let mapped_rows: Result<Vec<(DbRestaurant, Option<f64>)>, Error> = rows
-                .into_iter()
-                .map(|row| {
-                    Ok((
-                        DbRestaurant {
-                            id: row.id,
-                            restaurant_code: row.restaurant_code,
-                            group_id: row.group_id,
-                            cuisine: row.cuisine,
-                        },
-                        row.avg_rating,
-                    ))
-                })
-                .collect();
-
-            mapped_rows
-        }
-        Err(e) => {
-            tx.rollback().await?;
-            return Err(e.into());
-        }
-    };
-
-    let mut results = Vec::new();
-
-    for (db_restaurant, avg_rating) in db_restaurants_with_avg_rating_result? {
-        results.push((
-            Restaurant {
-                id: db_restaurant.id,
-                restaurant_code: db_restaurant.restaurant_code,
-                group_id: db_restaurant.group_id,
-                cuisine: db_restaurant.cuisine,
-            },
-            avg_rating.unwrap_or(0.0),
-        ));
-    }
-
-    tx.commit().await?;
+        date_range.1
+    );
+    let ratings = query.fetch_all(conn).await?;
 
-    Ok(results)
+    Ok(!ratings.is_empty())
 }
 
-// NOTE: Ratings
+// pub async fn is_restaurant_rated_by_user(
+//     conn: &mut MySqlConnection,
+//     restaurant_id: i32,
+//     user_id: &str,
+//     group_id: &str,
+// ) -> Result<bool> {
+//     let query = sqlx::query_as!(
+//         DbRating,
+//         "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color, r.review, r.visit_id
+//          FROM ratings r
+//          JOIN users u on u.id = r.user_id
+//          JOIN restaurants rest on r.restaurant_id = rest.id
+//          WHERE r.restaurant_id = ? AND r.user_id = ? AND r.group_id = ?",
+//         restaurant_id,
+//         user_id,
+//         group_id
+//     );
+//     let ratings = query.fetch_all(conn).await?;
+//
+//     Ok(!ratings.is_empty())
+// }
 
-pub async fn create_rating(conn: &mut MySqlConnection, rating: &NewRating) -> Result<Rating> {
+pub async fn update_rating(
+    conn: &mut MySqlConnection,
+    rating: &NewRating,
+    user_id: &str,
+) -> Result<Rating> {
     let mut tx = conn.begin().await?;
 
-    let created_at = Utc::now().naive_utc();
-    let updated_at = created_at;
-
-    if !check_group_membership_exists(&mut tx, &rating.user_id, &rating.group_id).await? {
+    if !check_group_membership_exists(&mut tx, user_id, &rating.group_id).await? {
         tx.rollback().await?;
         return Err(anyhow!("User does not belong to group"));
     }
 
-    let restaurant_exists = sqlx::query_scalar!(
-        "SELECT EXISTS(SELECT 1 FROM restaurants WHERE id = ? AND group_id = ?)",
-        rating.restaurant_id,
-        rating.group_id
-    )
-    .fetch_one(&mut *tx)
-    .await?;
-    if restaurant_exists == 0 {
-        return Err(anyhow!("Restaurant does not exist in this group"));
-    }
-
-    let query = sqlx::query_as!(
-        Rating,
-        "INSERT INTO ratings (group_id, restaurant_id, user_id, username, score, created_at, updated_at)
-         VALUES (?, ?, ?, ?, ?, ?, ?)",
-        rating.group_id,
-        rating.restaurant_id,
-        rating.user_id,
-        rating.username,
-        rating.score,
-        created_at,
-        updated_at
-    );
-    let result = match query.execute(&mut *tx).await {
-        Ok(query_result) => query_result,
+    let period = match get_current_period(&mut tx, &rating.group_id).await {
+        Ok(Some(period)) => period,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Err(anyhow!("There is no active rating period for this group"));
+        }
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
+            return Err(anyhow!("Could not get current period: {err}"));
         }
     };
+    let updated_at = Utc::now().naive_utc();
 
-    if result.rows_affected() == 1 {
-        let last_insert_id = result.last_insert_id();
-
-        // Get restaurant_code from the restaurants table
-        let restaurant_query = sqlx::query_as!(
-            DbRestaurant,
-            "SELECT id, restaurant_code, group_id, cuisine FROM restaurants WHERE id = ?",
-            rating.restaurant_id
-        );
-        let restaurant = restaurant_query.fetch_optional(&mut *tx).await?;
-        let restaurant_code = restaurant.map(|r| r.restaurant_code).unwrap_or_default();
-
-        tx.commit().await?;
-
-        Ok(Rating::new(
-            last_insert_id as i32,
-            rating.group_id.clone(),
-            rating.restaurant_id,
-            restaurant_code,
-            rating.user_id.clone(),
-            rating.username.clone(),
-            rating.score,
-            created_at,
-            updated_at,
-            None,
-        ))
-    } else {
+    let rating_scale = match get_group_rating_scale(&mut tx, &rating.group_id).await {
+        Ok(rating_scale) => rating_scale,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get group rating scale: {err}"));
+        }
+    };
+    if let Err(err) = rating_scale.check_rating(rating) {
         tx.rollback().await?;
-        Err(anyhow::anyhow!("Failed to create rating."))
+        return Err(anyhow!(err.to_string()));
     }
-}
 
-pub async fn get_ratings_by_user(
-    conn: &mut MySqlConnection,
-    user_id: &str,
-) -> Result<RatingsByPeriod> {
-    let mut tx = conn.begin().await?;
+    let (score, scores) = match get_rating_dimensions(&mut tx, &rating.group_id)
+        .await
+        .and_then(|dimensions| {
+            RatingDimension::resolve_scores(&dimensions, rating.score, &rating.scores)
+        }) {
+        Ok(resolved_scores) => resolved_scores,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(err);
+        }
+    };
 
-    let (current_year, current_period, date_range) = Period::current_period_info()?;
+    // NOTE: A visit is rated once per attendee, other ratings once per period
+    let rating_id = match rating.visit_id {
+        Some(visit_id) => {
+            sqlx::query_scalar!(
+                "SELECT id FROM ratings
+                 WHERE visit_id = ? AND group_id = ? AND user_id = ? AND restaurant_id = ?",
+                visit_id,
+                rating.group_id,
+                user_id,
+                rating.restaurant_id
+            )
+            .fetch_optional(&mut *tx)
+            .await
+        }
+        None => {
+            let date_range = period.to_date_range();
+
+            sqlx::query_scalar!(
+                "SELECT id FROM ratings
+                 WHERE group_id = ? AND user_id = ? AND restaurant_id = ? AND visit_id IS NULL
+                 AND created_at >= ? AND DATE(created_at) <= ?",
+                rating.group_id,
+                user_id,
+                rating.restaurant_id,
+                date_range.0,
+                date_range.1
+            )
+            .fetch_optional(&mut *tx)
+            .await
+        }
+    };
+    let rating_id = match rating_id {
+        Ok(Some(rating_id)) => rating_id,
+        Ok(None) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Rating not found"));
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get rating: {err}"));
+        }
+    };
 
-    let current_period_query = sqlx::query_as!(
-        DbRating,
-        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color
-         FROM ratings r
-         JOIN users u on u.id = r.user_id
-         JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.user_id = ? AND r.created_at >= ? AND r.created_at <= ?",
-        user_id,
-        date_range.0,
-        date_range.1
-    );
-    let current_period_ratings = match current_period_query.fetch_all(&mut *tx).await {
-        Ok(current_period_ratings) => current_period_ratings,
+    let _ = match sqlx::query!(
+        "UPDATE ratings
+         SET score = ?, username = ?, review = ?, updated_at = ?
+         WHERE id = ?",
+        score,
+        rating.username,
+        rating.review(),
+        updated_at,
+        rating_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(query_result) => query_result,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
+            return Err(anyhow!("Could not update rating: {err}"));
         }
     };
-    let current_period_ratings = current_period_ratings.iter().map(Rating::from_db).collect();
 
-    let historical_average_ratings_query = sqlx::query_as!(
-        AverageRatingPerPeriod,
-        "SELECT
-             r.restaurant_id,
-             rest.restaurant_code,
-             IFNULL(YEAR(r.created_at), 0) as year,
-             IFNULL(
-                 CASE
-                     WHEN MONTH(r.created_at) BETWEEN 1 AND 3 THEN 0
-                     WHEN MONTH(r.created_at) BETWEEN 4 AND 6 THEN 1
-                     WHEN MONTH(r.created_at) BETWEEN 7 AND 9 THEN 2
-                     ELSE 3
-                 END,
-                 0
-             ) as period,
-             IFNULL(AVG(r.score), 0) as average_score
-         FROM ratings r
-         JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.user_id = ?
-         GROUP BY r.restaurant_id, rest.restaurant_code, year, period
-         ORDER BY year ASC, period ASC",
-        user_id,
-    );
-    let historical_ratings = match historical_average_ratings_query.fetch_all(&mut *tx).await {
-        Ok(rows) => rows,
+    let updated_rating = match get_rating(&mut tx, rating_id).await {
+        Ok(updated_rating) => updated_rating.with_period(Some(&period)),
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
+            return Err(anyhow!("Could not get rating: {err}"));
         }
     };
 
-    let ratings_by_period = RatingsByPeriod {
-        current_year,
-        current_period,
-        current_period_ratings,
-        historical_ratings,
+    if let Err(err) = set_rating_scores(&mut tx, updated_rating.id, &scores).await {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not save rating scores: {err}"));
+    }
+
+    let scores = match get_rating_scores(&mut tx, updated_rating.id).await {
+        Ok(scores) => scores,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get rating scores: {err}"));
+        }
     };
 
     tx.commit().await?;
 
-    Ok(ratings_by_period)
+    Ok(updated_rating.with_scores(scores))
 }
 
-pub async fn get_ratings_by_user_and_group(
+pub async fn delete_rating(
     conn: &mut MySqlConnection,
+    rating_id: i32,
     user_id: &str,
     group_id: &str,
-) -> Result<RatingsByPeriod> {
-    let mut tx = conn.begin().await?;
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "DELETE FROM ratings WHERE id = ? AND group_id = ? AND user_id = ?",
+        rating_id,
+        group_id,
+        user_id
+    )
+    .execute(conn)
+    .await?;
 
-    let (current_year, current_period, date_range) = Period::current_period_info()?;
+    Ok(result)
+}
 
+pub async fn get_rating(conn: &mut MySqlConnection, rating_id: i32) -> Result<Rating> {
     let query = sqlx::query_as!(
         DbRating,
-        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color
+        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color, r.review, r.visit_id
          FROM ratings r
          JOIN users u on u.id = r.user_id
          JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.user_id = ? and r.group_id = ? AND r.created_at >= ? AND r.created_at <= ?",
-        user_id,
-        group_id,
-        date_range.0,
-        date_range.1
+         WHERE r.id = ?",
+        rating_id
     );
-    let current_period_ratings = match query.fetch_all(&mut *tx).await {
-        Ok(current_period_ratings) => current_period_ratings,
+    let rating = query.fetch_optional(&mut *conn).await?;
+
+    match rating {
+        Some(rating) => {
+            let scores = get_rating_scores(conn, rating.id).await?;
+
+            Ok(Rating::from_db(&rating).with_scores(scores))
+        }
+        None => Err(anyhow!("Rating not found")),
+    }
+}
+
+pub async fn delete_rating_review(
+    conn: &mut MySqlConnection,
+    rating_id: i32,
+    group_id: &str,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "UPDATE ratings SET review = NULL WHERE id = ? AND group_id = ?",
+        rating_id,
+        group_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
+// NOTE: Menu Items
+
+pub async fn create_menu_item(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+    new_menu_item: &NewMenuItem,
+) -> Result<MenuItem> {
+    let name = new_menu_item.name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Dish name can't be empty"));
+    }
+    if !new_menu_item.price.is_finite() || new_menu_item.price < 0.0 {
+        return Err(anyhow!("Dish price can't be negative"));
+    }
+
+    let mut tx = conn.begin().await?;
+
+    let result = match sqlx::query!(
+        "INSERT INTO menu_items (restaurant_id, name, price, category) VALUES (?, ?, ?, ?)",
+        restaurant_id,
+        name,
+        new_menu_item.price,
+        new_menu_item.category()
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(result) => result,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
+            return Err(anyhow!("Could not create dish: {err}"));
         }
     };
-    let current_period_ratings = current_period_ratings.iter().map(Rating::from_db).collect();
 
-    let historical_average_ratings_query = sqlx::query_as!(
-        AverageRatingPerPeriod,
-        "SELECT
-             r.restaurant_id,
-             rest.restaurant_code,
-             IFNULL(YEAR(r.created_at), 0) as year,
-             IFNULL(
-                 CASE
-                     WHEN MONTH(r.created_at) BETWEEN 1 AND 3 THEN 0
-                     WHEN MONTH(r.created_at) BETWEEN 4 AND 6 THEN 1
-                     WHEN MONTH(r.created_at) BETWEEN 7 AND 9 THEN 2
-                     ELSE 3
-                 END,
-                 0
-             ) as period,
-             IFNULL(AVG(r.score), 0) as average_score
-         FROM ratings r
-         JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.user_id = ? AND r.group_id = ?
-         GROUP BY r.restaurant_id, rest.restaurant_code, year, period
-         ORDER BY year ASC, period ASC;",
-        user_id,
-        group_id,
-    );
-    let historical_ratings = match historical_average_ratings_query.fetch_all(&mut *tx).await {
-        Ok(rows) => rows,
+    let menu_item = match get_menu_item(&mut tx, result.last_insert_id() as i32).await {
+        Ok(menu_item) => menu_item,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
+            return Err(err);
         }
     };
 
-    let ratings_by_period = RatingsByPeriod {
-        current_year,
-        current_period,
-        current_period_ratings,
-        historical_ratings,
+    tx.commit().await?;
+
+    Ok(menu_item)
+}
+
+pub async fn get_menu_item(conn: &mut MySqlConnection, menu_item_id: i32) -> Result<MenuItem> {
+    let menu_item = sqlx::query_as!(
+        MenuItem,
+        "SELECT id, restaurant_id, name, price, category, created_at, updated_at
+         FROM menu_items
+         WHERE id = ?",
+        menu_item_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    menu_item.ok_or_else(|| anyhow!("Dish not found"))
+}
+
+pub async fn get_menu_items(
+    conn: &mut MySqlConnection,
+    restaurant_id: i32,
+) -> Result<Vec<MenuItem>> {
+    let menu_items = sqlx::query_as!(
+        MenuItem,
+        "SELECT id, restaurant_id, name, price, category, created_at, updated_at
+         FROM menu_items
+         WHERE restaurant_id = ?
+         ORDER BY category IS NULL, category, name",
+        restaurant_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(menu_items)
+}
+
+pub async fn update_menu_item(
+    conn: &mut MySqlConnection,
+    menu_item_id: i32,
+    menu_item: &NewMenuItem,
+) -> Result<MenuItem> {
+    let name = menu_item.name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Dish name can't be empty"));
+    }
+    if !menu_item.price.is_finite() || menu_item.price < 0.0 {
+        return Err(anyhow!("Dish price can't be negative"));
+    }
+
+    let mut tx = conn.begin().await?;
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE menu_items SET name = ?, price = ?, category = ? WHERE id = ?",
+        name,
+        menu_item.price,
+        menu_item.category(),
+        menu_item_id
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not update dish: {err}"));
+    }
+
+    let menu_item = match get_menu_item(&mut tx, menu_item_id).await {
+        Ok(menu_item) => menu_item,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(err);
+        }
     };
 
     tx.commit().await?;
 
-    Ok(ratings_by_period)
+    Ok(menu_item)
 }
 
-pub async fn get_ratings_by_restaurant(
+pub async fn delete_menu_item(
+    conn: &mut MySqlConnection,
+    menu_item_id: i32,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM menu_items WHERE id = ?", menu_item_id)
+        .execute(conn)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn rate_menu_item(
     conn: &mut MySqlConnection,
-    restaurant_id: i32,
     group_id: &str,
-) -> Result<RatingsByPeriod> {
+    menu_item_id: i32,
+    user_id: &str,
+    score: f32,
+) -> Result<DishRating> {
     let mut tx = conn.begin().await?;
 
-    let (current_year, current_period, _) = Period::current_period_info()?;
+    if !check_group_membership_exists(&mut tx, user_id, group_id).await? {
+        tx.rollback().await?;
+        return Err(anyhow!("User does not belong to group"));
+    }
 
-    let current_period_ratings = match get_ratings_by_restaurant_per_period(
-        &mut tx,
-        group_id,
-        restaurant_id,
-        current_year,
-        &current_period,
-    )
-    .await
-    {
-        Ok(current_period_ratings) => current_period_ratings,
+    let rating_scale = match get_group_rating_scale(&mut tx, group_id).await {
+        Ok(rating_scale) => rating_scale,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
+            return Err(anyhow!("Could not get group rating scale: {err}"));
         }
     };
+    if let Err(err) = rating_scale.check_score(score, None) {
+        tx.rollback().await?;
+        return Err(anyhow!(err.to_string()));
+    }
 
-    let historical_average_ratings_query = sqlx::query_as!(
-        AverageRatingPerPeriod,
-        "SELECT
-            r.restaurant_id,
-            rest.restaurant_code,
-            IFNULL(YEAR(r.created_at), 0) as year,
-            IFNULL(
-                CASE
-                    WHEN MONTH(r.created_at) BETWEEN 1 AND 3 THEN 0
-                    WHEN MONTH(r.created_at) BETWEEN 4 AND 6 THEN 1
-                    WHEN MONTH(r.created_at) BETWEEN 7 AND 9 THEN 2
-                    ELSE 3
-                END,
-                0
-            ) as period,
-            IFNULL(AVG(r.score), 0) as average_score
-         FROM ratings r
-         JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.group_id = ? AND r.restaurant_id = ?
-         GROUP BY r.restaurant_id, rest.restaurant_code, year, period
-         ORDER BY year ASC, period ASC",
-        group_id,
-        restaurant_id,
-    );
-    let historical_ratings = match historical_average_ratings_query.fetch_all(&mut *tx).await {
-        Ok(rows) => rows,
+    let now = Utc::now().naive_utc();
+
+    // NOTE: Every member has a single rating per dish, rating it again replaces the score
+    if let Err(err) = sqlx::query!(
+        "INSERT INTO dish_ratings (menu_item_id, user_id, score, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?)
+         ON DUPLICATE KEY UPDATE score = VALUES(score), updated_at = VALUES(updated_at)",
+        menu_item_id,
+        user_id,
+        score,
+        now,
+        now
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        tx.rollback().await?;
+        return Err(anyhow!("Could not rate dish: {err}"));
+    }
+
+    let dish_rating = match sqlx::query_as!(
+        DishRating,
+        "SELECT dr.id, dr.menu_item_id, dr.user_id, u.username, u.color, dr.score, dr.created_at, dr.updated_at
+         FROM dish_ratings dr
+         JOIN users u ON u.id = dr.user_id
+         WHERE dr.menu_item_id = ? AND dr.user_id = ?",
+        menu_item_id,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    {
+        Ok(dish_rating) => dish_rating,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow::anyhow!(err));
+            return Err(anyhow!("Could not get dish rating: {err}"));
         }
     };
 
-    let ratings_by_period = RatingsByPeriod {
-        current_year,
-        current_period,
-        current_period_ratings,
-        historical_ratings,
-    };
-
     tx.commit().await?;
 
-    Ok(ratings_by_period)
+    Ok(dish_rating)
 }
 
-pub async fn get_ratings_by_restaurant_per_period(
+pub async fn get_dish_ratings(
     conn: &mut MySqlConnection,
-    group_id: &str,
-    restaurant_id: i32,
-    year: i32,
-    period: &Period,
-) -> Result<Vec<Rating>> {
-    let date_range = period.to_date_range(year)?;
-
-    let query = sqlx::query_as!(
-        DbRating,
-        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color
-         FROM ratings r
-         JOIN users u on u.id = r.user_id
-         JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.group_id = ? and r.restaurant_id = ? AND r.created_at >= ? AND r.created_at <= ?",
-        group_id,
-        restaurant_id,
-        date_range.0,
-        date_range.1
-    );
-    let ratings = query
-        .fetch_all(conn)
-        .await?
-        .iter()
-        .map(Rating::from_db)
-        .collect();
+    menu_item_id: i32,
+) -> Result<Vec<DishRating>> {
+    let dish_ratings = sqlx::query_as!(
+        DishRating,
+        "SELECT dr.id, dr.menu_item_id, dr.user_id, u.username, u.color, dr.score, dr.created_at, dr.updated_at
+         FROM dish_ratings dr
+         JOIN users u ON u.id = dr.user_id
+         WHERE dr.menu_item_id = ?
+         ORDER BY dr.score DESC, dr.updated_at DESC",
+        menu_item_id
+    )
+    .fetch_all(conn)
+    .await?;
 
-    Ok(ratings)
+    Ok(dish_ratings)
 }
 
-pub async fn get_rating_by_restaurant(
+pub async fn get_best_dishes(
     conn: &mut MySqlConnection,
-    user_id: &str,
-    group_id: &str,
     restaurant_id: i32,
-) -> Result<Rating> {
-    let date_range = Period::current_period_date_range()?;
-
-    let query = sqlx::query_as!(
-        DbRating,
-        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color
-         FROM ratings r
-         JOIN users u on u.id = r.user_id
-         JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.user_id = ? AND r.group_id = ? AND r.restaurant_id = ? AND r.created_at >= ? AND r.created_at <= ?",
-        user_id,
-        group_id,
+    limit: u32,
+) -> Result<Vec<RatedMenuItem>> {
+    let rows = sqlx::query!(
+        "SELECT mi.id, mi.restaurant_id, mi.name, mi.price, mi.category, mi.created_at, mi.updated_at,
+            AVG(dr.score) AS `average_score!: f64`,
+            COUNT(dr.id) AS num_ratings
+         FROM menu_items mi
+         JOIN dish_ratings dr ON dr.menu_item_id = mi.id
+         WHERE mi.restaurant_id = ?
+         GROUP BY mi.id
+         ORDER BY average_score DESC, num_ratings DESC, mi.name
+         LIMIT ?",
         restaurant_id,
-        date_range.0,
-        date_range.1
-    );
-    let rating = query.fetch_optional(conn).await?;
+        limit
+    )
+    .fetch_all(conn)
+    .await?;
 
-    match rating {
-        Some(rating) => Ok(Rating::from_db(&rating)),
-        None => Err(anyhow!("Rating not found")),
-    }
+    Ok(rows
+        .into_iter()
+        .map(|row| RatedMenuItem {
+            menu_item: MenuItem {
+                id: row.id,
+                restaurant_id: row.restaurant_id,
+                name: row.name,
+                price: row.price,
+                category: row.category,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            },
+            average_score: row.average_score,
+            num_ratings: row.num_ratings,
+        })
+        .collect())
 }
 
-pub async fn is_restaurant_rated_by_user(
+// NOTE: Comments
+
+pub async fn create_comment(
     conn: &mut MySqlConnection,
+    group_id: &str,
     restaurant_id: i32,
     user_id: &str,
-    group_id: &str,
-) -> Result<bool> {
-    let date_range = Period::current_period_date_range()?;
-
-    let query = sqlx::query_as!(
-        DbRating,
-        "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color
-         FROM ratings r
-         JOIN users u on u.id = r.user_id
-         JOIN restaurants rest on r.restaurant_id = rest.id
-         WHERE r.restaurant_id = ? AND r.user_id = ? AND r.group_id = ?
-         AND r.created_at >= ? AND DATE(r.created_at) <= ?",
-        restaurant_id,
-        user_id,
-        group_id,
-        date_range.0,
-        date_range.1
-    );
-    let ratings = query.fetch_all(conn).await?;
-
-    Ok(!ratings.is_empty())
-}
-
-// pub async fn is_restaurant_rated_by_user(
-//     conn: &mut MySqlConnection,
-//     restaurant_id: i32,
-//     user_id: &str,
-//     group_id: &str,
-// ) -> Result<bool> {
-//     let query = sqlx::query_as!(
-//         DbRating,
-//         "SELECT r.id, r.group_id, r.restaurant_id, rest.restaurant_code, r.user_id, r.score, r.username, r.created_at, r.updated_at, u.color
-//          FROM ratings r
-//          JOIN users u on u.id = r.user_id
-//          JOIN restaurants rest on r.restaurant_id = rest.id
-//          WHERE r.restaurant_id = ? AND r.user_id = ? AND r.group_id = ?",
-//         restaurant_id,
-//         user_id,
-//         group_id
-//     );
-//     let ratings = query.fetch_all(conn).await?;
-//
-//     Ok(!ratings.is_empty())
-// }
+    new_comment: &NewComment,
+) -> Result<Comment> {
+    if new_comment.body.trim().is_empty() {
+        return Err(anyhow!("Comment cannot be empty"));
+    }
 
-pub async fn update_rating(
-    conn: &mut MySqlConnection,
-    rating: &NewRating,
-    user_id: &str,
-) -> Result<Rating> {
     let mut tx = conn.begin().await?;
 
-    if !check_group_membership_exists(&mut tx, user_id, &rating.group_id).await? {
+    let restaurant_exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM restaurants WHERE id = ? AND group_id = ?)",
+        restaurant_id,
+        group_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if restaurant_exists == 0 {
         tx.rollback().await?;
-        return Err(anyhow!("User does not belong to group"));
+        return Err(anyhow!("Restaurant does not exist in this group"));
     }
 
-    let date_range = Period::current_period_date_range()?;
-    let updated_at = Utc::now().naive_utc();
+    // NOTE: Replies must stay in the thread of the restaurant they answer to
+    if let Some(parent_id) = new_comment.parent_id {
+        match get_comment(&mut tx, parent_id).await {
+            Ok(parent) if parent.group_id == group_id && parent.restaurant_id == restaurant_id => {}
+            Ok(_) | Err(_) => {
+                tx.rollback().await?;
+                return Err(anyhow!("Parent comment not found"));
+            }
+        }
+    }
 
-    let _ = match sqlx::query!(
-        "UPDATE ratings
-         SET score = ?, username = ?, updated_at = ?
-         WHERE group_id = ? AND user_id = ? AND restaurant_id = ? AND created_at >= ? AND created_at <= ?",
-        rating.score,
-        rating.username,
-        updated_at,
-        rating.group_id,
+    let created_at = Utc::now().naive_utc();
+    let updated_at = created_at;
+
+    let result = match sqlx::query!(
+        "INSERT INTO restaurant_comments (group_id, restaurant_id, user_id, parent_id, body, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        group_id,
+        restaurant_id,
         user_id,
-        rating.restaurant_id,
-        date_range.0,
-        date_range.1
+        new_comment.parent_id,
+        new_comment.body.trim(),
+        created_at,
+        updated_at,
     )
     .execute(&mut *tx)
     .await
@@ -1753,41 +5422,90 @@ pub async fn update_rating(
         Ok(query_result) => query_result,
         Err(err) => {
             tx.rollback().await?;
-            return Err(anyhow!("Could not update rating: {err}"));
+            return Err(anyhow!("Could not create comment: {err}"));
         }
     };
 
-    let updated_rating =
-        match get_rating_by_restaurant(&mut tx, user_id, &rating.group_id, rating.restaurant_id)
-            .await
-        {
-            Ok(updated_rating) => updated_rating,
-            Err(err) => {
-                tx.rollback().await?;
-                return Err(anyhow!("Could not get rating: {err}"));
-            }
-        };
+    let comment = match get_comment(&mut tx, result.last_insert_id() as i32).await {
+        Ok(comment) => comment,
+        Err(err) => {
+            tx.rollback().await?;
+            return Err(anyhow!("Could not get comment: {err}"));
+        }
+    };
 
     tx.commit().await?;
 
-    Ok(updated_rating)
+    Ok(comment)
 }
 
-pub async fn delete_rating(
+pub async fn get_comment(conn: &mut MySqlConnection, comment_id: i32) -> Result<Comment> {
+    let comment = sqlx::query_as!(
+        Comment,
+        "SELECT c.id, c.group_id, c.restaurant_id, c.user_id, u.username, u.color, c.parent_id, c.body, c.created_at, c.updated_at
+         FROM restaurant_comments c
+         JOIN users u on u.id = c.user_id
+         WHERE c.id = ?",
+        comment_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    comment.ok_or_else(|| anyhow!("Comment not found"))
+}
+
+pub async fn get_comments(
     conn: &mut MySqlConnection,
-    rating_id: i32,
-    user_id: &str,
     group_id: &str,
-) -> Result<MySqlQueryResult> {
-    let result = sqlx::query!(
-        "DELETE FROM ratings WHERE id = ? AND group_id = ? AND user_id = ?",
-        rating_id,
+    restaurant_id: i32,
+) -> Result<Vec<Comment>> {
+    let comments = sqlx::query_as!(
+        Comment,
+        "SELECT c.id, c.group_id, c.restaurant_id, c.user_id, u.username, u.color, c.parent_id, c.body, c.created_at, c.updated_at
+         FROM restaurant_comments c
+         JOIN users u on u.id = c.user_id
+         WHERE c.group_id = ? AND c.restaurant_id = ?
+         ORDER BY c.created_at, c.id",
         group_id,
-        user_id
+        restaurant_id
     )
-    .execute(conn)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(comments)
+}
+
+pub async fn update_comment(
+    conn: &mut MySqlConnection,
+    comment_id: i32,
+    update_comment: &UpdateComment,
+) -> Result<Comment> {
+    if update_comment.body.trim().is_empty() {
+        return Err(anyhow!("Comment cannot be empty"));
+    }
+
+    let updated_at = Utc::now().naive_utc();
+
+    sqlx::query!(
+        "UPDATE restaurant_comments SET body = ?, updated_at = ? WHERE id = ?",
+        update_comment.body.trim(),
+        updated_at,
+        comment_id
+    )
+    .execute(&mut *conn)
     .await?;
 
+    get_comment(conn, comment_id).await
+}
+
+pub async fn delete_comment(
+    conn: &mut MySqlConnection,
+    comment_id: i32,
+) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!("DELETE FROM restaurant_comments WHERE id = ?", comment_id)
+        .execute(conn)
+        .await?;
+
     Ok(result)
 }
 
@@ -1801,6 +5519,8 @@ pub async fn create_ip_blacklist(
         .iter()
         .map(|ip| NewIp {
             ip_address: ip.ip_address.as_str(),
+            reason: ip.reason.as_deref(),
+            expires_at: ip.expires_at,
         })
         .collect();
 
@@ -1809,8 +5529,10 @@ pub async fn create_ip_blacklist(
     let mut results = Vec::<MySqlQueryResult>::new();
     for ip in new_ips {
         let result = sqlx::query!(
-            "INSERT INTO ip_blacklist (ip_address) VALUES (?)",
-            ip.ip_address
+            "INSERT INTO ip_blacklist (ip_address, reason, expires_at) VALUES (?, ?, ?)",
+            ip.ip_address,
+            ip.reason,
+            ip.expires_at
         )
         .execute(&mut *tx)
         .await;
@@ -1827,13 +5549,27 @@ pub async fn create_ip_blacklist(
 }
 
 pub async fn get_ip_blacklist(conn: impl MySqlExecutor<'_>) -> Result<Vec<Ip>> {
-    let db_ips = sqlx::query_as!(Ip, "SELECT ip_address FROM ip_blacklist")
-        .fetch_all(conn)
-        .await?;
+    let db_ips = sqlx::query_as!(
+        Ip,
+        "SELECT ip_address, reason, expires_at FROM ip_blacklist ORDER BY created_at, id"
+    )
+    .fetch_all(conn)
+    .await?;
 
     Ok(db_ips)
 }
 
+pub async fn delete_expired_ips(conn: impl MySqlExecutor<'_>) -> Result<MySqlQueryResult> {
+    let result = sqlx::query!(
+        "DELETE FROM ip_blacklist WHERE expires_at <= ?",
+        Utc::now().naive_utc()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result)
+}
+
 pub async fn delete_ip(conn: &mut MySqlConnection, ip: &str) -> Result<MySqlQueryResult> {
     let result = sqlx::query!("DELETE FROM ip_blacklist WHERE ip_address = ?", ip)
         .execute(conn)
@@ -1879,6 +5615,7 @@ mod tests {
             username: USER_USERNAME_1.to_owned(),
             password: USER_PASSWORD_1.to_owned(),
             color: USER_COLOR_1.to_owned(),
+            email: None,
         };
 
         let mut conn = get_connection(&pool)
@@ -1897,29 +5634,25 @@ mod tests {
     }
 
     #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_get_users(pool: MySqlPool) -> Result<()> {
-        let users_result = get_users(&pool).await;
-        assert!(users_result.is_ok());
-
-        let users = users_result?;
-        assert!(!users.is_empty());
+    async fn test_get_users_sharing_groups(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
 
-        let user = users.first().unwrap();
-        assert_eq!(user.id, USER_ID_1);
-        assert_eq!(user.username, USER_USERNAME_1);
-        assert_eq!(user.password, USER_PASSWORD_1);
-        assert_eq!(user.color, USER_COLOR_1);
+        let users = get_users_sharing_groups(&mut conn, USER_ID_1).await?;
+        assert_eq!(
+            users.iter().map(|u| u.id.as_str()).collect::<Vec<_>>(),
+            vec![USER_ID_1, USER_ID_2]
+        );
+        assert_eq!(users[0].username, USER_USERNAME_1);
+        assert_eq!(users[0].color, USER_COLOR_1);
+
+        // NOTE: Users without a group only see themselves
+        let users = get_users_sharing_groups(&mut conn, USER_ID_3).await?;
         assert_eq!(
-            user.group_memberships
-                .iter()
-                .map(|gm| gm.group_id.clone())
-                .collect::<Vec<String>>(),
-            vec![GROUP_ID_1, GROUP_ID_2]
+            users.iter().map(|u| u.id.as_str()).collect::<Vec<_>>(),
+            vec![USER_ID_3]
         );
-        assert!(user
-            .group_memberships
-            .iter()
-            .all(|gm| gm.role == Role::Admin));
 
         Ok(())
     }
@@ -2024,6 +5757,7 @@ mod tests {
             username: NEW_USERNAME.to_owned(),
             password: USER_PASSWORD_1.to_owned(),
             color: NEW_COLOR.to_owned(),
+            email: None,
         };
 
         let update_user_result = update_user(&mut conn, &user.id, &user).await;
@@ -2173,11 +5907,10 @@ mod tests {
         assert_eq!(rating_notification.restaurant_id, rest_id);
         assert_eq!(rating_notification.group_id, GROUP_ID_1);
 
-        let current_period = Period::current_period()?;
-        assert_eq!(
-            Period::from_date(rating_notification.notified_at.into()),
-            current_period
-        );
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        assert!(current_period.contains(rating_notification.notified_at.date()));
 
         Ok(())
     }
@@ -2200,169 +5933,634 @@ mod tests {
         assert_eq!(group_membership.group.name, new_group.name);
         assert_eq!(group_membership.group.description, new_group.description);
 
+        let rating_dimensions =
+            get_rating_dimensions(&mut conn, &group_membership.group_id).await?;
+        assert_eq!(rating_dimensions.len(), 1);
+        assert_eq!(rating_dimensions[0].name, "overall");
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_get_group(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let group_result = get_group(&mut conn, GROUP_ID_1).await;
+        assert!(group_result.is_ok());
+
+        let group = group_result?;
+        assert_eq!(group.id, GROUP_ID_1);
+        assert_eq!(group.name, GROUP_NAME_1);
+        assert_eq!(group.description, Some(GROUP_DESCRIPTION_1.to_owned()));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_get_group_by_membership(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let group_result = get_group_by_membership(&mut conn, &1).await;
+        assert!(group_result.is_ok());
+
+        let group = group_result?;
+        assert_eq!(group.id, GROUP_ID_1);
+        assert_eq!(group.name, GROUP_NAME_1);
+        assert_eq!(group.description, Some(GROUP_DESCRIPTION_1.to_owned()));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_delete_group(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let group_result = delete_group(&mut conn, GROUP_ID_2).await;
+        assert!(group_result.is_ok());
+
+        let query_result = group_result?;
+        assert_eq!(query_result.rows_affected(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_create_group_membership(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let new_group_membership = NewGroupMembership {
+            group_id: GROUP_ID_2.to_owned(),
+            user_id: USER_ID_3.to_owned(),
+            role: Role::Member,
+            invited_by: None,
+        };
+        let create_group_membership_result =
+            create_group_membership(&mut conn, &new_group_membership).await;
+        assert!(create_group_membership_result.is_ok());
+
+        let group_membership = create_group_membership_result?;
+        assert_eq!(group_membership.group_id, GROUP_ID_2);
+        assert_eq!(group_membership.group.id, GROUP_ID_2);
+        assert_eq!(group_membership.group.name, GROUP_NAME_2);
+        assert_eq!(
+            group_membership.group.description,
+            Some(GROUP_DESCRIPTION_2.to_owned())
+        );
+        assert_eq!(group_membership.user_id, USER_ID_3);
+        assert_eq!(group_membership.role, Role::Member);
+
+        let create_group_membership_result =
+            create_group_membership(&mut conn, &new_group_membership).await;
+        assert!(create_group_membership_result.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_get_group_membership(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let get_group_membership_result = get_group_membership(&mut conn, &1).await;
+        assert!(get_group_membership_result.is_ok());
+
+        let group_membership = get_group_membership_result?;
+        assert_eq!(group_membership.group_id, GROUP_ID_1);
+        assert_eq!(group_membership.group.id, GROUP_ID_1);
+        assert_eq!(group_membership.group.name, GROUP_NAME_1);
+        assert_eq!(
+            group_membership.group.description,
+            Some(GROUP_DESCRIPTION_1.to_owned())
+        );
+        assert_eq!(group_membership.user_id, USER_ID_1);
+        assert_eq!(group_membership.role, Role::Admin);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_get_group_membership_by_user(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let get_group_membership_result = get_group_memberships_by_user(&mut conn, USER_ID_1).await;
+        assert!(get_group_membership_result.is_ok());
+
+        let group_memberships = get_group_membership_result?;
+        let mut group_memberships_iter = group_memberships.iter();
+
+        let first = group_memberships_iter.next();
+        assert!(first.is_some());
+        let first = first.unwrap();
+        assert_eq!(first.group_id, GROUP_ID_1);
+        assert_eq!(first.group.id, GROUP_ID_1);
+        assert_eq!(first.group.name, GROUP_NAME_1);
+        assert_eq!(
+            first.group.description,
+            Some(GROUP_DESCRIPTION_1.to_owned())
+        );
+        assert_eq!(first.user_id, USER_ID_1);
+        assert_eq!(first.role, Role::Admin);
+
+        let second = group_memberships_iter.next();
+        assert!(second.is_some());
+        let second = second.unwrap();
+        assert_eq!(second.group_id, GROUP_ID_2);
+        assert_eq!(second.group.id, GROUP_ID_2);
+        assert_eq!(second.group.name, GROUP_NAME_2);
+        assert_eq!(
+            second.group.description,
+            Some(GROUP_DESCRIPTION_2.to_owned())
+        );
+        assert_eq!(second.user_id, USER_ID_1);
+        assert_eq!(second.role, Role::Admin);
+
+        let third = group_memberships_iter.next();
+        assert!(third.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_delete_group_membership(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let group_result = delete_group_membership(&mut conn, &1).await;
+        assert!(group_result.is_ok());
+
+        let query_result = group_result?;
+        assert_eq!(query_result.rows_affected(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_update_group(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let update = UpdateGroup {
+            name: "renamed_group".to_owned(),
+            description: Some("renamed description".to_owned()),
+        };
+        let group = update_group(&mut conn, GROUP_ID_1, &update).await?;
+        assert_eq!(group.id, GROUP_ID_1);
+        assert_eq!(group.name, "renamed_group");
+        assert_eq!(group.description, Some("renamed description".to_owned()));
+
+        let update_result = update_group(&mut conn, "missing_group", &update).await;
+        assert!(update_result.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_get_group_members(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let group_members = get_group_members(&mut conn, GROUP_ID_1).await?;
+        assert_eq!(group_members.len(), 2);
+        assert_eq!(group_members[0].user_id, USER_ID_1);
+        assert_eq!(group_members[0].username, "test_username");
+        assert_eq!(group_members[0].role, Role::Admin);
+        assert_eq!(group_members[1].user_id, USER_ID_2);
+        assert_eq!(group_members[1].role, Role::Member);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_update_group_member_role(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        // NOTE: The only admin can't demote themselves
+        let demote_result =
+            update_group_member_role(&mut conn, GROUP_ID_1, USER_ID_1, &Role::Member).await;
+        assert!(demote_result.is_err());
+
+        let group_membership =
+            update_group_member_role(&mut conn, GROUP_ID_1, USER_ID_2, &Role::Admin).await?;
+        assert_eq!(group_membership.user_id, USER_ID_2);
+        assert_eq!(group_membership.role, Role::Admin);
+
+        let group_membership =
+            update_group_member_role(&mut conn, GROUP_ID_1, USER_ID_1, &Role::Member).await?;
+        assert_eq!(group_membership.user_id, USER_ID_1);
+        assert_eq!(group_membership.role, Role::Member);
+
+        let missing_result =
+            update_group_member_role(&mut conn, GROUP_ID_1, USER_ID_3, &Role::Admin).await;
+        assert!(missing_result.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_remove_group_member(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let leave_result = remove_group_member(&mut conn, GROUP_ID_1, USER_ID_1).await;
+        assert!(leave_result.is_err());
+
+        let query_result = remove_group_member(&mut conn, GROUP_ID_1, USER_ID_2).await?;
+        assert_eq!(query_result.rows_affected(), 1);
+        assert!(!check_group_membership_exists(&mut conn, USER_ID_2, GROUP_ID_1).await?);
+
+        let missing_result = remove_group_member(&mut conn, GROUP_ID_1, USER_ID_2).await;
+        assert!(missing_result.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
+    async fn test_update_group_period_scheme(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let group = get_group(&mut conn, GROUP_ID_1).await?;
+        assert_eq!(group.period_scheme, PeriodScheme::Quarterly);
+
+        let group =
+            update_group_period_scheme(&mut conn, GROUP_ID_1, &PeriodScheme::Monthly).await?;
+        assert_eq!(group.period_scheme, PeriodScheme::Monthly);
+
+        let today = Utc::now().date_naive();
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        assert_eq!(current_period.index, today.month0() as i32);
+        assert_eq!(current_period.start_date.month(), today.month());
+        assert_eq!(current_period.end_date.month(), today.month());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        path = "./../fixtures",
+        scripts("users", "restaurants", "ratings_incomplete")
+    ))]
+    async fn test_update_group_blind_ratings(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        assert!(!get_group(&mut conn, GROUP_ID_1).await?.blind_ratings);
+        assert!(!are_ratings_hidden(&pool, rest_id, GROUP_ID_1).await?);
+
+        let group = update_group_blind_ratings(&mut conn, GROUP_ID_1, true).await?;
+        assert!(group.blind_ratings);
+        assert!(are_ratings_hidden(&pool, rest_id, GROUP_ID_1).await?);
+
+        // NOTE: Ratings are revealed once every member has rated
+        let new_rating = NewRating {
+            group_id: GROUP_ID_1.to_owned(),
+            restaurant_id: rest_id,
+            user_id: USER_ID_2.to_owned(),
+            username: USER_USERNAME_2.to_owned(),
+            score: 7.0,
+            scores: Vec::new(),
+            review: None,
+            visit_id: None,
+        };
+        create_rating(&mut conn, &new_rating).await?;
+        assert!(!are_ratings_hidden(&pool, rest_id, GROUP_ID_1).await?);
+
         Ok(())
     }
 
-    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_get_group(pool: MySqlPool) -> Result<()> {
+    #[sqlx::test(fixtures(
+        path = "./../fixtures",
+        scripts("users", "restaurants", "ratings_complete")
+    ))]
+    async fn test_update_group_rating_scale(pool: MySqlPool) -> Result<()> {
         let mut conn = get_connection(&pool)
             .await
             .ok_or(anyhow!("Failed to get connection."))?;
 
-        let group_result = get_group(&mut conn, GROUP_ID_1).await;
-        assert!(group_result.is_ok());
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
 
-        let group = group_result?;
-        assert_eq!(group.id, GROUP_ID_1);
-        assert_eq!(group.name, GROUP_NAME_1);
-        assert_eq!(group.description, Some(GROUP_DESCRIPTION_1.to_owned()));
+        let group = get_group(&mut conn, GROUP_ID_1).await?;
+        assert_eq!(group.rating_scale(), RatingScale::default());
 
-        Ok(())
-    }
+        let invalid_scale = RatingScale {
+            min: 5.0,
+            max: 1.0,
+            step: 1.0,
+        };
+        assert!(
+            update_group_rating_scale(&mut conn, GROUP_ID_1, &invalid_scale)
+                .await
+                .is_err()
+        );
 
-    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_get_group_by_membership(pool: MySqlPool) -> Result<()> {
-        let mut conn = get_connection(&pool)
-            .await
-            .ok_or(anyhow!("Failed to get connection."))?;
+        let rating_scale = RatingScale {
+            min: 1.0,
+            max: 5.0,
+            step: 1.0,
+        };
+        assert!(
+            update_group_rating_scale(&mut conn, "missing_group_id", &rating_scale)
+                .await
+                .is_err()
+        );
+        let group = update_group_rating_scale(&mut conn, GROUP_ID_1, &rating_scale).await?;
+        assert_eq!(group.rating_scale(), rating_scale);
 
-        let group_result = get_group_by_membership(&mut conn, &1).await;
-        assert!(group_result.is_ok());
+        // NOTE: The fixture scores 10 and 8 out of 10 become 5 and 4.2 out of 5
+        let avg_rating = get_avg_rating(&pool, rest_id, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("Expected an average rating."))?;
+        assert!((avg_rating - 4.6).abs() < 0.01);
 
-        let group = group_result?;
-        assert_eq!(group.id, GROUP_ID_1);
-        assert_eq!(group.name, GROUP_NAME_1);
-        assert_eq!(group.description, Some(GROUP_DESCRIPTION_1.to_owned()));
+        let new_rating = NewRating {
+            group_id: GROUP_ID_1.to_owned(),
+            restaurant_id: rest_id,
+            user_id: USER_ID_2.to_owned(),
+            username: USER_USERNAME_2.to_owned(),
+            score: 8.0,
+            scores: Vec::new(),
+            review: None,
+            visit_id: None,
+        };
+        assert!(update_rating(&mut conn, &new_rating, USER_ID_2)
+            .await
+            .is_err());
 
         Ok(())
     }
 
     #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_delete_group(pool: MySqlPool) -> Result<()> {
+    async fn test_create_group_round(pool: MySqlPool) -> Result<()> {
         let mut conn = get_connection(&pool)
             .await
             .ok_or(anyhow!("Failed to get connection."))?;
 
-        let group_result = delete_group(&mut conn, GROUP_ID_2).await;
-        assert!(group_result.is_ok());
+        update_group_period_scheme(&mut conn, GROUP_ID_1, &PeriodScheme::Custom).await?;
+        assert!(get_current_period(&mut conn, GROUP_ID_1).await?.is_none());
 
-        let query_result = group_result?;
+        let today = Utc::now().date_naive();
+        let new_group_round = NewGroupRound {
+            name: "Summer season".to_owned(),
+            start_date: today - chrono::Duration::days(7),
+            end_date: today + chrono::Duration::days(7),
+        };
+        let group_round = create_group_round(&mut conn, GROUP_ID_1, &new_group_round).await?;
+        assert_eq!(group_round.group_id, GROUP_ID_1);
+        assert_eq!(group_round.name, new_group_round.name);
+
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        assert_eq!(current_period.index, group_round.id);
+        assert_eq!(current_period.name, new_group_round.name);
+
+        // NOTE: A round is only found in the year it starts in
+        let round_year = new_group_round.start_date.year();
+        let round_path = group_round.id.to_string();
+        let period = get_period_by_path(&mut conn, GROUP_ID_1, round_year, &round_path).await?;
+        assert_eq!(period, current_period);
+        assert!(
+            get_period_by_path(&mut conn, GROUP_ID_1, round_year - 1, &round_path)
+                .await
+                .is_err()
+        );
+        assert!(get_period_by_path(&mut conn, GROUP_ID_1, round_year, "Q1")
+            .await
+            .is_err());
+
+        // NOTE: Rounds of a group can't overlap
+        let overlapping_round = NewGroupRound {
+            name: "Overlap".to_owned(),
+            start_date: today,
+            end_date: today + chrono::Duration::days(30),
+        };
+        assert!(
+            create_group_round(&mut conn, GROUP_ID_1, &overlapping_round)
+                .await
+                .is_err()
+        );
+
+        let query_result = delete_group_round(&mut conn, group_round.id, GROUP_ID_1).await?;
         assert_eq!(query_result.rows_affected(), 1);
+        assert!(get_group_rounds(&mut conn, GROUP_ID_1).await?.is_empty());
 
         Ok(())
     }
 
-    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_create_group_membership(pool: MySqlPool) -> Result<()> {
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users", "restaurants")))]
+    async fn test_rating_dimensions(pool: MySqlPool) -> Result<()> {
         let mut conn = get_connection(&pool)
             .await
             .ok_or(anyhow!("Failed to get connection."))?;
 
-        let new_group_membership = NewGroupMembership {
-            group_id: GROUP_ID_2.to_owned(),
-            user_id: USER_ID_3.to_owned(),
-            role: Role::Member,
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        let food = create_rating_dimension(
+            &mut conn,
+            GROUP_ID_1,
+            &NewRatingDimension {
+                name: "food".to_owned(),
+                weight: 3.0,
+            },
+        )
+        .await?;
+        let service = create_rating_dimension(
+            &mut conn,
+            GROUP_ID_1,
+            &NewRatingDimension {
+                name: "service".to_owned(),
+                weight: 1.0,
+            },
+        )
+        .await?;
+        assert_eq!(get_rating_dimensions(&mut conn, GROUP_ID_1).await?.len(), 2);
+
+        // NOTE: Every dimension of the group needs a score
+        let mut new_rating = NewRating {
+            group_id: GROUP_ID_1.to_owned(),
+            restaurant_id: rest_id,
+            user_id: USER_ID_1.to_owned(),
+            username: "test_username".to_owned(),
+            score: 0.0,
+            scores: vec![NewDimensionScore {
+                dimension_id: food.id,
+                score: 8.0,
+            }],
+            review: None,
+            visit_id: None,
         };
-        let create_group_membership_result =
-            create_group_membership(&mut conn, &new_group_membership).await;
-        assert!(create_group_membership_result.is_ok());
+        assert!(create_rating(&mut conn, &new_rating).await.is_err());
 
-        let group_membership = create_group_membership_result?;
-        assert_eq!(group_membership.group_id, GROUP_ID_2);
-        assert_eq!(group_membership.group.id, GROUP_ID_2);
-        assert_eq!(group_membership.group.name, GROUP_NAME_2);
-        assert_eq!(
-            group_membership.group.description,
-            Some(GROUP_DESCRIPTION_2.to_owned())
-        );
-        assert_eq!(group_membership.user_id, USER_ID_3);
-        assert_eq!(group_membership.role, Role::Member);
+        new_rating.scores.push(NewDimensionScore {
+            dimension_id: service.id,
+            score: 4.0,
+        });
+        let rating = create_rating(&mut conn, &new_rating).await?;
+        assert_eq!(rating.score, 7.0);
+        assert_eq!(rating.scores.len(), 2);
 
-        let create_group_membership_result =
-            create_group_membership(&mut conn, &new_group_membership).await;
-        assert!(create_group_membership_result.is_err());
+        update_rating_dimension(
+            &mut conn,
+            GROUP_ID_1,
+            service.id,
+            &NewRatingDimension {
+                name: "service".to_owned(),
+                weight: 3.0,
+            },
+        )
+        .await?;
+        let rating = get_rating_by_restaurant(&mut conn, USER_ID_1, GROUP_ID_1, rest_id).await?;
+        assert_eq!(rating.score, 6.0);
+
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let dimension_averages =
+            get_dimension_averages_per_period(&mut conn, GROUP_ID_1, rest_id, &current_period)
+                .await?;
+        assert_eq!(dimension_averages.len(), 2);
+        assert_eq!(dimension_averages[0].name, "food");
+        assert_eq!(dimension_averages[0].average_score, 8.0);
+
+        delete_rating_dimension(&mut conn, GROUP_ID_1, service.id).await?;
+        let rating = get_rating_by_restaurant(&mut conn, USER_ID_1, GROUP_ID_1, rest_id).await?;
+        assert_eq!(rating.score, 8.0);
+
+        // NOTE: A group always keeps at least one dimension
+        assert!(delete_rating_dimension(&mut conn, GROUP_ID_1, food.id)
+            .await
+            .is_err());
 
         Ok(())
     }
 
     #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_get_group_membership(pool: MySqlPool) -> Result<()> {
+    async fn test_create_group_invite(pool: MySqlPool) -> Result<()> {
         let mut conn = get_connection(&pool)
             .await
             .ok_or(anyhow!("Failed to get connection."))?;
 
-        let get_group_membership_result = get_group_membership(&mut conn, &1).await;
-        assert!(get_group_membership_result.is_ok());
-
-        let group_membership = get_group_membership_result?;
-        assert_eq!(group_membership.group_id, GROUP_ID_1);
-        assert_eq!(group_membership.group.id, GROUP_ID_1);
-        assert_eq!(group_membership.group.name, GROUP_NAME_1);
-        assert_eq!(
-            group_membership.group.description,
-            Some(GROUP_DESCRIPTION_1.to_owned())
+        let new_group_invite = NewGroupInvite {
+            max_uses: Some(1),
+            expires_in_hours: Some(24),
+        };
+        let create_group_invite_result =
+            create_group_invite(&mut conn, GROUP_ID_2, USER_ID_1, &new_group_invite).await;
+        assert!(create_group_invite_result.is_ok());
+
+        let group_invite = create_group_invite_result?;
+        assert_eq!(group_invite.group_id, GROUP_ID_2);
+        assert_eq!(group_invite.created_by, USER_ID_1);
+        assert_eq!(group_invite.max_uses, Some(1));
+        assert_eq!(group_invite.uses, 0);
+        assert!(group_invite.expires_at.is_some());
+        assert!(group_invite.revoked_at.is_none());
+        assert!(!group_invite.code.is_empty());
+
+        let group_invites = get_group_invites(&mut conn, GROUP_ID_2).await?;
+        assert_eq!(group_invites.len(), 1);
+
+        // NOTE: Expiries past what a date can hold are refused instead of overflowing
+        let new_group_invite = NewGroupInvite {
+            max_uses: None,
+            expires_in_hours: Some(i64::MAX),
+        };
+        assert!(
+            create_group_invite(&mut conn, GROUP_ID_2, USER_ID_1, &new_group_invite)
+                .await
+                .is_err()
         );
-        assert_eq!(group_membership.user_id, USER_ID_1);
-        assert_eq!(group_membership.role, Role::Admin);
 
         Ok(())
     }
 
     #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_get_group_membership_by_user(pool: MySqlPool) -> Result<()> {
+    async fn test_redeem_group_invite(pool: MySqlPool) -> Result<()> {
         let mut conn = get_connection(&pool)
             .await
             .ok_or(anyhow!("Failed to get connection."))?;
 
-        let get_group_membership_result = get_group_memberships_by_user(&mut conn, USER_ID_1).await;
-        assert!(get_group_membership_result.is_ok());
+        let group_invite = create_group_invite(
+            &mut conn,
+            GROUP_ID_2,
+            USER_ID_1,
+            &NewGroupInvite {
+                max_uses: Some(1),
+                expires_in_hours: None,
+            },
+        )
+        .await?;
 
-        let group_memberships = get_group_membership_result?;
-        let mut group_memberships_iter = group_memberships.iter();
+        let redeem_group_invite_result =
+            redeem_group_invite(&mut conn, &group_invite.code, USER_ID_3).await;
+        assert!(redeem_group_invite_result.is_ok());
 
-        let first = group_memberships_iter.next();
-        assert!(first.is_some());
-        let first = first.unwrap();
-        assert_eq!(first.group_id, GROUP_ID_1);
-        assert_eq!(first.group.id, GROUP_ID_1);
-        assert_eq!(first.group.name, GROUP_NAME_1);
-        assert_eq!(
-            first.group.description,
-            Some(GROUP_DESCRIPTION_1.to_owned())
-        );
-        assert_eq!(first.user_id, USER_ID_1);
-        assert_eq!(first.role, Role::Admin);
+        let group_membership = redeem_group_invite_result?;
+        assert_eq!(group_membership.group_id, GROUP_ID_2);
+        assert_eq!(group_membership.user_id, USER_ID_3);
+        assert_eq!(group_membership.role, Role::Member);
+        assert_eq!(group_membership.invited_by, Some(USER_ID_1.to_owned()));
 
-        let second = group_memberships_iter.next();
-        assert!(second.is_some());
-        let second = second.unwrap();
-        assert_eq!(second.group_id, GROUP_ID_2);
-        assert_eq!(second.group.id, GROUP_ID_2);
-        assert_eq!(second.group.name, GROUP_NAME_2);
-        assert_eq!(
-            second.group.description,
-            Some(GROUP_DESCRIPTION_2.to_owned())
-        );
-        assert_eq!(second.user_id, USER_ID_1);
-        assert_eq!(second.role, Role::Admin);
+        let used_invite = get_group_invite_by_code(&mut conn, &group_invite.code)
+            .await?
+            .ok_or(anyhow!("Invite not found"))?;
+        assert_eq!(used_invite.uses, 1);
 
-        let third = group_memberships_iter.next();
-        assert!(third.is_none());
+        // NOTE: Single-use invite can't be redeemed twice
+        let redeem_group_invite_result =
+            redeem_group_invite(&mut conn, &group_invite.code, USER_ID_2).await;
+        assert!(redeem_group_invite_result.is_err());
 
         Ok(())
     }
 
     #[sqlx::test(fixtures(path = "./../fixtures", scripts("users")))]
-    async fn test_delete_group_membership(pool: MySqlPool) -> Result<()> {
+    async fn test_revoke_group_invite(pool: MySqlPool) -> Result<()> {
         let mut conn = get_connection(&pool)
             .await
             .ok_or(anyhow!("Failed to get connection."))?;
 
-        let group_result = delete_group_membership(&mut conn, &1).await;
-        assert!(group_result.is_ok());
+        let group_invite = create_group_invite(
+            &mut conn,
+            GROUP_ID_2,
+            USER_ID_1,
+            &NewGroupInvite {
+                max_uses: None,
+                expires_in_hours: None,
+            },
+        )
+        .await?;
 
-        let query_result = group_result?;
-        assert_eq!(query_result.rows_affected(), 1);
+        let revoke_group_invite_result =
+            revoke_group_invite(&mut conn, group_invite.id, GROUP_ID_2).await;
+        assert!(revoke_group_invite_result.is_ok());
+        assert_eq!(revoke_group_invite_result?.rows_affected(), 1);
+
+        let redeem_group_invite_result =
+            redeem_group_invite(&mut conn, &group_invite.code, USER_ID_3).await;
+        assert!(redeem_group_invite_result.is_err());
 
         Ok(())
     }
@@ -2457,14 +6655,218 @@ mod tests {
             restaurant_code: "new_restaurant".to_owned(),
             group_id: GROUP_ID_1.to_owned(),
             cuisine: "new_cuisine".to_owned(),
+            ..Default::default()
+        };
+
+        let update_restaurant_result =
+            update_restaurant(&mut conn, restaurant_id, &restaurant).await;
+        assert!(update_restaurant_result.is_ok());
+
+        let query_result = update_restaurant_result?;
+        assert_eq!(query_result.rows_affected(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("restaurants")))]
+    async fn test_restaurant_venues(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let venue = get_venue_by_name(&mut conn, "ARMYRA BY PAPAIOANNOU")
+            .await?
+            .ok_or(anyhow!("Venue not found"))?;
+        let fixture_restaurant = get_restaurant(&mut conn, 100).await?;
+        assert_eq!(fixture_restaurant.venue_id, venue.id);
+
+        // NOTE: A restaurant of the same name in another group is the same venue
+        let restaurant = create_restaurant(
+            &mut conn,
+            &Restaurant {
+                restaurant_code: venue.name.clone(),
+                group_id: GROUP_ID_2.to_owned(),
+                cuisine: venue.cuisine.clone(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        assert_eq!(restaurant.venue_id, venue.id);
+
+        // NOTE: Only the restaurant without its own cuisine follows the venue
+        let updated_venue = NewVenue {
+            name: venue.name.clone(),
+            cuisine: "Greek".to_owned(),
+        };
+        update_venue(&mut conn, venue.id, &updated_venue).await?;
+        assert_eq!(
+            get_restaurant(&mut conn, restaurant.id).await?.cuisine,
+            "Greek"
+        );
+        assert_eq!(
+            get_restaurant(&mut conn, 100).await?.cuisine,
+            "test_cuisine"
+        );
+
+        let new_venue = NewVenue {
+            name: "new_venue".to_owned(),
+            cuisine: "new_cuisine".to_owned(),
+        };
+        let venue = create_venue(&mut conn, &new_venue).await?;
+        let adopt = AdoptVenue {
+            group_id: GROUP_ID_2.to_owned(),
+            cuisine: None,
+            notes: Some("Book a table".to_owned()),
+        };
+        let restaurant = adopt_venue(&mut conn, &venue, &adopt).await?;
+        assert_eq!(restaurant.venue_id, venue.id);
+        assert_eq!(restaurant.restaurant_code, "new_venue");
+        assert_eq!(restaurant.cuisine, "new_cuisine");
+        assert_eq!(restaurant.notes.as_deref(), Some("Book a table"));
+
+        // NOTE: A group adopts a venue once
+        assert!(adopt_venue(&mut conn, &venue, &adopt).await.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        path = "./../fixtures",
+        scripts("users", "restaurants", "ratings_for_rest1")
+    ))]
+    async fn test_import_restaurants(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let import = ImportRestaurants {
+            source_group_id: GROUP_ID_1.to_owned(),
+            restaurant_ids: None,
+        };
+        let restaurants = import_restaurants(&mut conn, GROUP_ID_2, &import).await?;
+        assert_eq!(restaurants.len(), 1);
+
+        let source_restaurant = get_restaurant(&mut conn, 100).await?;
+        let restaurant = &restaurants[0];
+        assert_ne!(restaurant.id, source_restaurant.id);
+        assert_eq!(restaurant.group_id, GROUP_ID_2);
+        assert_eq!(restaurant.venue_id, source_restaurant.venue_id);
+        assert_eq!(restaurant.cuisine, source_restaurant.cuisine);
+
+        // NOTE: Ratings stay with the source group
+        let ratings: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM ratings WHERE restaurant_id = ?")
+                .bind(restaurant.id)
+                .fetch_one(&mut *conn)
+                .await?;
+        assert_eq!(ratings, 0);
+
+        // NOTE: Restaurants the group already has are skipped
+        let restaurants = import_restaurants(&mut conn, GROUP_ID_2, &import).await?;
+        assert!(restaurants.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("restaurants")))]
+    async fn test_restaurant_profile(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let new_restaurant = Restaurant {
+            restaurant_code: "profile_restaurant".to_owned(),
+            group_id: GROUP_ID_1.to_owned(),
+            cuisine: "Japanese".to_owned(),
+            address: Some("Ermou 1, Athens".to_owned()),
+            latitude: Some(37.9755),
+            longitude: Some(23.7348),
+            price_tier: Some(3),
+            website: Some("https://example.com".to_owned()),
+            phone: Some("+30 210 000 0000".to_owned()),
+            opening_hours: Some("Mon-Sun 13:00-23:00".to_owned()),
+            tags: vec!["omakase".to_owned(), "sushi".to_owned()],
+            ..Default::default()
+        };
+        let restaurant = create_restaurant(&mut conn, &new_restaurant).await?;
+        assert_eq!(
+            Restaurant {
+                id: restaurant.id,
+                venue_id: restaurant.venue_id,
+                active: true,
+                ..new_restaurant.clone()
+            },
+            restaurant
+        );
+
+        let updated_restaurant = Restaurant {
+            price_tier: Some(4),
+            menu_url: Some("https://example.com/menu".to_owned()),
+            tags: vec!["sushi".to_owned()],
+            ..restaurant.clone()
+        };
+        update_restaurant(&mut conn, restaurant.id, &updated_restaurant).await?;
+        assert_eq!(
+            get_restaurant(&mut conn, restaurant.id).await?,
+            updated_restaurant
+        );
+
+        let restaurants = get_restaurants(&mut conn, GROUP_ID_1).await?;
+        assert!(restaurants.contains(&updated_restaurant));
+
+        // NOTE: Imported restaurants keep their profile
+        let import = ImportRestaurants {
+            source_group_id: GROUP_ID_1.to_owned(),
+            restaurant_ids: Some(vec![restaurant.id]),
+        };
+        let imported_restaurants = import_restaurants(&mut conn, GROUP_ID_2, &import).await?;
+        assert_eq!(imported_restaurants.len(), 1);
+        assert_eq!(
+            imported_restaurants[0],
+            Restaurant {
+                id: imported_restaurants[0].id,
+                group_id: GROUP_ID_2.to_owned(),
+                ..updated_restaurant
+            }
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("restaurants", "restaurants_nearby")))]
+    async fn test_get_restaurants_near(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let near = |radius_meters: f64| GeoSearch {
+            latitude: 37.9755,
+            longitude: 23.7348,
+            radius_meters,
+        };
+        let ids = |restaurants: &[NearbyRestaurant]| {
+            restaurants
+                .iter()
+                .map(|nearby| nearby.restaurant.id)
+                .collect::<Vec<i32>>()
         };
 
-        let update_restaurant_result =
-            update_restaurant(&mut conn, restaurant_id, &restaurant).await;
-        assert!(update_restaurant_result.is_ok());
+        // NOTE: Nearest first and archived ones included, restaurant 100 has no coordinates
+        let restaurants = get_restaurants_near(&mut conn, GROUP_ID_1, &near(1_000.0)).await?;
+        assert_eq!(ids(&restaurants), vec![103, 101, 102]);
+        assert!((restaurants[1].distance_meters - 58.3).abs() < 1.0);
+        assert_eq!(restaurants[1].restaurant.tags, vec!["souvlaki".to_owned()]);
 
-        let query_result = update_restaurant_result?;
-        assert_eq!(query_result.rows_affected(), 1);
+        let restaurants = get_restaurants_near(&mut conn, GROUP_ID_1, &near(100.0)).await?;
+        assert_eq!(ids(&restaurants), vec![103, 101]);
+
+        let restaurants = get_restaurants_near(&mut conn, GROUP_ID_1, &near(10_000.0)).await?;
+        assert_eq!(ids(&restaurants), vec![103, 101, 102, 104]);
+        assert!((restaurants[3].distance_meters - 8_417.6).abs() < 10.0);
+
+        let restaurants = get_restaurants_near(&mut conn, GROUP_ID_2, &near(1_000.0)).await?;
+        assert_eq!(ids(&restaurants), vec![105]);
+        assert!(restaurants[0].distance_meters < 1.0);
 
         Ok(())
     }
@@ -2521,6 +6923,9 @@ mod tests {
             user_id: USER_ID_2.to_owned(),
             username: USER_USERNAME_2.to_owned(),
             score: 8.0,
+            scores: Vec::new(),
+            review: None,
+            visit_id: None,
         };
 
         let create_rating_result = create_rating(&mut conn, &new_rating).await;
@@ -2536,6 +6941,97 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures(
+        path = "./../fixtures",
+        scripts("users", "restaurants", "ratings_incomplete")
+    ))]
+    async fn test_rating_quorum(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        assert!(!is_restaurant_rating_complete(&pool, None, rest_id, GROUP_ID_1).await?);
+        assert_eq!(get_avg_rating(&pool, rest_id, GROUP_ID_1).await?, None);
+
+        assert!(update_group_rating_quorum(&mut conn, GROUP_ID_1, 0)
+            .await
+            .is_err());
+
+        // NOTE: One of the two members is enough for a 50% quorum
+        let group = update_group_rating_quorum(&mut conn, GROUP_ID_1, 50).await?;
+        assert_eq!(group.rating_quorum, 50);
+
+        assert!(is_restaurant_rating_complete(&pool, None, rest_id, GROUP_ID_1).await?);
+        assert_eq!(
+            get_avg_rating(&pool, rest_id, GROUP_ID_1).await?,
+            Some(10.0)
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        path = "./../fixtures",
+        scripts("users", "restaurants", "ratings_incomplete")
+    ))]
+    async fn test_member_absences(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+        let period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let (start_date, _) = period.to_date_range();
+
+        // NOTE: An absence that ended before the period started doesn't excuse the member
+        let past_absence = NewMemberAbsence {
+            user_id: USER_ID_2.to_owned(),
+            restaurant_id: None,
+            start_date: None,
+            end_date: Some(start_date - chrono::Duration::days(1)),
+        };
+        create_member_absence(&mut conn, GROUP_ID_1, &past_absence).await?;
+        assert!(!is_restaurant_rating_complete(&pool, None, rest_id, GROUP_ID_1).await?);
+
+        let absence = NewMemberAbsence {
+            user_id: USER_ID_2.to_owned(),
+            restaurant_id: Some(rest_id),
+            start_date: None,
+            end_date: None,
+        };
+        let absence = create_member_absence(&mut conn, GROUP_ID_1, &absence).await?;
+        assert_eq!(absence.restaurant_id, Some(rest_id));
+        assert_eq!(get_member_absences(&mut conn, GROUP_ID_1).await?.len(), 2);
+
+        assert!(is_restaurant_rating_complete(&pool, None, rest_id, GROUP_ID_1).await?);
+
+        let restaurants_with_avg_rating =
+            get_restaurants_with_avg_rating(&mut conn, GROUP_ID_1).await?;
+        let (restaurant, avg_rating, _) = &restaurants_with_avg_rating[0];
+        assert_eq!(restaurant.id, rest_id);
+        assert_eq!(*avg_rating, 10.0);
+
+        delete_member_absence(&mut conn, absence.id).await?;
+        assert!(!is_restaurant_rating_complete(&pool, None, rest_id, GROUP_ID_1).await?);
+
+        // NOTE: Only members of the group can be marked absent
+        let absence = NewMemberAbsence {
+            user_id: USER_ID_3.to_owned(),
+            restaurant_id: None,
+            start_date: None,
+            end_date: None,
+        };
+        assert!(create_member_absence(&mut conn, GROUP_ID_1, &absence)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures(
         path = "./../fixtures",
         scripts("users", "restaurants", "ratings_complete")
@@ -2595,7 +7091,7 @@ mod tests {
         // Don't check specific IDs since they're auto-generated
         let has_correct_rating = restaurants_with_avg_rating
             .iter()
-            .any(|(_, rating)| (*rating - 9.0).abs() < 0.01);
+            .any(|(_, rating, _)| (*rating - 9.0).abs() < 0.01);
         assert!(
             has_correct_rating,
             "Expected at least one restaurant with avg rating 9.0"
@@ -2604,6 +7100,92 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures(
+        path = "./../fixtures",
+        scripts("users", "restaurants", "ratings_incomplete")
+    ))]
+    async fn test_archive_restaurant(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        sqlx::query("UPDATE ratings SET restaurant_id = ? WHERE group_id = ?")
+            .bind(rest_id)
+            .bind(GROUP_ID_1)
+            .execute(&mut *conn)
+            .await?;
+        assert!(restaurant_has_history(&mut conn, rest_id).await?);
+
+        update_group_blind_ratings(&mut conn, GROUP_ID_1, true).await?;
+        assert!(are_ratings_hidden(&pool, rest_id, GROUP_ID_1).await?);
+
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let period_ratings =
+            get_ratings_by_restaurant_per_period(&mut conn, GROUP_ID_1, rest_id, &current_period)
+                .await?;
+
+        let restaurants_with_avg_rating =
+            get_restaurants_with_avg_rating(&mut conn, GROUP_ID_1).await?;
+        assert!(restaurants_with_avg_rating
+            .iter()
+            .any(|(restaurant, _, _)| restaurant.id == rest_id));
+
+        let query_result = update_restaurant_active(&mut conn, rest_id, GROUP_ID_2, false).await?;
+        assert_eq!(query_result.rows_affected(), 0);
+        let query_result = update_restaurant_active(&mut conn, rest_id, GROUP_ID_1, false).await?;
+        assert_eq!(query_result.rows_affected(), 1);
+        assert!(!get_restaurant(&mut conn, rest_id).await?.active);
+
+        // NOTE: The round can't be finished anymore, so a blind group gets to see it
+        assert!(!are_ratings_hidden(&pool, rest_id, GROUP_ID_1).await?);
+
+        // NOTE: Archived restaurants are left out of the rate flow, but keep their history
+        let restaurants_with_avg_rating =
+            get_restaurants_with_avg_rating(&mut conn, GROUP_ID_1).await?;
+        assert!(!restaurants_with_avg_rating
+            .iter()
+            .any(|(restaurant, _, _)| restaurant.id == rest_id));
+        assert_eq!(
+            get_ratings_by_restaurant_per_period(&mut conn, GROUP_ID_1, rest_id, &current_period)
+                .await?,
+            period_ratings
+        );
+
+        let new_rating = NewRating {
+            group_id: GROUP_ID_1.to_owned(),
+            restaurant_id: rest_id,
+            user_id: USER_ID_2.to_owned(),
+            username: USER_USERNAME_2.to_owned(),
+            score: 8.0,
+            scores: Vec::new(),
+            review: None,
+            visit_id: None,
+        };
+        assert!(create_rating(&mut conn, &new_rating).await.is_err());
+
+        let new_visit = NewVisit {
+            visit_date: Utc::now().date_naive(),
+            bill_total: None,
+            attendee_ids: Vec::new(),
+        };
+        let create_visit_result =
+            create_visit(&mut conn, GROUP_ID_1, rest_id, USER_ID_1, &new_visit).await;
+        assert!(create_visit_result.is_err());
+
+        update_restaurant_active(&mut conn, rest_id, GROUP_ID_1, true).await?;
+        assert!(get_restaurant(&mut conn, rest_id).await?.active);
+        assert!(are_ratings_hidden(&pool, rest_id, GROUP_ID_1).await?);
+        let create_visit_result =
+            create_visit(&mut conn, GROUP_ID_1, rest_id, USER_ID_1, &new_visit).await;
+        assert!(create_visit_result.is_ok());
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures(
         path = "./../fixtures",
         scripts("users", "restaurants", "ratings_incomplete")
@@ -2621,9 +7203,15 @@ mod tests {
             user_id: USER_ID_2.to_owned(),
             username: USER_USERNAME_2.to_owned(),
             score: 8.0,
+            scores: Vec::new(),
+            review: Some("Great grilled octopus".to_owned()),
+            visit_id: None,
         };
 
-        let (current_year, current_period, _) = Period::current_period_info()?;
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let current_year = current_period.year();
 
         let create_rating_result = create_rating(&mut conn, &new_rating).await;
         assert!(create_rating_result.is_ok());
@@ -2634,8 +7222,40 @@ mod tests {
         assert_eq!(rating.user_id, new_rating.user_id);
         assert_eq!(rating.username, new_rating.username);
         assert_eq!(rating.score, new_rating.score);
+        assert_eq!(rating.review, new_rating.review);
         assert_eq!(rating.created_at.year(), current_year);
-        assert_eq!(rating.period, current_period);
+        assert_eq!(rating.period, Some(current_period));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        path = "./../fixtures",
+        scripts("users", "restaurants", "ratings_complete")
+    ))]
+    async fn test_get_ratings_by_user(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+
+        // NOTE: Both groups of the user rate quarterly, so they share the current period
+        let ratings_by_user = get_ratings_by_user(&mut conn, USER_ID_1).await?;
+        assert_eq!(ratings_by_user.current_year, current_period.year());
+        assert_eq!(ratings_by_user.current_period, Some(current_period.clone()));
+        assert_eq!(ratings_by_user.current_period_ratings.len(), 1);
+        assert_eq!(
+            ratings_by_user.current_period_ratings[0].period,
+            Some(current_period)
+        );
+
+        update_group_period_scheme(&mut conn, GROUP_ID_2, &PeriodScheme::Monthly).await?;
+        let ratings_by_user = get_ratings_by_user(&mut conn, USER_ID_1).await?;
+        assert_eq!(ratings_by_user.current_period, None);
+        assert_eq!(ratings_by_user.current_period_ratings.len(), 1);
 
         Ok(())
     }
@@ -2657,9 +7277,12 @@ mod tests {
         let current_period_ratings = ratings_by_user.current_period_ratings;
         let historical_ratings = ratings_by_user.historical_ratings;
 
-        let (current_year, current_period, _) = Period::current_period_info()?;
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let current_year = current_period.year();
         assert_eq!(ratings_by_user.current_year, current_year);
-        assert_eq!(ratings_by_user.current_period, current_period);
+        assert_eq!(ratings_by_user.current_period, Some(current_period));
 
         assert!(!current_period_ratings.is_empty());
         assert_eq!(current_period_ratings.len(), 1);
@@ -2694,9 +7317,12 @@ mod tests {
         let current_period_ratings = ratings_by_restaurant.current_period_ratings;
         let historical_ratings = ratings_by_restaurant.historical_ratings;
 
-        let (current_year, current_period, _) = Period::current_period_info()?;
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let current_year = current_period.year();
         assert_eq!(ratings_by_restaurant.current_year, current_year);
-        assert_eq!(ratings_by_restaurant.current_period, current_period);
+        assert_eq!(ratings_by_restaurant.current_period, Some(current_period));
 
         assert!(!current_period_ratings.is_empty());
         assert_eq!(current_period_ratings.len(), 2);
@@ -2724,16 +7350,14 @@ mod tests {
 
         let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
 
-        let (current_year, current_period, _) = Period::current_period_info()?;
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let current_year = current_period.year();
 
-        let ratings_by_restaurant_result = get_ratings_by_restaurant_per_period(
-            &mut conn,
-            GROUP_ID_1,
-            rest_id,
-            current_year,
-            &current_period,
-        )
-        .await;
+        let ratings_by_restaurant_result =
+            get_ratings_by_restaurant_per_period(&mut conn, GROUP_ID_1, rest_id, &current_period)
+                .await;
         assert!(ratings_by_restaurant_result.is_ok());
 
         let ratings_by_restaurant = ratings_by_restaurant_result?;
@@ -2744,10 +7368,24 @@ mod tests {
             assert_eq!(rating.group_id, GROUP_ID_1);
             assert_eq!(rating.restaurant_id, rest_id);
             assert_eq!(rating.created_at.year(), current_year);
-            assert_eq!(rating.period, current_period);
+            assert_eq!(rating.period.as_ref(), Some(&current_period));
             assert!(rating.color.is_some());
         }
 
+        // NOTE: Ratings made later on the last day of the period still count towards it
+        let last_evening = current_period.end_date.and_hms_opt(18, 0, 0).unwrap();
+        sqlx::query!(
+            "UPDATE ratings SET created_at = ? WHERE restaurant_id = ?",
+            last_evening,
+            rest_id
+        )
+        .execute(&mut *conn)
+        .await?;
+        let ratings_by_restaurant =
+            get_ratings_by_restaurant_per_period(&mut conn, GROUP_ID_1, rest_id, &current_period)
+                .await?;
+        assert_eq!(ratings_by_restaurant.len(), 2);
+
         Ok(())
     }
 
@@ -2820,9 +7458,15 @@ mod tests {
             user_id: USER_ID_2.to_owned(),
             username: USER_USERNAME_2.to_owned(),
             score: 9.0,
+            scores: Vec::new(),
+            review: Some("Even better the second time".to_owned()),
+            visit_id: None,
         };
 
-        let (current_year, current_period, _) = Period::current_period_info()?;
+        let current_period = get_current_period(&mut conn, GROUP_ID_1)
+            .await?
+            .ok_or(anyhow!("No current period."))?;
+        let current_year = current_period.year();
 
         let update_rating_result = update_rating(&mut conn, &new_rating, USER_ID_2).await;
         assert!(update_rating_result.is_ok());
@@ -2833,8 +7477,9 @@ mod tests {
         assert_eq!(updated_rating.user_id, new_rating.user_id);
         assert_eq!(updated_rating.username, new_rating.username);
         assert_eq!(updated_rating.score, new_rating.score);
+        assert_eq!(updated_rating.review, new_rating.review);
         assert_eq!(updated_rating.updated_at.year(), current_year);
-        assert_eq!(updated_rating.period, current_period);
+        assert_eq!(updated_rating.period, Some(current_period));
 
         Ok(())
     }
@@ -2856,4 +7501,303 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users", "restaurants")))]
+    async fn test_create_visit(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+        let today = Utc::now().date_naive();
+
+        let new_visit = NewVisit {
+            visit_date: today,
+            bill_total: Some(84.5),
+            attendee_ids: Vec::new(),
+        };
+        let visit = create_visit(&mut conn, GROUP_ID_1, rest_id, USER_ID_1, &new_visit).await?;
+        assert_eq!(visit.visit_date, today);
+        assert_eq!(visit.bill_total, Some(84.5));
+        assert_eq!(visit.attendees, vec![USER_ID_1.to_owned()]);
+        assert!(!visit.is_complete);
+
+        let visits = get_visits(&mut conn, GROUP_ID_1, rest_id).await?;
+        assert_eq!(visits, vec![visit]);
+
+        // NOTE: Only group members can attend a visit of the group
+        let new_visit = NewVisit {
+            visit_date: today,
+            bill_total: None,
+            attendee_ids: vec![USER_ID_1.to_owned(), USER_ID_3.to_owned()],
+        };
+        let create_visit_result =
+            create_visit(&mut conn, GROUP_ID_1, rest_id, USER_ID_1, &new_visit).await;
+        assert!(create_visit_result.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users", "restaurants")))]
+    async fn test_visit_rating_completeness(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        let new_visit = NewVisit {
+            visit_date: Utc::now().date_naive(),
+            bill_total: None,
+            attendee_ids: vec![USER_ID_1.to_owned()],
+        };
+        let visit = create_visit(&mut conn, GROUP_ID_1, rest_id, USER_ID_1, &new_visit).await?;
+
+        // NOTE: Members who didn't attend can't rate the visit
+        let mut new_rating = NewRating {
+            group_id: GROUP_ID_1.to_owned(),
+            restaurant_id: rest_id,
+            user_id: USER_ID_2.to_owned(),
+            username: USER_USERNAME_2.to_owned(),
+            score: 6.0,
+            scores: Vec::new(),
+            review: None,
+            visit_id: Some(visit.id),
+        };
+        assert!(create_rating(&mut conn, &new_rating).await.is_err());
+
+        new_rating.user_id = USER_ID_1.to_owned();
+        let rating = create_rating(&mut conn, &new_rating).await?;
+        assert_eq!(rating.visit_id, Some(visit.id));
+        assert!(is_visit_rated_by_user(&mut conn, visit.id, USER_ID_1).await?);
+        assert!(!is_restaurant_rated_by_user(&mut conn, rest_id, USER_ID_1, GROUP_ID_1).await?);
+
+        // NOTE: Completeness only waits for the attendees, not for every member of the group
+        assert!(get_visit(&mut conn, visit.id).await?.is_complete);
+        assert!(is_restaurant_rating_complete(&pool, None, rest_id, GROUP_ID_1).await?);
+
+        let restaurants_with_avg_rating =
+            get_restaurants_with_avg_rating(&mut conn, GROUP_ID_1).await?;
+        let (_, avg_rating, _) = restaurants_with_avg_rating
+            .iter()
+            .find(|(restaurant, _, _)| restaurant.id == rest_id)
+            .ok_or(anyhow!("Restaurant not found"))?;
+        assert_eq!(*avg_rating, 6.0);
+
+        new_rating.score = 7.5;
+        let updated_rating = update_rating(&mut conn, &new_rating, USER_ID_1).await?;
+        assert_eq!(updated_rating.id, rating.id);
+        assert_eq!(updated_rating.score, 7.5);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users", "restaurants")))]
+    async fn test_menu_items(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        let new_menu_item = NewMenuItem {
+            name: " Grilled octopus ".to_owned(),
+            price: 16.5,
+            category: Some("Seafood".to_owned()),
+        };
+        let menu_item = create_menu_item(&mut conn, rest_id, &new_menu_item).await?;
+        assert_eq!(menu_item.name, "Grilled octopus");
+        assert_eq!(menu_item.category, Some("Seafood".to_owned()));
+
+        let update = NewMenuItem {
+            name: "Grilled octopus".to_owned(),
+            price: 18.0,
+            category: Some(" ".to_owned()),
+        };
+        let menu_item = update_menu_item(&mut conn, menu_item.id, &update).await?;
+        assert_eq!(menu_item.price, 18.0);
+        assert_eq!(menu_item.category, None);
+
+        let invalid = NewMenuItem {
+            name: "Fava".to_owned(),
+            price: -1.0,
+            category: None,
+        };
+        assert!(create_menu_item(&mut conn, rest_id, &invalid)
+            .await
+            .is_err());
+
+        assert_eq!(
+            get_menu_items(&mut conn, rest_id).await?,
+            vec![menu_item.clone()]
+        );
+
+        delete_menu_item(&mut conn, menu_item.id).await?;
+        assert!(get_menu_items(&mut conn, rest_id).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users", "restaurants")))]
+    async fn test_best_dishes(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        let mut menu_items = Vec::new();
+        for name in ["Fava", "Grilled octopus", "Moussaka"] {
+            let new_menu_item = NewMenuItem {
+                name: name.to_owned(),
+                price: 12.0,
+                category: None,
+            };
+            menu_items.push(create_menu_item(&mut conn, rest_id, &new_menu_item).await?);
+        }
+
+        rate_menu_item(&mut conn, GROUP_ID_1, menu_items[0].id, USER_ID_1, 6.0).await?;
+        rate_menu_item(&mut conn, GROUP_ID_1, menu_items[1].id, USER_ID_1, 9.0).await?;
+        rate_menu_item(&mut conn, GROUP_ID_1, menu_items[1].id, USER_ID_2, 7.0).await?;
+
+        // NOTE: Rating a dish again replaces the previous score
+        let dish_rating =
+            rate_menu_item(&mut conn, GROUP_ID_1, menu_items[1].id, USER_ID_2, 8.0).await?;
+        assert_eq!(dish_rating.score, 8.0);
+        assert_eq!(
+            get_dish_ratings(&mut conn, menu_items[1].id).await?.len(),
+            2
+        );
+
+        assert!(
+            rate_menu_item(&mut conn, GROUP_ID_1, menu_items[2].id, USER_ID_1, 10.5)
+                .await
+                .is_err()
+        );
+        assert!(
+            rate_menu_item(&mut conn, GROUP_ID_1, menu_items[2].id, USER_ID_3, 5.0)
+                .await
+                .is_err()
+        );
+
+        let best_dishes = get_best_dishes(&mut conn, rest_id, 5).await?;
+        assert_eq!(best_dishes.len(), 2);
+        assert_eq!(best_dishes[0].menu_item.id, menu_items[1].id);
+        assert_eq!(best_dishes[0].average_score, 8.5);
+        assert_eq!(best_dishes[0].num_ratings, 2);
+        assert_eq!(best_dishes[1].menu_item.id, menu_items[0].id);
+
+        assert_eq!(get_best_dishes(&mut conn, rest_id, 1).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "./../fixtures", scripts("users", "restaurants")))]
+    async fn test_create_comment(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let rest_id = get_test_restaurant_id(&mut conn, GROUP_ID_1).await;
+
+        let comment = create_comment(
+            &mut conn,
+            GROUP_ID_1,
+            rest_id,
+            USER_ID_1,
+            &NewComment {
+                body: "Best octopus in town".to_owned(),
+                parent_id: None,
+            },
+        )
+        .await?;
+        assert_eq!(comment.group_id, GROUP_ID_1);
+        assert_eq!(comment.restaurant_id, rest_id);
+        assert_eq!(comment.parent_id, None);
+
+        let reply = create_comment(
+            &mut conn,
+            GROUP_ID_1,
+            rest_id,
+            USER_ID_2,
+            &NewComment {
+                body: "Agreed".to_owned(),
+                parent_id: Some(comment.id),
+            },
+        )
+        .await?;
+        assert_eq!(reply.parent_id, Some(comment.id));
+        assert_eq!(reply.username, USER_USERNAME_2);
+
+        // NOTE: Replies can't point to a comment of another thread
+        assert!(create_comment(
+            &mut conn,
+            GROUP_ID_2,
+            rest_id,
+            USER_ID_1,
+            &NewComment {
+                body: "Wrong group".to_owned(),
+                parent_id: Some(comment.id),
+            },
+        )
+        .await
+        .is_err());
+
+        let updated_comment = update_comment(
+            &mut conn,
+            comment.id,
+            &UpdateComment {
+                body: "Best grilled octopus in town".to_owned(),
+            },
+        )
+        .await?;
+        assert_eq!(updated_comment.body, "Best grilled octopus in town");
+
+        delete_comment(&mut conn, comment.id).await?;
+        assert!(get_comments(&mut conn, GROUP_ID_1, rest_id)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_ip_blacklist(pool: MySqlPool) -> Result<()> {
+        let mut conn = get_connection(&pool)
+            .await
+            .ok_or(anyhow!("Failed to get connection."))?;
+
+        let seeded = get_ip_blacklist(&mut *conn).await?.len();
+        let now = Utc::now().naive_utc();
+        let ips = [
+            Ip {
+                ip_address: "2001:db8::/32".to_owned(),
+                reason: Some("Scanner".to_owned()),
+                expires_at: None,
+            },
+            Ip {
+                ip_address: "10.0.0.0/8".to_owned(),
+                reason: None,
+                expires_at: Some(now - chrono::Duration::minutes(1)),
+            },
+        ];
+        create_ip_blacklist(&mut conn, &ips).await?;
+
+        let ip_blacklist = get_ip_blacklist(&mut *conn).await?;
+        assert_eq!(ip_blacklist.len(), seeded + 2);
+        assert!(ip_blacklist.contains(&ips[0]));
+
+        // NOTE: Addresses are unique
+        assert!(create_ip_blacklist(&mut conn, &ips[..1]).await.is_err());
+
+        delete_expired_ips(&mut *conn).await?;
+        let ip_blacklist = get_ip_blacklist(&mut *conn).await?;
+        assert_eq!(ip_blacklist.len(), seeded + 1);
+
+        let result = delete_ip(&mut conn, "2001:db8::/32").await?;
+        assert_eq!(result.rows_affected(), 1);
+        assert_eq!(get_ip_blacklist(&mut *conn).await?.len(), seeded);
+
+        Ok(())
+    }
 }
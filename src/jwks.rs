@@ -0,0 +1,203 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use actix_web::rt::time::sleep;
+use anyhow::{anyhow, Result};
+use argon2::password_hash::rand_core::OsRng;
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use chrono::NaiveDateTime;
+use ed25519_dalek::{
+    pkcs8::{DecodePrivateKey, EncodePrivateKey},
+    SigningKey,
+};
+use jsonwebtoken::{
+    decode, decode_header, encode,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use sqlx::MySqlPool;
+
+use crate::{auth::ACCESS_TOKEN_TTL_MINUTES, db_models::DbSigningKey, db_util, models::UserClaims};
+
+// NOTE: A new key is published this long before it starts signing, so that every instance
+// and every service caching our JWKS already knows it when the first token shows up.
+pub const KEY_PUBLISH_LEAD_MINUTES: i64 = 60;
+const KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+pub type SigningKeys = Arc<RwLock<KeyRing>>;
+
+struct Key {
+    kid: String,
+    activates_at: NaiveDateTime,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    jwk: Jwk,
+}
+
+impl Key {
+    fn from_db(signing_key: DbSigningKey) -> Result<Self> {
+        let private_key = STANDARD.decode(&signing_key.private_key)?;
+        let public_key = SigningKey::from_pkcs8_der(&private_key)
+            .map_err(|err| anyhow!("Invalid signing key {}: {err}", signing_key.kid))?
+            .verifying_key();
+        let x = URL_SAFE_NO_PAD.encode(public_key.as_bytes());
+
+        Ok(Self {
+            encoding_key: EncodingKey::from_ed_der(&private_key),
+            decoding_key: DecodingKey::from_ed_components(&x)?,
+            jwk: Jwk {
+                common: CommonParameters {
+                    public_key_use: Some(PublicKeyUse::Signature),
+                    key_algorithm: Some(KeyAlgorithm::EdDSA),
+                    key_id: Some(signing_key.kid.clone()),
+                    ..Default::default()
+                },
+                algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x,
+                }),
+            },
+            kid: signing_key.kid,
+            activates_at: signing_key.activates_at,
+        })
+    }
+}
+
+/// The keys access tokens are signed and verified with, oldest first.
+pub struct KeyRing {
+    keys: Vec<Key>,
+}
+
+impl KeyRing {
+    pub fn from_db(signing_keys: Vec<DbSigningKey>) -> Result<Self> {
+        let mut keys = signing_keys
+            .into_iter()
+            .map(Key::from_db)
+            .collect::<Result<Vec<_>>>()?;
+        keys.sort_by_key(|key| key.activates_at);
+
+        Ok(Self { keys })
+    }
+
+    /// The newest key that has been published for long enough to sign with.
+    fn signing_key(&self) -> Option<&Key> {
+        let now = chrono::Utc::now().naive_utc();
+        self.keys.iter().rev().find(|key| key.activates_at <= now)
+    }
+
+    pub fn sign(&self, claims: &UserClaims) -> Result<String> {
+        let key = self
+            .signing_key()
+            .ok_or_else(|| anyhow!("No active signing key"))?;
+
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(key.kid.clone());
+
+        Ok(encode(&header, claims, &key.encoding_key)?)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<UserClaims> {
+        let kid = decode_header(token)?
+            .kid
+            .ok_or_else(|| anyhow!("Token has no key id"))?;
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .ok_or_else(|| anyhow!("Unknown signing key {kid}"))?;
+
+        let token_data =
+            decode::<UserClaims>(token, &key.decoding_key, &Validation::new(Algorithm::EdDSA))?;
+
+        Ok(token_data.claims)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().map(|key| key.jwk.clone()).collect(),
+        }
+    }
+}
+
+pub fn generate_signing_key(activates_at: NaiveDateTime) -> Result<DbSigningKey> {
+    let private_key = SigningKey::generate(&mut OsRng)
+        .to_pkcs8_der()
+        .map_err(|err| anyhow!("Could not encode signing key: {err}"))?;
+
+    Ok(DbSigningKey {
+        kid: uuid::Uuid::new_v4().to_string(),
+        private_key: STANDARD.encode(private_key.as_bytes()),
+        activates_at,
+    })
+}
+
+/// Schedules the next key once the current one is due for rotation and deletes keys that
+/// can no longer have signed a valid token, then returns the keys that are left.
+async fn rotate_signing_keys(pool: &MySqlPool, rotation: chrono::Duration) -> Result<KeyRing> {
+    let now = chrono::Utc::now().naive_utc();
+    let lead = chrono::Duration::minutes(KEY_PUBLISH_LEAD_MINUTES);
+
+    let mut signing_keys = db_util::get_signing_keys(pool).await?;
+
+    let next_activation = match signing_keys.last() {
+        None => Some(now),
+        Some(newest)
+            if newest.activates_at <= now && newest.activates_at + rotation <= now + lead =>
+        {
+            Some((newest.activates_at + rotation).max(now + lead))
+        }
+        Some(_) => None,
+    };
+    if let Some(activates_at) = next_activation {
+        let signing_key = generate_signing_key(activates_at)?;
+        db_util::create_signing_key(pool, &signing_key).await?;
+        signing_keys.push(signing_key);
+    }
+
+    // NOTE: A key stops signing once its successor activates, and is kept around until the
+    // last access token it signed has expired.
+    let retention = chrono::Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
+    let expired: Vec<String> = signing_keys
+        .windows(2)
+        .filter(|pair| pair[1].activates_at + retention < now)
+        .map(|pair| pair[0].kid.clone())
+        .collect();
+    for kid in &expired {
+        db_util::delete_signing_key(pool, kid).await?;
+    }
+    signing_keys.retain(|signing_key| !expired.contains(&signing_key.kid));
+
+    KeyRing::from_db(signing_keys)
+}
+
+pub async fn init_signing_keys(
+    pool: &MySqlPool,
+    rotation: chrono::Duration,
+) -> Result<SigningKeys> {
+    let key_ring = rotate_signing_keys(pool, rotation).await?;
+    Ok(Arc::new(RwLock::new(key_ring)))
+}
+
+pub async fn update_signing_keys(
+    db_pool: MySqlPool,
+    signing_keys: SigningKeys,
+    rotation: chrono::Duration,
+) {
+    loop {
+        sleep(KEY_REFRESH_INTERVAL).await;
+
+        match rotate_signing_keys(&db_pool, rotation).await {
+            Ok(key_ring) => *signing_keys.write().unwrap() = key_ring,
+            Err(err) => eprintln!("ERROR: failed rotating signing keys: {err}"),
+        }
+    }
+}
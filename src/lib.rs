@@ -1,8 +1,13 @@
 pub mod auth;
+pub mod client_ip;
 pub mod config;
 pub mod db_models;
 pub mod db_util;
+pub mod jwks;
 pub mod middleware;
 pub mod models;
+pub mod notifier;
 pub mod oidc;
+pub mod rate_limit;
 pub mod routes;
+pub mod totp;
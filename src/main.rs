@@ -1,4 +1,3 @@
-use actix_governor::Governor;
 use actix_web::{
     middleware::Logger,
     web::{self, Data},
@@ -7,9 +6,12 @@ use actix_web::{
 use dotenvy::dotenv;
 use env_logger::Env;
 use ratings_lib::auth;
+use ratings_lib::client_ip::TrustedProxies;
 use ratings_lib::config::AppConfig;
 use ratings_lib::db_util;
-use ratings_lib::middleware::{configure_cors, configure_governor, json_error_handler};
+use ratings_lib::jwks;
+use ratings_lib::middleware::{configure_cors, json_error_handler};
+use ratings_lib::rate_limit::{self, Bucket, RateLimited, RateLimiters};
 use ratings_lib::routes::*;
 use std::sync::{Arc, Mutex};
 
@@ -21,21 +23,36 @@ async fn main() -> anyhow::Result<()> {
 
     let app_config = AppConfig::load().expect("Failed to load configuration from environment");
 
-    let secret_key = Data::new(app_config.jwt_secret.clone());
-
     let db_pool = db_util::init_database(&app_config.database_url).await?;
     let push_client = db_util::init_push_notifications(
         app_config.vapid_public_key.clone(),
         app_config.vapid_private_key.clone(),
     )?;
 
+    let notifier = ratings_lib::notifier::Notifier::from_config(&app_config, &push_client)
+        .expect("Failed to initialize notifier");
+
     let oidc_config = ratings_lib::oidc::build_oidc_config(&app_config)
         .await
         .expect("Failed to initialize OIDC config");
 
-    let governor_conf = configure_governor();
+    let key_rotation = chrono::Duration::days(app_config.jwt_key_rotation_days);
+    let signing_keys = jwks::init_signing_keys(&db_pool, key_rotation).await?;
+    actix_web::rt::spawn(jwks::update_signing_keys(
+        db_pool.clone(),
+        signing_keys.clone(),
+        key_rotation,
+    ));
 
-    let ip_blacklist = Arc::new(Mutex::new(Vec::<String>::new()));
+    let trusted_proxies =
+        TrustedProxies::parse(&app_config.trusted_proxies, app_config.trusted_proxy_header)
+            .expect("Failed to parse TRUSTED_PROXIES");
+
+    let rate_limiters =
+        RateLimiters::new(&app_config.rate_limits).expect("Failed to configure rate limits");
+    actix_web::rt::spawn(rate_limit::prune_rate_limiters(rate_limiters.clone()));
+
+    let ip_blacklist: auth::IpBlacklist = Arc::new(Mutex::new(Vec::new()));
     actix_web::rt::spawn(auth::update_blacklist(
         db_pool.clone(),
         ip_blacklist.clone(),
@@ -43,29 +60,84 @@ async fn main() -> anyhow::Result<()> {
 
     let server_config = HttpServer::new(move || {
         App::new()
-            .wrap(Logger::new(
-                "%a \"%r\" %s %b %D \"%{Referer}i\" \"%{User-Agent}i\" %U %{r}a",
-            ))
-            .wrap(Governor::new(&governor_conf))
+            .wrap(
+                Logger::new(
+                    "%a \"%r\" %s %b %D \"%{Referer}i\" \"%{User-Agent}i\" %U %{client_ip}xi",
+                )
+                .custom_request_replace("client_ip", {
+                    let trusted_proxies = trusted_proxies.clone();
+                    move |req| match req.peer_addr() {
+                        Some(peer) => trusted_proxies
+                            .resolve(peer.ip(), req.headers())
+                            .to_string(),
+                        None => "-".to_string(),
+                    }
+                }),
+            )
             .wrap(configure_cors())
             .service(
                 web::scope("ratings")
+                    .wrap(RateLimited::new(Bucket::Requests))
                     .app_data(Data::new(db_pool.clone()))
                     .app_data(Data::new(push_client.clone()))
+                    .app_data(Data::new(notifier.clone()))
                     .app_data(Data::new(ip_blacklist.clone()))
-                    .app_data(secret_key.clone())
+                    .app_data(Data::new(trusted_proxies.clone()))
+                    .app_data(Data::new(rate_limiters.clone()))
+                    .app_data(Data::new(signing_keys.clone()))
                     .app_data(Data::new(oidc_config.clone()))
                     .app_data(web::JsonConfig::default().error_handler(json_error_handler))
                     .service(health_route)
+                    .service(jwks_route)
                     .service(get_users_route)
                     .service(get_user_oidc_links_route)
                     .service(unlink_oidc_route)
                     .service(update_user_route)
                     .service(change_password_route)
+                    .service(update_user_email_route)
+                    .service(get_totp_status_route)
+                    .service(enroll_totp_route)
+                    .service(verify_totp_route)
+                    .service(regenerate_recovery_codes_route)
+                    .service(disable_totp_route)
                     .service(delete_user_route)
+                    .service(get_sessions_route)
+                    .service(revoke_session_route)
+                    .service(update_user_superadmin_route)
+                    .service(get_ip_blacklist_route)
+                    .service(create_ip_blacklist_route)
+                    .service(delete_ip_blacklist_route)
                     .service(create_group_route)
                     .service(join_group_route)
+                    .service(update_group_route)
+                    .service(delete_group_route)
+                    .service(get_group_members_route)
+                    .service(update_group_member_role_route)
+                    .service(remove_group_member_route)
+                    .service(leave_group_route)
+                    .service(update_group_period_scheme_route)
+                    .service(update_group_blind_ratings_route)
+                    .service(update_group_rating_scale_route)
+                    .service(update_group_rating_quorum_route)
+                    .service(get_member_absences_route)
+                    .service(create_member_absence_route)
+                    .service(delete_member_absence_route)
+                    .service(get_group_rounds_route)
+                    .service(create_group_round_route)
+                    .service(delete_group_round_route)
+                    .service(get_rating_dimensions_route)
+                    .service(create_rating_dimension_route)
+                    .service(update_rating_dimension_route)
+                    .service(delete_rating_dimension_route)
+                    .service(create_group_invite_route)
+                    .service(get_group_invites_route)
+                    .service(revoke_group_invite_route)
                     .service(get_group_memberships_by_user_route)
+                    .service(get_venues_route)
+                    .service(create_venue_route)
+                    .service(update_venue_route)
+                    .service(adopt_venue_route)
+                    .service(import_restaurants_route)
                     .service(create_restaurant_route)
                     .service(update_restaurant_route)
                     .service(get_restaurant_route)
@@ -73,7 +145,24 @@ async fn main() -> anyhow::Result<()> {
                     .service(get_restaurants_with_avg_rating_route)
                     .service(get_restaurant_ratings_route)
                     .service(get_restaurant_ratings_per_period_route)
+                    .service(delete_rating_review_route)
+                    .service(get_restaurant_comments_route)
+                    .service(create_restaurant_comment_route)
+                    .service(update_restaurant_comment_route)
+                    .service(delete_restaurant_comment_route)
+                    .service(get_restaurant_visits_route)
+                    .service(create_restaurant_visit_route)
+                    .service(get_restaurant_visit_route)
+                    .service(delete_restaurant_visit_route)
+                    .service(get_menu_items_route)
+                    .service(create_menu_item_route)
+                    .service(update_menu_item_route)
+                    .service(delete_menu_item_route)
+                    .service(get_dish_ratings_route)
+                    .service(rate_menu_item_route)
+                    .service(get_best_dishes_route)
                     .service(is_restaurant_rating_complete_route)
+                    .service(update_restaurant_active_route)
                     .service(delete_restaurant_route)
                     .service(rate_restaurant_route)
                     .service(get_ratings_by_user_and_group_route)
@@ -84,6 +173,12 @@ async fn main() -> anyhow::Result<()> {
                         web::scope("auth")
                             .service(register_user_route)
                             .service(login_user_route)
+                            .service(login_totp_route)
+                            .service(refresh_token_route)
+                            .service(logout_route)
+                            .service(logout_all_route)
+                            .service(request_password_reset_route)
+                            .service(confirm_password_reset_route)
                             .route("/oidc/login", web::get().to(ratings_lib::oidc::oidc_login))
                             .route(
                                 "/oidc/callback",
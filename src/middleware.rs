@@ -1,21 +1,8 @@
 use actix_cors::Cors;
-use actix_governor::{
-    governor::{clock::QuantaInstant, middleware::NoOpMiddleware},
-    GovernorConfig, GovernorConfigBuilder, PeerIpKeyExtractor,
-};
 use actix_web::HttpResponse;
 
 use crate::models;
 
-pub fn configure_governor() -> GovernorConfig<PeerIpKeyExtractor, NoOpMiddleware<QuantaInstant>> {
-    GovernorConfigBuilder::default()
-        .permissive(true)
-        .seconds_per_request(60)
-        .burst_size(100)
-        .finish()
-        .unwrap()
-}
-
 pub fn configure_cors() -> Cors {
     let cors = Cors::default()
         .allow_any_origin()
@@ -1,29 +1,155 @@
 #![allow(dead_code)]
 
+use std::collections::HashMap;
+
 use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use web_push::{IsahcWebPushClient, SubscriptionInfo};
 
-use crate::db_models::{DbGroupMembership, DbRating};
+use crate::db_models::{
+    DbAverageRatingPerPeriod, DbDimensionAveragePerPeriod, DbGroupMembership, DbRating,
+    DbRestaurant, DbSession, DbVisit, NewDimensionScore, NewRating,
+};
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// An account as stored, password hash included. It is never sent to clients, they get a
+/// `UserProfile` of their own account or a `PublicUser` of someone else's.
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct User {
     pub id: String,
     pub token: String,
+    pub refresh_token: String,
     pub username: String,
     pub password: String,
     pub color: String,
+    pub email: Option<String>,
+    pub ratings: Vec<Rating>,
+    pub group_memberships: Vec<GroupMembership>,
+}
+
+/// The signed in user's own account
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub id: String,
+    pub token: String,
+    pub refresh_token: String,
+    pub username: String,
+    pub color: String,
+    pub email: Option<String>,
     pub ratings: Vec<Rating>,
     pub group_memberships: Vec<GroupMembership>,
 }
 
+impl UserProfile {
+    pub fn from_user(user: User) -> Self {
+        Self {
+            id: user.id,
+            token: user.token,
+            refresh_token: user.refresh_token,
+            username: user.username,
+            color: user.color,
+            email: user.email,
+            ratings: user.ratings,
+            group_memberships: user.group_memberships,
+        }
+    }
+}
+
+/// Someone the signed in user shares a group with
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublicUser {
+    pub id: String,
+    pub username: String,
+    pub color: String,
+}
+
+/// Trims an email address and checks that it looks deliverable. An empty address means none.
+pub fn normalize_email(email: Option<&str>) -> anyhow::Result<Option<String>> {
+    let email = match email.map(str::trim) {
+        None | Some("") => return Ok(None),
+        Some(email) => email,
+    };
+
+    let plausible = email.len() <= 255
+        && !email
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '<' || c == '>')
+        && matches!(
+            email.split_once('@'),
+            Some((local, domain)) if !local.is_empty() && domain.contains('.') && !domain.contains('@')
+        );
+    if !plausible {
+        return Err(anyhow::anyhow!("Invalid email address"));
+    }
+
+    Ok(Some(email.to_string()))
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UserClaims {
     pub id: String,
     pub username: String,
+    /// The session the token was issued for
+    #[serde(default)]
+    pub sid: String,
     pub exp: usize,
 }
 
+/// A short-lived access token paired with the refresh token that renews it
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthTokens {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+/// A signed-in device, identified by the refresh tokens it keeps rotating
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+impl Session {
+    pub fn from_db(db_session: DbSession, current_session_id: &str) -> Session {
+        Self {
+            current: db_session.id == current_session_id,
+            id: db_session.id,
+            user_agent: db_session.user_agent,
+            ip_address: db_session.ip_address,
+            created_at: db_session.created_at,
+            last_seen_at: db_session.last_seen_at,
+        }
+    }
+}
+
+/// Returned instead of a session when a password login still needs a second factor
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoginChallenge {
+    pub challenge_token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TotpStatus {
+    pub enabled: bool,
+    pub recovery_codes_left: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
 #[derive(Default, Clone)]
 pub struct PushClient {
     pub vapid_public_key: String,
@@ -71,15 +197,162 @@ impl std::convert::From<String> for Role {
     }
 }
 
+impl Role {
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        match self {
+            Role::Admin => true,
+            Role::Member => matches!(permission, Permission::ViewGroup | Permission::Contribute),
+        }
+    }
+}
+
+/// Everything a group role can allow. Routes ask for one of these through `auth::authorize`,
+/// which also grants every permission to superadmins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Seeing the restaurants, ratings and members of the group
+    ViewGroup,
+    /// Rating, commenting and logging visits, dishes or one's own absences
+    Contribute,
+    /// Editing or deleting the group along with its periods, rounds, scale and dimensions
+    ManageGroup,
+    /// Changing roles, removing or excusing members and handling invites
+    ManageMembers,
+    /// Creating, editing and deleting restaurants and their dishes
+    ManageRestaurants,
+    /// Editing or deleting what other members posted
+    Moderate,
+}
+
+impl Permission {
+    pub fn denied_message(&self) -> &'static str {
+        match self {
+            Permission::ViewGroup | Permission::Contribute => "User is not a member of this group",
+            Permission::ManageGroup => "Only admins can manage the group",
+            Permission::ManageMembers => "Only admins can manage members",
+            Permission::ManageRestaurants => "Only admins can manage restaurants",
+            Permission::Moderate => "Only the author or an admin can change this",
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Group {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    pub period_scheme: PeriodScheme,
+    pub blind_ratings: bool,
+    pub scale_min: f32,
+    pub scale_max: f32,
+    pub scale_step: f32,
+    pub rating_quorum: i32,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
+impl Group {
+    pub fn rating_scale(&self) -> RatingScale {
+        RatingScale {
+            min: self.scale_min,
+            max: self.scale_max,
+            step: self.scale_step,
+        }
+    }
+}
+
+/// Whether enough of the expected raters have rated, with the quorum given as a
+/// percentage of them. Nothing is complete before the first rating comes in.
+pub fn is_quorum_reached(rating_quorum: i32, rated: i64, expected: i64) -> bool {
+    rated > 0 && rated * 100 >= expected * i64::from(rating_quorum)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RatingScale {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+impl Default for RatingScale {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 10.0,
+            step: 0.5,
+        }
+    }
+}
+
+impl RatingScale {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(self.min.is_finite() && self.max.is_finite() && self.step.is_finite()) {
+            return Err(anyhow::anyhow!(
+                "Rating scale values must be finite numbers"
+            ));
+        }
+        if self.min >= self.max {
+            return Err(anyhow::anyhow!("Rating scale min must be lower than max"));
+        }
+        if self.step <= 0.0 || self.step > self.max - self.min {
+            return Err(anyhow::anyhow!(
+                "Rating scale step must be greater than 0 and fit within the scale"
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_score(&self, score: f32, dimension_id: Option<i32>) -> Result<(), InvalidScore> {
+        let steps = (score - self.min) / self.step;
+        let on_step = (steps - steps.round()).abs() < 1e-3;
+
+        if score.is_finite() && score >= self.min && score <= self.max && on_step {
+            Ok(())
+        } else {
+            Err(InvalidScore {
+                dimension_id,
+                score,
+                scale: *self,
+            })
+        }
+    }
+
+    pub fn check_rating(&self, rating: &NewRating) -> Result<(), InvalidScore> {
+        if rating.scores.is_empty() {
+            return self.check_score(rating.score, None);
+        }
+
+        rating
+            .scores
+            .iter()
+            .try_for_each(|s| self.check_score(s.score, Some(s.dimension_id)))
+    }
+
+    /// Maps a score linearly from this scale onto another one.
+    pub fn rescale(&self, score: f32, to: &RatingScale) -> f32 {
+        to.min + (score - self.min) * (to.max - to.min) / (self.max - self.min)
+    }
+}
+
+/// A score that doesn't fit the rating scale of its group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvalidScore {
+    pub dimension_id: Option<i32>,
+    pub score: f32,
+    pub scale: RatingScale,
+}
+
+impl std::fmt::Display for InvalidScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Score {} must be between {} and {} in steps of {}",
+            self.score, self.scale.min, self.scale.max, self.scale.step
+        )
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GroupMembership {
     pub id: i32,
@@ -87,6 +360,7 @@ pub struct GroupMembership {
     pub group: Group,
     pub user_id: String,
     pub role: Role,
+    pub invited_by: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -99,19 +373,456 @@ impl GroupMembership {
             group: db_group.clone(),
             user_id: db_group_membership.user_id.clone(),
             role: db_group_membership.role.clone(),
+            invited_by: db_group_membership.invited_by.clone(),
             created_at: db_group_membership.created_at,
             updated_at: db_group_membership.updated_at,
         }
     }
 }
 
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupMember {
+    pub id: i32,
+    pub user_id: String,
+    pub username: String,
+    pub color: String,
+    pub role: Role,
+    pub invited_by: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupInvite {
+    pub id: i32,
+    pub code: String,
+    pub group_id: String,
+    pub created_by: String,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl GroupInvite {
+    pub fn is_redeemable(&self, now: NaiveDateTime) -> bool {
+        if self.revoked_at.is_some() {
+            return false;
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return false;
+        }
+        if self.max_uses.is_some_and(|max_uses| self.uses >= max_uses) {
+            return false;
+        }
+
+        true
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Restaurant {
     pub id: i32,
+    /// The catalogue venue the restaurant was adopted from, assigned when it's created
+    #[serde(default)]
+    pub venue_id: i32,
     pub restaurant_code: String,
     pub group_id: String,
+    /// The cuisine of the group, or of the venue if the group doesn't override it
+    pub cuisine: String,
+    pub notes: Option<String>,
+    pub address: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// From 1, cheap, to 4, expensive
+    pub price_tier: Option<i8>,
+    pub website: Option<String>,
+    pub menu_url: Option<String>,
+    pub phone: Option<String>,
+    pub opening_hours: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Archived restaurants are inactive, changed through `PUT /restaurants/{id}/active` only
+    #[serde(default)]
+    pub active: bool,
+}
+
+const MAX_RESTAURANT_TAGS: usize = 20;
+const MAX_TAG_LENGTH: usize = 50;
+const MAX_URL_LENGTH: usize = 2048;
+const MAX_PHONE_LENGTH: usize = 32;
+
+impl Restaurant {
+    pub fn from_db(db_restaurant: DbRestaurant, tags: Vec<String>) -> Restaurant {
+        Self {
+            id: db_restaurant.id,
+            venue_id: db_restaurant.venue_id,
+            restaurant_code: db_restaurant.restaurant_code,
+            group_id: db_restaurant.group_id,
+            cuisine: db_restaurant.cuisine,
+            notes: db_restaurant.notes,
+            address: db_restaurant.address,
+            latitude: db_restaurant.latitude,
+            longitude: db_restaurant.longitude,
+            price_tier: db_restaurant.price_tier,
+            website: db_restaurant.website,
+            menu_url: db_restaurant.menu_url,
+            phone: db_restaurant.phone,
+            opening_hours: db_restaurant.opening_hours,
+            tags,
+            active: db_restaurant.active,
+        }
+    }
+
+    /// Trims the profile of a restaurant and checks it before it's saved. Empty optional fields
+    /// become missing ones and tags are lowercased, without duplicates.
+    pub fn normalize(mut self) -> anyhow::Result<Restaurant> {
+        self.restaurant_code = self.restaurant_code.trim().to_string();
+        self.cuisine = self.cuisine.trim().to_string();
+        if self.restaurant_code.is_empty() || self.restaurant_code.chars().count() > 255 {
+            return Err(anyhow::anyhow!(
+                "Restaurant name must be between 1 and 255 characters"
+            ));
+        }
+        if self.cuisine.is_empty() || self.cuisine.chars().count() > 255 {
+            return Err(anyhow::anyhow!(
+                "Cuisine must be between 1 and 255 characters"
+            ));
+        }
+
+        self.notes = non_empty(self.notes);
+        self.opening_hours = non_empty(self.opening_hours);
+        self.address = non_empty(self.address);
+        if self
+            .address
+            .as_ref()
+            .is_some_and(|address| address.chars().count() > 255)
+        {
+            return Err(anyhow::anyhow!(
+                "Address can't be longer than 255 characters"
+            ));
+        }
+
+        match (self.latitude, self.longitude) {
+            (None, None) => {}
+            (Some(latitude), Some(longitude))
+                if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) => {}
+            (Some(_), Some(_)) => {
+                return Err(anyhow::anyhow!(
+                    "Latitude must be between -90 and 90, longitude between -180 and 180"
+                ))
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Latitude and longitude must be given together"
+                ))
+            }
+        }
+
+        if self
+            .price_tier
+            .is_some_and(|price_tier| !(1..=4).contains(&price_tier))
+        {
+            return Err(anyhow::anyhow!("Price tier must be between 1 and 4"));
+        }
+
+        self.website = normalize_url(self.website, "Website")?;
+        self.menu_url = normalize_url(self.menu_url, "Menu URL")?;
+
+        self.phone = non_empty(self.phone);
+        if let Some(phone) = &self.phone {
+            let valid = phone.len() <= MAX_PHONE_LENGTH
+                && phone.chars().filter(char::is_ascii_digit).count() >= 3
+                && phone
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || " +-().".contains(c));
+            if !valid {
+                return Err(anyhow::anyhow!("Invalid phone number"));
+            }
+        }
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in &self.tags {
+            let tag = tag.trim().to_lowercase();
+            if tag.is_empty() || tags.contains(&tag) {
+                continue;
+            }
+            if tag.chars().count() > MAX_TAG_LENGTH {
+                return Err(anyhow::anyhow!(
+                    "Tags can't be longer than {MAX_TAG_LENGTH} characters"
+                ));
+            }
+            tags.push(tag);
+        }
+        if tags.len() > MAX_RESTAURANT_TAGS {
+            return Err(anyhow::anyhow!(
+                "A restaurant can't have more than {MAX_RESTAURANT_TAGS} tags"
+            ));
+        }
+        tags.sort();
+        self.tags = tags;
+
+        Ok(self)
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Only web links are accepted, so that they can be opened from the restaurant card.
+fn normalize_url(url: Option<String>, field: &str) -> anyhow::Result<Option<String>> {
+    let Some(url) = non_empty(url) else {
+        return Ok(None);
+    };
+
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .map(|rest| rest.split(['/', '?', '#']).next().unwrap_or_default());
+    let valid = url.len() <= MAX_URL_LENGTH
+        && host.is_some_and(|host| !host.is_empty())
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control());
+    if !valid {
+        return Err(anyhow::anyhow!("{field} must be an http or https link"));
+    }
+
+    Ok(Some(url))
+}
+
+/// The order `GET /restaurants` lists restaurants in
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestaurantSort {
+    #[default]
+    Id,
+    Name,
+    Cuisine,
+    /// Cheapest first, restaurants without a price tier last
+    PriceTier,
+}
+
+impl std::str::FromStr for RestaurantSort {
+    type Err = anyhow::Error;
+
+    fn from_str(sort: &str) -> anyhow::Result<Self> {
+        match sort.to_ascii_lowercase().as_str() {
+            "id" => Ok(RestaurantSort::Id),
+            "name" => Ok(RestaurantSort::Name),
+            "cuisine" => Ok(RestaurantSort::Cuisine),
+            "price_tier" => Ok(RestaurantSort::PriceTier),
+            _ => Err(anyhow::anyhow!(
+                "Invalid sort, expected id, name, cuisine or price_tier: {sort}"
+            )),
+        }
+    }
+}
+
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+const DEFAULT_SEARCH_RADIUS_METERS: f64 = 1_000.0;
+const MAX_SEARCH_RADIUS_METERS: f64 = 50_000.0;
+
+/// A location to find restaurants around, from the `lat`, `lng` and `radius` query of
+/// `GET /restaurants`. The radius is in meters and defaults to a kilometre, a short walk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoSearch {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_meters: f64,
+}
+
+impl GeoSearch {
+    fn from_query(query: &HashMap<String, String>) -> anyhow::Result<Option<GeoSearch>> {
+        let value = |name: &str| -> anyhow::Result<Option<f64>> {
+            non_empty(query.get(name).cloned())
+                .map(|value| {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|value| value.is_finite())
+                        .ok_or_else(|| anyhow::anyhow!("{name} must be a number"))
+                })
+                .transpose()
+        };
+
+        let radius_meters = value("radius")?;
+        let (latitude, longitude) = match (value("lat")?, value("lng")?, radius_meters) {
+            (Some(latitude), Some(longitude), _) => (latitude, longitude),
+            (None, None, None) => return Ok(None),
+            _ => return Err(anyhow::anyhow!("lat and lng must be given together")),
+        };
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(anyhow::anyhow!(
+                "lat must be between -90 and 90, lng between -180 and 180"
+            ));
+        }
+
+        let radius_meters = radius_meters.unwrap_or(DEFAULT_SEARCH_RADIUS_METERS);
+        if radius_meters <= 0.0 || radius_meters > MAX_SEARCH_RADIUS_METERS {
+            return Err(anyhow::anyhow!(
+                "radius must be more than 0 and at most {MAX_SEARCH_RADIUS_METERS} meters"
+            ));
+        }
+
+        Ok(Some(Self {
+            latitude,
+            longitude,
+            radius_meters,
+        }))
+    }
+
+    /// The latitude and longitude ranges that hold the whole radius, so that candidates can be
+    /// looked up by index before their exact distance is worked out. Near the poles or the
+    /// antimeridian the box gives up on narrowing the longitude.
+    pub fn bounding_box(&self) -> ((f64, f64), (f64, f64)) {
+        let latitude_delta = (self.radius_meters / EARTH_RADIUS_METERS).to_degrees();
+        let latitudes = (
+            self.latitude - latitude_delta,
+            self.latitude + latitude_delta,
+        );
+        if latitudes.0 <= -90.0 || latitudes.1 >= 90.0 {
+            return (
+                (latitudes.0.max(-90.0), latitudes.1.min(90.0)),
+                (-180.0, 180.0),
+            );
+        }
+
+        let longitude_delta = latitude_delta / self.latitude.to_radians().cos();
+        let longitudes = (
+            self.longitude - longitude_delta,
+            self.longitude + longitude_delta,
+        );
+        if longitudes.0 < -180.0 || longitudes.1 > 180.0 {
+            return (latitudes, (-180.0, 180.0));
+        }
+
+        (latitudes, longitudes)
+    }
+
+    /// The great-circle distance to a point, by the haversine formula.
+    pub fn distance_meters(&self, latitude: f64, longitude: f64) -> f64 {
+        let latitude_delta = (latitude - self.latitude).to_radians();
+        let longitude_delta = (longitude - self.longitude).to_radians();
+        let haversine = (latitude_delta / 2.0).sin().powi(2)
+            + self.latitude.to_radians().cos()
+                * latitude.to_radians().cos()
+                * (longitude_delta / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_METERS * haversine.sqrt().min(1.0).asin()
+    }
+}
+
+/// A restaurant found by a `GeoSearch`, with how far it is from the searched location.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearbyRestaurant {
+    #[serde(flatten)]
+    pub restaurant: Restaurant,
+    pub distance_meters: f64,
+}
+
+/// Narrows down and orders the restaurants of a group, from the query of `GET /restaurants`.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RestaurantFilter {
+    pub cuisine: Option<String>,
+    pub tag: Option<String>,
+    pub price_tier: Option<i8>,
+    /// Archived restaurants are left out unless asked for, they can't be rated anymore
+    pub include_archived: bool,
+    pub sort: RestaurantSort,
+    /// Only restaurants around a location, nearest first whatever the sort
+    pub near: Option<GeoSearch>,
+}
+
+impl RestaurantFilter {
+    pub fn from_query(query: &HashMap<String, String>) -> anyhow::Result<RestaurantFilter> {
+        let value = |name: &str| non_empty(query.get(name).cloned());
+
+        let price_tier = match value("price_tier") {
+            Some(price_tier) => Some(
+                price_tier
+                    .parse::<i8>()
+                    .ok()
+                    .filter(|price_tier| (1..=4).contains(price_tier))
+                    .ok_or_else(|| anyhow::anyhow!("Price tier must be between 1 and 4"))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            cuisine: value("cuisine"),
+            tag: value("tag").map(|tag| tag.to_lowercase()),
+            price_tier,
+            include_archived: value("include_archived")
+                .is_some_and(|include_archived| include_archived.eq_ignore_ascii_case("true")),
+            sort: value("sort")
+                .map(|sort| sort.parse())
+                .transpose()?
+                .unwrap_or_default(),
+            near: GeoSearch::from_query(query)?,
+        })
+    }
+
+    fn matches(&self, restaurant: &Restaurant) -> bool {
+        (self.include_archived || restaurant.active)
+            && self
+                .cuisine
+                .as_ref()
+                .is_none_or(|cuisine| restaurant.cuisine.eq_ignore_ascii_case(cuisine))
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| restaurant.tags.contains(tag))
+            && self
+                .price_tier
+                .is_none_or(|price_tier| restaurant.price_tier == Some(price_tier))
+    }
+
+    pub fn apply(&self, restaurants: Vec<Restaurant>) -> Vec<Restaurant> {
+        let mut restaurants: Vec<Restaurant> = restaurants
+            .into_iter()
+            .filter(|restaurant| self.matches(restaurant))
+            .collect();
+
+        match self.sort {
+            RestaurantSort::Id => restaurants.sort_by_key(|restaurant| restaurant.id),
+            RestaurantSort::Name => restaurants.sort_by_cached_key(|restaurant| {
+                (restaurant.restaurant_code.to_lowercase(), restaurant.id)
+            }),
+            RestaurantSort::Cuisine => restaurants.sort_by_cached_key(|restaurant| {
+                (
+                    restaurant.cuisine.to_lowercase(),
+                    restaurant.restaurant_code.to_lowercase(),
+                    restaurant.id,
+                )
+            }),
+            RestaurantSort::PriceTier => restaurants.sort_by_key(|restaurant| {
+                (
+                    restaurant.price_tier.is_none(),
+                    restaurant.price_tier,
+                    restaurant.id,
+                )
+            }),
+        }
+
+        restaurants
+    }
+
+    /// Like `apply`, for restaurants that are already ordered by distance.
+    pub fn apply_near(&self, restaurants: Vec<NearbyRestaurant>) -> Vec<NearbyRestaurant> {
+        restaurants
+            .into_iter()
+            .filter(|nearby| self.matches(&nearby.restaurant))
+            .collect()
+    }
+}
+
+/// A restaurant of the shared catalogue, which groups adopt into their own list.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Venue {
+    pub id: i32,
+    pub name: String,
     pub cuisine: String,
-    // TODO: Add active, so that we can enable or disable in the admin panel
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -120,72 +831,130 @@ pub struct MenuItem {
     pub restaurant_id: i32,
     pub name: String,
     pub price: f32,
+    pub category: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum Period {
-    Q1,
-    Q2,
-    Q3,
-    Q4,
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DishRating {
+    pub id: i32,
+    pub menu_item_id: i32,
+    pub user_id: String,
+    pub username: String,
+    pub color: Option<String>,
+    pub score: f32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
 }
 
-impl std::convert::From<i32> for Period {
-    fn from(value: i32) -> Self {
-        match value {
-            0 => Self::Q1,
-            1 => Self::Q2,
-            2 => Self::Q3,
-            3 => Self::Q4,
-            _ => unreachable!(),
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatedMenuItem {
+    pub menu_item: MenuItem,
+    pub average_score: f64,
+    pub num_ratings: i64,
+}
+
+/// Parsed case-insensitively wherever it's read, unknown schemes are an error.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(try_from = "String")]
+#[sqlx(rename_all = "lowercase")]
+pub enum PeriodScheme {
+    Monthly,
+    #[default]
+    Quarterly,
+    Yearly,
+    Custom,
+}
+
+impl std::str::FromStr for PeriodScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(period_scheme: &str) -> anyhow::Result<Self> {
+        match period_scheme.to_ascii_lowercase().as_str() {
+            "monthly" => Ok(PeriodScheme::Monthly),
+            "quarterly" => Ok(PeriodScheme::Quarterly),
+            "yearly" => Ok(PeriodScheme::Yearly),
+            "custom" => Ok(PeriodScheme::Custom),
+            _ => Err(anyhow::anyhow!(
+                "Invalid period scheme, expected monthly, quarterly, yearly or custom: {period_scheme}"
+            )),
         }
     }
 }
 
-impl Period {
-    pub fn from_date(date: NaiveDate) -> Self {
-        match date.month() {
-            1..=3 => Period::Q1,
-            4..=6 => Period::Q2,
-            7..=9 => Period::Q3,
-            _ => Period::Q4,
-        }
+impl TryFrom<String> for PeriodScheme {
+    type Error = anyhow::Error;
+
+    fn try_from(period_scheme: String) -> anyhow::Result<Self> {
+        period_scheme.parse()
     }
+}
 
-    pub fn to_month_range(&self) -> (u32, u32) {
+impl PeriodScheme {
+    pub fn periods_per_year(&self) -> Option<u32> {
         match self {
-            Period::Q1 => (1, 3),
-            Period::Q2 => (4, 6),
-            Period::Q3 => (7, 9),
-            Period::Q4 => (10, 12),
+            PeriodScheme::Monthly => Some(12),
+            PeriodScheme::Quarterly => Some(4),
+            PeriodScheme::Yearly => Some(1),
+            PeriodScheme::Custom => None,
         }
     }
+}
 
-    pub fn to_date_range(&self, year: i32) -> anyhow::Result<(NaiveDate, NaiveDate)> {
-        let month_range = self.to_month_range();
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupRound {
+    pub id: i32,
+    pub group_id: String,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub created_at: NaiveDateTime,
+}
+
+/// A rating round of a group. For calendar schemes `index` is the zero based position of the
+/// period within its year, for custom schemes it is the id of the `GroupRound`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Period {
+    pub index: i32,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
 
-        let start_date = NaiveDate::from_ymd_opt(year, month_range.0, 1).ok_or_else(|| {
+impl Period {
+    pub fn from_index(scheme: &PeriodScheme, year: i32, index: i32) -> anyhow::Result<Self> {
+        let periods_per_year = scheme
+            .periods_per_year()
+            .ok_or_else(|| anyhow::anyhow!("Custom periods are defined by the group's rounds"))?;
+        if index < 0 || index as u32 >= periods_per_year {
+            return Err(anyhow::anyhow!(
+                "Invalid period index {index} for {scheme:?} periods"
+            ));
+        }
+
+        let months_per_period = 12 / periods_per_year;
+        let start_month = index as u32 * months_per_period + 1;
+        let end_month = start_month + months_per_period - 1;
+
+        let start_date = NaiveDate::from_ymd_opt(year, start_month, 1).ok_or_else(|| {
             anyhow::anyhow!(
                 "Invalid start date for year: {}, month: {}",
                 year,
-                month_range.0
+                start_month
             )
         })?;
 
         let end_date = NaiveDate::from_ymd_opt(
-            if month_range.1 == 12 { year + 1 } else { year },
-            if month_range.1 == 12 {
-                1
-            } else {
-                month_range.1 + 1
-            },
+            if end_month == 12 { year + 1 } else { year },
+            if end_month == 12 { 1 } else { end_month + 1 },
             1,
         )
         .ok_or_else(|| {
             anyhow::anyhow!(
                 "Invalid end date calculation for year: {}, month: {}",
                 year,
-                month_range.1
+                end_month
             )
         })?
         .pred_opt()
@@ -193,31 +962,201 @@ impl Period {
             anyhow::anyhow!(
                 "Failed to calculate the last day of the month for year: {}, month: {}",
                 year,
-                month_range.1
+                end_month
             )
         })?;
 
-        Ok((start_date, end_date))
+        let name = match scheme {
+            PeriodScheme::Monthly => format!("{year}-{start_month:02}"),
+            PeriodScheme::Quarterly => format!("{year}-Q{}", index + 1),
+            _ => year.to_string(),
+        };
+
+        Ok(Self {
+            index,
+            name,
+            start_date,
+            end_date,
+        })
+    }
+
+    pub fn from_date(scheme: &PeriodScheme, date: NaiveDate) -> Option<Self> {
+        let periods_per_year = scheme.periods_per_year()?;
+        let index = date.month0() / (12 / periods_per_year);
+
+        Self::from_index(scheme, date.year(), index as i32).ok()
+    }
+
+    pub fn from_db_columns(
+        scheme: &PeriodScheme,
+        year: i32,
+        index: i32,
+        round_name: Option<&str>,
+        round_start_date: Option<NaiveDate>,
+        round_end_date: Option<NaiveDate>,
+    ) -> Option<Self> {
+        match scheme {
+            PeriodScheme::Custom => Some(Self {
+                index,
+                name: round_name?.to_owned(),
+                start_date: round_start_date?,
+                end_date: round_end_date?,
+            }),
+            _ => Self::from_index(scheme, year, index).ok(),
+        }
     }
 
-    pub fn current_period_info() -> anyhow::Result<(i32, Period, (NaiveDate, NaiveDate))> {
-        let now = chrono::Utc::now();
-        let current_year = now.year();
-        let current_period = Period::from_date(now.date_naive());
-        let date_range = current_period.to_date_range(current_year)?;
+    /// Reads the `{period}` of a ratings path, the index of the period within its year or the id
+    /// of a custom round. Quarter names like `Q2`, which the path took before periods were
+    /// configurable, are still read for quarterly groups.
+    pub fn parse_index(scheme: &PeriodScheme, period: &str) -> anyhow::Result<i32> {
+        if let Ok(index) = period.parse::<i32>() {
+            return Ok(index);
+        }
 
-        Ok((current_year, current_period, date_range))
+        let quarter = period
+            .strip_prefix(['Q', 'q'])
+            .and_then(|quarter| quarter.parse::<i32>().ok())
+            .filter(|quarter| (1..=4).contains(quarter));
+        match (scheme, quarter) {
+            (PeriodScheme::Quarterly, Some(quarter)) => Ok(quarter - 1),
+            (_, Some(_)) => Err(anyhow::anyhow!(
+                "Quarters only apply to groups rating quarterly: {period}"
+            )),
+            (_, None) => Err(anyhow::anyhow!("Invalid period: {period}")),
+        }
     }
 
-    pub fn current_period() -> anyhow::Result<Period> {
-        Ok(Self::current_period_info()?.1)
+    pub fn from_round(round: &GroupRound) -> Self {
+        Self {
+            index: round.id,
+            name: round.name.clone(),
+            start_date: round.start_date,
+            end_date: round.end_date,
+        }
     }
 
-    pub fn current_period_date_range() -> anyhow::Result<(NaiveDate, NaiveDate)> {
-        Ok(Self::current_period_info()?.2)
+    /// The period containing `date`, or `None` if a custom scheme has no round covering it.
+    pub fn containing(
+        scheme: &PeriodScheme,
+        rounds: &[GroupRound],
+        date: NaiveDate,
+    ) -> Option<Self> {
+        match scheme {
+            PeriodScheme::Custom => rounds
+                .iter()
+                .filter(|round| round.start_date <= date && date <= round.end_date)
+                .max_by_key(|round| round.start_date)
+                .map(Self::from_round),
+            _ => Self::from_date(scheme, date),
+        }
+    }
+
+    pub fn current(scheme: &PeriodScheme, rounds: &[GroupRound]) -> Option<Self> {
+        Self::containing(scheme, rounds, chrono::Utc::now().date_naive())
+    }
+
+    pub fn year(&self) -> i32 {
+        self.start_date.year()
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && date <= self.end_date
+    }
+
+    pub fn to_date_range(&self) -> (NaiveDate, NaiveDate) {
+        (self.start_date, self.end_date)
     }
 }
 
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatingDimension {
+    pub id: i32,
+    pub group_id: String,
+    pub name: String,
+    pub weight: f32,
+    pub position: i32,
+    pub created_at: NaiveDateTime,
+}
+
+impl RatingDimension {
+    /// Resolves the per dimension scores of a rating and its weighted overall score.
+    /// Ratings without per dimension scores apply their single score to every dimension.
+    pub fn resolve_scores(
+        dimensions: &[RatingDimension],
+        score: f32,
+        scores: &[NewDimensionScore],
+    ) -> anyhow::Result<(f32, Vec<NewDimensionScore>)> {
+        if scores.is_empty() {
+            let scores = dimensions
+                .iter()
+                .map(|dimension| NewDimensionScore {
+                    dimension_id: dimension.id,
+                    score,
+                })
+                .collect();
+            return Ok((score, scores));
+        }
+
+        if let Some(unknown) = scores
+            .iter()
+            .find(|s| !dimensions.iter().any(|d| d.id == s.dimension_id))
+        {
+            return Err(anyhow::anyhow!(
+                "Dimension {} does not belong to this group",
+                unknown.dimension_id
+            ));
+        }
+
+        let mut weighted_sum = 0.0;
+        let mut total_weight = 0.0;
+        for dimension in dimensions {
+            let mut dimension_scores = scores.iter().filter(|s| s.dimension_id == dimension.id);
+            let dimension_score = match (dimension_scores.next(), dimension_scores.next()) {
+                (Some(dimension_score), None) => dimension_score,
+                (None, _) => {
+                    return Err(anyhow::anyhow!(
+                        "Missing score for dimension '{}'",
+                        dimension.name
+                    ))
+                }
+                (Some(_), Some(_)) => {
+                    return Err(anyhow::anyhow!(
+                        "Duplicate score for dimension '{}'",
+                        dimension.name
+                    ))
+                }
+            };
+
+            weighted_sum += dimension_score.score * dimension.weight;
+            total_weight += dimension.weight;
+        }
+
+        if total_weight <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Dimension weights must add up to more than 0"
+            ));
+        }
+
+        Ok((weighted_sum / total_weight, scores.to_vec()))
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionScore {
+    pub dimension_id: i32,
+    pub name: String,
+    pub score: f32,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionAverage {
+    pub dimension_id: i32,
+    pub name: String,
+    pub weight: f32,
+    pub average_score: f64,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rating {
     pub id: i32,
@@ -228,9 +1167,12 @@ pub struct Rating {
     pub score: f32,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
-    pub period: Period,
+    pub period: Option<Period>,
     pub group_id: String,
     pub color: Option<String>,
+    pub review: Option<String>,
+    pub visit_id: Option<i32>,
+    pub scores: Vec<DimensionScore>,
 }
 
 impl Default for Rating {
@@ -239,7 +1181,7 @@ impl Default for Rating {
         Self {
             created_at: default_datetime,
             updated_at: default_datetime,
-            period: Period::from_date(default_datetime.date()),
+            period: None,
             id: Default::default(),
             restaurant_id: Default::default(),
             restaurant_code: Default::default(),
@@ -248,6 +1190,9 @@ impl Default for Rating {
             score: Default::default(),
             group_id: Default::default(),
             color: Default::default(),
+            review: Default::default(),
+            visit_id: Default::default(),
+            scores: Default::default(),
         }
     }
 }
@@ -265,6 +1210,8 @@ impl Rating {
         created_at: NaiveDateTime,
         updated_at: NaiveDateTime,
         color: Option<String>,
+        review: Option<String>,
+        visit_id: Option<i32>,
     ) -> Rating {
         Self {
             id,
@@ -275,9 +1222,12 @@ impl Rating {
             score,
             created_at,
             updated_at,
-            period: Period::from_date(updated_at.date()),
+            period: None,
             group_id,
             color,
+            review,
+            visit_id,
+            scores: Vec::new(),
         }
     }
 
@@ -293,18 +1243,142 @@ impl Rating {
             db_rating.created_at,
             db_rating.updated_at,
             db_rating.color.clone(),
+            db_rating.review.clone(),
+            db_rating.visit_id,
         )
     }
+
+    pub fn with_period(mut self, period: Option<&Period>) -> Rating {
+        self.period = period.cloned();
+        self
+    }
+
+    pub fn with_scores(mut self, scores: Vec<DimensionScore>) -> Rating {
+        self.scores = scores;
+        self
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    pub id: i32,
+    pub group_id: String,
+    pub restaurant_id: i32,
+    pub visit_date: NaiveDate,
+    pub bill_total: Option<f64>,
+    pub created_by: String,
+    pub created_at: NaiveDateTime,
+    pub attendees: Vec<String>,
+    pub is_complete: bool,
+}
+
+impl Visit {
+    pub fn from_db(db_visit: DbVisit, attendees: Vec<String>, is_complete: bool) -> Visit {
+        Self {
+            id: db_visit.id,
+            group_id: db_visit.group_id,
+            restaurant_id: db_visit.restaurant_id,
+            visit_date: db_visit.visit_date,
+            bill_total: db_visit.bill_total,
+            created_by: db_visit.created_by,
+            created_at: db_visit.created_at,
+            attendees,
+            is_complete,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemberAbsence {
+    pub id: i32,
+    pub group_id: String,
+    pub user_id: String,
+    pub restaurant_id: Option<i32>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: i32,
+    pub group_id: String,
+    pub restaurant_id: i32,
+    pub user_id: String,
+    pub username: String,
+    pub color: Option<String>,
+    pub parent_id: Option<i32>,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RatingsByPeriod {
     pub current_year: i32,
-    pub current_period: Period,
+    pub current_period: Option<Period>,
     pub current_period_ratings: Vec<Rating>,
+    pub current_dimension_averages: Vec<DimensionAverage>,
+    pub submitted_ratings: usize,
+    pub ratings_hidden: bool,
     pub historical_ratings: Vec<AverageRatingPerPeriod>,
 }
 
+impl RatingsByPeriod {
+    /// Keeps only the caller's own rating of the current period, for groups that rate blind.
+    /// The current period's averages are left out of the history too, they'd give it away.
+    pub fn blinded(mut self, user_id: &str) -> Self {
+        self.current_period_ratings
+            .retain(|rating| rating.user_id == user_id);
+        self.current_dimension_averages.clear();
+        self.historical_ratings
+            .retain(|average| Some(&average.period) != self.current_period.as_ref());
+        self.ratings_hidden = true;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RatingsForPeriod {
+    pub period: Period,
+    pub average_score: Option<f64>,
+    pub dimension_averages: Vec<DimensionAverage>,
+    pub submitted_ratings: usize,
+    pub ratings_hidden: bool,
+    pub ratings: Vec<Rating>,
+}
+
+impl RatingsForPeriod {
+    pub fn new(
+        period: Period,
+        ratings: Vec<Rating>,
+        dimension_averages: Vec<DimensionAverage>,
+    ) -> Self {
+        let average_score = match ratings.len() {
+            0 => None,
+            len => Some(ratings.iter().map(|r| r.score as f64).sum::<f64>() / len as f64),
+        };
+
+        Self {
+            period,
+            average_score,
+            dimension_averages,
+            submitted_ratings: ratings.len(),
+            ratings_hidden: false,
+            ratings,
+        }
+    }
+
+    /// Keeps only the caller's own rating, for groups that rate blind.
+    pub fn blinded(mut self, user_id: &str) -> Self {
+        self.ratings.retain(|rating| rating.user_id == user_id);
+        self.average_score = None;
+        self.dimension_averages.clear();
+        self.ratings_hidden = true;
+        self
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AverageRatingPerPeriod {
     pub restaurant_id: i32,
@@ -312,11 +1386,79 @@ pub struct AverageRatingPerPeriod {
     pub year: i32,
     pub period: Period,
     pub average_score: f64,
+    pub dimension_averages: Vec<DimensionAverage>,
+}
+
+impl AverageRatingPerPeriod {
+    pub fn from_db(db_average_rating: &DbAverageRatingPerPeriod) -> Option<Self> {
+        let period = Period::from_db_columns(
+            &db_average_rating.period_scheme,
+            db_average_rating.year,
+            db_average_rating.period_index,
+            db_average_rating.round_name.as_deref(),
+            db_average_rating.round_start_date,
+            db_average_rating.round_end_date,
+        )?;
+
+        Some(Self {
+            restaurant_id: db_average_rating.restaurant_id,
+            restaurant_code: db_average_rating.restaurant_code.clone(),
+            year: period.year(),
+            period,
+            average_score: db_average_rating.average_score,
+            dimension_averages: Vec::new(),
+        })
+    }
+
+    pub fn from_db_rows(db_average_ratings: &[DbAverageRatingPerPeriod]) -> Vec<Self> {
+        let mut average_ratings: Vec<Self> = db_average_ratings
+            .iter()
+            .filter_map(Self::from_db)
+            .collect();
+        average_ratings.sort_by_key(|average_rating| average_rating.period.start_date);
+
+        average_ratings
+    }
+
+    pub fn with_dimension_averages(
+        mut average_ratings: Vec<Self>,
+        db_dimension_averages: &[DbDimensionAveragePerPeriod],
+    ) -> Vec<Self> {
+        for db_dimension_average in db_dimension_averages {
+            let Some(period) = Period::from_db_columns(
+                &db_dimension_average.period_scheme,
+                db_dimension_average.year,
+                db_dimension_average.period_index,
+                db_dimension_average.round_name.as_deref(),
+                db_dimension_average.round_start_date,
+                db_dimension_average.round_end_date,
+            ) else {
+                continue;
+            };
+
+            if let Some(average_rating) = average_ratings.iter_mut().find(|average_rating| {
+                average_rating.restaurant_id == db_dimension_average.restaurant_id
+                    && average_rating.period == period
+            }) {
+                average_rating.dimension_averages.push(DimensionAverage {
+                    dimension_id: db_dimension_average.dimension_id,
+                    name: db_dimension_average.name.clone(),
+                    weight: db_dimension_average.weight,
+                    average_score: db_dimension_average.average_score,
+                });
+            }
+        }
+
+        average_ratings
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A blacklisted address or CIDR range. Entries without an expiry are permanent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Ip {
     pub ip_address: String,
+    pub reason: Option<String>,
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use actix_web::rt::{net::TcpStream, time::timeout};
+use anyhow::{anyhow, Result};
+use sqlx::MySqlPool;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use web_push::SubscriptionInfo;
+
+use crate::{config::AppConfig, db_util, models::PushClient};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The user a notification is meant for.
+pub struct Recipient {
+    pub user_id: String,
+    pub username: String,
+    pub email: Option<String>,
+}
+
+/// An SMTP server that accepts mail from us without authentication, like a local MTA, which
+/// takes care of TLS and authentication towards the outside world.
+#[derive(Clone)]
+pub struct SmtpRelay {
+    pub address: String,
+    pub from: String,
+}
+
+#[derive(Clone)]
+pub enum Channel {
+    /// Mails the user's email address
+    Smtp(SmtpRelay),
+    /// Pushes to every device the user subscribed to notifications on
+    Push(PushClient),
+    /// Only logs notifications, for local testing
+    Log,
+}
+
+/// Delivers account notifications, like password reset links, to a single user.
+#[derive(Clone)]
+pub struct Notifier {
+    pub channel: Channel,
+    pub frontend_base_url: String,
+}
+
+impl Notifier {
+    pub fn from_config(app_config: &AppConfig, push_client: &PushClient) -> Result<Self> {
+        let channel = match app_config.notifier.as_str() {
+            "smtp" => Channel::Smtp(SmtpRelay {
+                address: app_config.smtp_relay.clone(),
+                from: app_config
+                    .smtp_from
+                    .clone()
+                    .ok_or_else(|| anyhow!("SMTP_FROM is required by the smtp notifier"))?,
+            }),
+            "push" => Channel::Push(push_client.clone()),
+            "log" => Channel::Log,
+            notifier => return Err(anyhow!("Unknown notifier: {notifier}")),
+        };
+
+        Ok(Self {
+            channel,
+            frontend_base_url: app_config.frontend_base_url.clone(),
+        })
+    }
+
+    /// A link to a page of the frontend.
+    pub fn link(&self, path: &str) -> String {
+        format!("{}{path}", self.frontend_base_url.trim_end_matches('/'))
+    }
+
+    pub async fn notify(
+        &self,
+        pool: &MySqlPool,
+        recipient: &Recipient,
+        subject: &str,
+        body: &str,
+    ) -> Result<()> {
+        match &self.channel {
+            Channel::Smtp(relay) => {
+                let email = recipient
+                    .email
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("{} has no email address", recipient.username))?;
+
+                timeout(SMTP_TIMEOUT, send_mail(relay, email, subject, body))
+                    .await
+                    .map_err(|_| anyhow!("SMTP relay timed out"))?
+            }
+            Channel::Push(push_client) => {
+                let mut conn = db_util::get_connection(pool)
+                    .await
+                    .ok_or(anyhow!("Failed to get connection."))?;
+                let push_subscriptions =
+                    db_util::get_push_subscriptions_by_user(&mut conn, &recipient.user_id).await?;
+                if push_subscriptions.is_empty() {
+                    return Err(anyhow!("{} has no push subscriptions", recipient.username));
+                }
+
+                let message = format!("{subject}: {body}");
+                let mut delivered = false;
+                for push_subscription in push_subscriptions {
+                    let subscription_info = SubscriptionInfo::new(
+                        push_subscription.endpoint,
+                        push_subscription.p256dh,
+                        push_subscription.auth,
+                    );
+                    match db_util::send_notification(
+                        &mut conn,
+                        push_client,
+                        &subscription_info,
+                        &message,
+                    )
+                    .await
+                    {
+                        Ok(()) => delivered = true,
+                        Err(err) => eprintln!(
+                            "ERROR: failed sending notification to {}: {err}",
+                            subscription_info.endpoint
+                        ),
+                    }
+                }
+
+                match delivered {
+                    true => Ok(()),
+                    false => Err(anyhow!("No device of {} was reachable", recipient.username)),
+                }
+            }
+            Channel::Log => {
+                println!(
+                    "INFO:[{}]: Notification for {}: {subject}\n{body}",
+                    chrono::Utc::now(),
+                    recipient.username
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn send_mail(relay: &SmtpRelay, to: &str, subject: &str, body: &str) -> Result<()> {
+    let stream = TcpStream::connect(&relay.address).await?;
+    let mut stream = BufReader::new(stream);
+    read_reply(&mut stream, 220).await?;
+
+    let commands = [
+        ("EHLO ratings".to_string(), 250),
+        (format!("MAIL FROM:<{}>", relay.from), 250),
+        (format!("RCPT TO:<{to}>"), 250),
+        ("DATA".to_string(), 354),
+    ];
+    for (command, code) in commands {
+        stream
+            .write_all(format!("{command}\r\n").as_bytes())
+            .await?;
+        read_reply(&mut stream, code).await?;
+    }
+
+    // NOTE: Lines starting with a dot are escaped, a lone dot would end the message
+    let body = body
+        .lines()
+        .map(|line| match line.starts_with('.') {
+            true => format!(".{line}"),
+            false => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    let message = format!(
+        "From: <{}>\r\nTo: <{to}>\r\nSubject: {subject}\r\nDate: {}\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\r\n{body}\r\n.\r\n",
+        relay.from,
+        chrono::Utc::now().to_rfc2822()
+    );
+    stream.write_all(message.as_bytes()).await?;
+    read_reply(&mut stream, 250).await?;
+
+    stream.write_all(b"QUIT\r\n").await?;
+    read_reply(&mut stream, 221).await?;
+
+    Ok(())
+}
+
+/// Reads a possibly multiline reply and checks its status code.
+async fn read_reply(stream: &mut BufReader<TcpStream>, expected: u16) -> Result<()> {
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            return Err(anyhow!("SMTP relay closed the connection"));
+        }
+
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+        if code != Some(expected) {
+            return Err(anyhow!("Unexpected SMTP reply: {}", line.trim_end()));
+        }
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
@@ -7,7 +7,11 @@ use openidconnect::{
 };
 use urlencoding::encode;
 
-use crate::{auth::generate_token, auth::validate_token, db_util, models::ApiResponse};
+use crate::{
+    auth::{generate_token, start_login_challenge, start_session, totp_enabled, validate_token},
+    db_util,
+    models::ApiResponse,
+};
 use sqlx::MySqlPool;
 
 #[derive(serde::Deserialize)]
@@ -33,6 +37,9 @@ pub struct OidcConfig {
     pub redirect_url: String,
     pub frontend_base_url: String,
     pub cookie_domain: Option<String>,
+    /// Whether the identity provider already requires a second factor for every login, in which
+    /// case users with TOTP enabled aren't asked for it again
+    pub enforces_mfa: bool,
 }
 
 pub async fn build_oidc_config(app_config: &AppConfig) -> anyhow::Result<OidcConfig> {
@@ -59,6 +66,7 @@ pub async fn build_oidc_config(app_config: &AppConfig) -> anyhow::Result<OidcCon
         redirect_url: app_config.oidc_redirect_url.clone(),
         frontend_base_url: app_config.frontend_base_url.clone(),
         cookie_domain: app_config.cookie_domain.clone(),
+        enforces_mfa: app_config.oidc_enforces_mfa,
     })
 }
 
@@ -284,6 +292,18 @@ pub async fn oidc_callback(
         b.finish()
     };
 
+    let create_refresh_cookie = |refresh_token: String| {
+        let mut b = cookie::Cookie::build("refresh_token", refresh_token)
+            .path("/")
+            .http_only(false)
+            .secure(true)
+            .same_site(cookie::SameSite::Lax);
+        if let Some(d) = &cookie_domain {
+            b = b.domain(d.clone());
+        }
+        b.finish()
+    };
+
     let create_color_cookie = |color: String| {
         let safe_color = urlencoding::encode(&color).into_owned();
         let mut b = cookie::Cookie::build("color", safe_color)
@@ -313,7 +333,51 @@ pub async fn oidc_callback(
     };
 
     if let Some(user) = user_opt {
-        let token = generate_token(&req, user.id.clone(), user.username.clone());
+        let mfa_done = oidc_config.enforces_mfa
+            || claims
+                .auth_method_refs()
+                .is_some_and(|amr| amr.iter().any(|method| method.as_str() == "mfa"));
+        let needs_totp = match totp_enabled(&mut conn, &user.id).await {
+            Ok(enabled) => enabled && !mfa_done,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::error(e.to_string()))
+            }
+        };
+        if needs_totp {
+            let challenge = match start_login_challenge(&mut conn, &user.id).await {
+                Ok(challenge) => challenge,
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .json(ApiResponse::<()>::error(e.to_string()))
+                }
+            };
+
+            let mut frontend_url = format!(
+                "{}/login?totp_challenge={}",
+                oidc_config.frontend_base_url,
+                encode(&challenge.challenge_token)
+            );
+            if let Some(rt) = &redirect_target {
+                frontend_url = format!("{}&redirect={}", frontend_url, encode(rt));
+            }
+
+            return HttpResponse::Found()
+                .append_header(("Location", frontend_url))
+                .cookie(clear_cookie("oidc_state"))
+                .cookie(clear_cookie("oidc_nonce"))
+                .cookie(clear_cookie("oidc_pkce_verifier"))
+                .cookie(clear_cookie("oidc_redirect"))
+                .finish();
+        }
+
+        let tokens = match start_session(&req, &mut conn, &user.id, &user.username).await {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::error(e.to_string()))
+            }
+        };
 
         let mut frontend_url = format!("{}/login?oidc_success=true", oidc_config.frontend_base_url);
         if let Some(rt) = &redirect_target {
@@ -322,7 +386,8 @@ pub async fn oidc_callback(
 
         return HttpResponse::Found()
             .append_header(("Location", frontend_url))
-            .cookie(create_auth_cookie(token))
+            .cookie(create_auth_cookie(tokens.token))
+            .cookie(create_refresh_cookie(tokens.refresh_token))
             .cookie(create_color_cookie(user.color))
             .cookie(clear_cookie("oidc_state"))
             .cookie(clear_cookie("oidc_nonce"))
@@ -331,11 +396,15 @@ pub async fn oidc_callback(
             .finish();
     }
 
-    if let Ok(user_claims) = validate_token(&req) {
+    if let Ok(user_claims) = validate_token(&req).await {
         match db_util::link_oidc_to_user(&mut conn, &user_claims.id, &provider, &subject).await {
             Ok(_) => {
-                let token =
-                    generate_token(&req, user_claims.id.clone(), user_claims.username.clone());
+                let token = generate_token(
+                    &req,
+                    user_claims.id.clone(),
+                    user_claims.username.clone(),
+                    user_claims.sid.clone(),
+                );
 
                 let mut frontend_url =
                     format!("{}/login?oidc_success=true", oidc_config.frontend_base_url);
@@ -394,7 +463,7 @@ pub async fn link_oidc_account(
     pool: web::Data<MySqlPool>,
     body: web::Json<LinkOidcBody>,
 ) -> HttpResponse {
-    let user_claims = match validate_token(&req) {
+    let user_claims = match validate_token(&req).await {
         Ok(c) => c,
         Err(e) => return e,
     };
@@ -0,0 +1,336 @@
+use std::{
+    future::{ready, Ready},
+    net::IpAddr,
+    num::NonZeroU32,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
+    rt::time::sleep,
+    web, Error, HttpResponse,
+};
+use anyhow::{anyhow, Result};
+use futures::future::LocalBoxFuture;
+use governor::{
+    clock::Clock, middleware::StateInformationMiddleware, DefaultKeyedRateLimiter, Quota,
+};
+
+use crate::{auth, jwks::SigningKeys, models::ApiResponse};
+
+const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+const RATE_LIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+const RATE_LIMIT_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How many requests a client may make per period. Unused requests are handed back one at a
+/// time, evenly spread over the period, so a client that waits can burst up to the limit again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub period_seconds: u64,
+}
+
+impl RateLimit {
+    pub const fn new(requests: u32, period_seconds: u64) -> Self {
+        Self {
+            requests,
+            period_seconds,
+        }
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        let requests = NonZeroU32::new(self.requests)?;
+        let replenish_interval = Duration::from_secs(self.period_seconds) / requests.get();
+        Some(Quota::with_period(replenish_interval)?.allow_burst(requests))
+    }
+}
+
+/// Parses limits like `10/60`, ten requests per minute.
+impl FromStr for RateLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(rate_limit: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid rate limit, expected requests/seconds: {rate_limit}");
+
+        let (requests, period_seconds) = rate_limit.trim().split_once('/').ok_or_else(invalid)?;
+        let rate_limit = Self::new(
+            requests.trim().parse().map_err(|_| invalid())?,
+            period_seconds.trim().parse().map_err(|_| invalid())?,
+        );
+        rate_limit.quota().ok_or_else(invalid)?;
+
+        Ok(rate_limit)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimits {
+    /// Requests without a valid access token, per client IP
+    pub anonymous: RateLimit,
+    /// Requests with a valid access token, per user
+    pub user: RateLimit,
+    pub login: RateLimit,
+    pub register: RateLimit,
+    pub password: RateLimit,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            anonymous: RateLimit::new(60, 60),
+            user: RateLimit::new(300, 60),
+            login: RateLimit::new(10, 300),
+            register: RateLimit::new(5, 3600),
+            password: RateLimit::new(5, 900),
+        }
+    }
+}
+
+/// The budget a request is taken from. Routes with a stricter budget are limited by it on top
+/// of `Requests`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bucket {
+    /// Every request, against `RateLimits::user` or `RateLimits::anonymous`
+    Requests,
+    Login,
+    Register,
+    /// Password changes and resets
+    Password,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Client {
+    User(String),
+    Ip(IpAddr),
+}
+
+type Limiter = DefaultKeyedRateLimiter<Client, StateInformationMiddleware>;
+
+#[derive(Clone)]
+pub struct RateLimiters {
+    anonymous: Arc<Limiter>,
+    user: Arc<Limiter>,
+    login: Arc<Limiter>,
+    register: Arc<Limiter>,
+    password: Arc<Limiter>,
+}
+
+impl RateLimiters {
+    pub fn new(rate_limits: &RateLimits) -> Result<Self> {
+        let limiter = |rate_limit: RateLimit| {
+            rate_limit
+                .quota()
+                .map(|quota| Arc::new(governor::RateLimiter::keyed(quota).with_middleware()))
+                .ok_or_else(|| anyhow!("Invalid rate limit: {rate_limit:?}"))
+        };
+
+        Ok(Self {
+            anonymous: limiter(rate_limits.anonymous)?,
+            user: limiter(rate_limits.user)?,
+            login: limiter(rate_limits.login)?,
+            register: limiter(rate_limits.register)?,
+            password: limiter(rate_limits.password)?,
+        })
+    }
+
+    fn limiter(&self, bucket: Bucket, client: &Client) -> &Limiter {
+        match (bucket, client) {
+            (Bucket::Requests, Client::User(_)) => &self.user,
+            (Bucket::Requests, Client::Ip(_)) => &self.anonymous,
+            (Bucket::Login, _) => &self.login,
+            (Bucket::Register, _) => &self.register,
+            (Bucket::Password, _) => &self.password,
+        }
+    }
+
+    /// Takes a request from the client's budget, or says how long it has to wait for one.
+    fn check(&self, bucket: Bucket, client: &Client) -> Result<RateLimitState, RateLimitState> {
+        let limiter = self.limiter(bucket, client);
+        match limiter.check_key(client) {
+            Ok(snapshot) => {
+                let quota = snapshot.quota();
+                let used = quota.burst_size().get() - snapshot.remaining_burst_capacity();
+                Ok(RateLimitState {
+                    quota,
+                    remaining: snapshot.remaining_burst_capacity(),
+                    reset: quota.replenish_interval() * used,
+                })
+            }
+            Err(not_until) => {
+                let quota = not_until.quota();
+                let retry_after = not_until.wait_time_from(limiter.clock().now());
+                Err(RateLimitState {
+                    quota,
+                    remaining: 0,
+                    reset: retry_after
+                        + quota.replenish_interval() * (quota.burst_size().get() - 1),
+                })
+            }
+        }
+    }
+
+    /// Forgets clients whose budget has filled up again, so they stop taking up memory.
+    fn retain_recent(&self) {
+        for limiter in [
+            &self.anonymous,
+            &self.user,
+            &self.login,
+            &self.register,
+            &self.password,
+        ] {
+            limiter.retain_recent();
+            limiter.shrink_to_fit();
+        }
+    }
+}
+
+pub async fn prune_rate_limiters(rate_limiters: RateLimiters) {
+    loop {
+        sleep(RATE_LIMIT_PRUNE_INTERVAL).await;
+        rate_limiters.retain_recent();
+    }
+}
+
+struct RateLimitState {
+    quota: Quota,
+    remaining: u32,
+    /// Until the client's whole budget is back
+    reset: Duration,
+}
+
+impl RateLimitState {
+    fn retry_after(&self) -> u64 {
+        let refilled = self.quota.replenish_interval() * (self.quota.burst_size().get() - 1);
+        seconds(self.reset.saturating_sub(refilled)).max(1)
+    }
+
+    /// Sets the `RateLimit-*` headers, unless a tighter bucket of the same request already did.
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        let remaining = headers
+            .get(RATE_LIMIT_REMAINING)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u32>().ok());
+        if remaining.is_some_and(|remaining| remaining <= self.remaining) {
+            return;
+        }
+
+        let limit = self.quota.burst_size().get();
+        let period = seconds(self.quota.burst_size_replenished_in());
+        headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(limit));
+        headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(self.remaining));
+        headers.insert(RATE_LIMIT_RESET, HeaderValue::from(seconds(self.reset)));
+        if let Ok(policy) = HeaderValue::from_str(&format!("{limit};w={period}")) {
+            headers.insert(RATE_LIMIT_POLICY, policy);
+        }
+    }
+}
+
+fn seconds(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+/// Signed in users are limited on their own, whichever IP they come from. Everyone else is
+/// limited by IP, with IPv6 clients grouped by the /56 prefix they are usually handed.
+fn client(req: &ServiceRequest) -> Option<Client> {
+    if req.app_data::<web::Data<SigningKeys>>().is_some() {
+        if let Ok(user_claims) = auth::verify_token(req.request()) {
+            return Some(Client::User(user_claims.id));
+        }
+    }
+
+    match auth::client_ip(req.request())? {
+        IpAddr::V6(ipv6) => {
+            let mut octets = ipv6.octets();
+            octets[7..16].fill(0);
+            Some(Client::Ip(IpAddr::V6(octets.into())))
+        }
+        ip => Some(Client::Ip(ip)),
+    }
+}
+
+/// Middleware that limits requests by the budget of a `Bucket`, with the limiters taken from
+/// `RateLimiters` app data. Without it, requests aren't limited.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimited {
+    bucket: Bucket,
+}
+
+impl RateLimited {
+    pub fn new(bucket: Bucket) -> Self {
+        Self { bucket }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimited
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitedService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitedService {
+            service,
+            bucket: self.bucket,
+        }))
+    }
+}
+
+pub struct RateLimitedService<S> {
+    service: S,
+    bucket: Bucket,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitedService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let state = req
+            .app_data::<web::Data<RateLimiters>>()
+            .zip(client(&req))
+            .map(|(rate_limiters, client)| rate_limiters.check(self.bucket, &client));
+
+        match state {
+            Some(Err(state)) => {
+                let mut response = HttpResponse::TooManyRequests()
+                    .insert_header((header::RETRY_AFTER, state.retry_after()))
+                    .json(ApiResponse::<()>::error(
+                        "Too many requests, try again later".to_string(),
+                    ));
+                state.insert_headers(response.headers_mut());
+
+                let response = req.into_response(response).map_into_right_body();
+                Box::pin(ready(Ok(response)))
+            }
+            state => {
+                let response = self.service.call(req);
+                Box::pin(async move {
+                    let mut response = response.await?;
+                    if let Some(Ok(state)) = state {
+                        state.insert_headers(response.headers_mut());
+                    }
+                    Ok(response.map_into_left_body())
+                })
+            }
+        }
+    }
+}
@@ -1,17 +1,36 @@
 use std::collections::HashMap;
 
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use actix_web::{delete, get, http::header, post, put, web, HttpRequest, HttpResponse};
 use sqlx::MySqlPool;
 use uuid::Uuid;
 
-use crate::{auth, db_models::*, db_util, models::*};
+use crate::{
+    auth,
+    db_models::*,
+    db_util,
+    jwks::SigningKeys,
+    models::*,
+    notifier::{Notifier, Recipient},
+    rate_limit::{Bucket, RateLimited},
+    totp,
+};
 
 #[get("/health")]
 async fn health_route() -> HttpResponse {
     HttpResponse::Ok().json(ApiResponse::success("ok"))
 }
 
-#[post("/register")]
+/// Public keys for verifying access tokens, for services that accept ratings tokens.
+#[get("/.well-known/jwks.json")]
+async fn jwks_route(signing_keys: web::Data<SigningKeys>) -> HttpResponse {
+    let jwks = signing_keys.read().unwrap().jwks();
+
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "public, max-age=300"))
+        .json(jwks)
+}
+
+#[post("/register", wrap = "RateLimited::new(Bucket::Register)")]
 async fn register_user_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
@@ -24,6 +43,13 @@ async fn register_user_route(
     new_user.0.id = Uuid::new_v4().to_string();
     let username = new_user.0.username.clone();
 
+    new_user.0.email = match normalize_email(new_user.0.email.as_deref()) {
+        Ok(email) => email,
+        Err(error) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
     let hashed_password = match auth::generate_password_hash(new_user.0.password.clone()) {
         Ok(password) => password,
         Err(error) => {
@@ -38,15 +64,17 @@ async fn register_user_route(
 
     match result {
         Ok(db_user) => {
-            let token = auth::generate_token(&req, db_user.id.clone(), username);
-            HttpResponse::Created().json(ApiResponse::success(User {
-                id: db_user.id,
-                username: db_user.username,
-                password: db_user.password,
-                color: db_user.color,
-                token: token.clone(),
-                ratings: db_user.ratings,
-                group_memberships: db_user.group_memberships,
+            let tokens = match auth::start_session(&req, &mut conn, &db_user.id, &username).await {
+                Ok(tokens) => tokens,
+                Err(error) => {
+                    return HttpResponse::InternalServerError()
+                        .json(ApiResponse::<()>::error(error.to_string()));
+                }
+            };
+            HttpResponse::Created().json(ApiResponse::success(UserProfile {
+                token: tokens.token,
+                refresh_token: tokens.refresh_token,
+                ..UserProfile::from_user(db_user)
             }))
         }
         Err(error) => {
@@ -55,7 +83,7 @@ async fn register_user_route(
     }
 }
 
-#[post("/login")]
+#[post("/login", wrap = "RateLimited::new(Bucket::Login)")]
 async fn login_user_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
@@ -69,24 +97,385 @@ async fn login_user_route(
     let password = credentials.0.password.clone();
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::get_user_by_credentials(&mut conn, &credentials.0.username.clone()).await;
 
-    match result {
-        Ok(users_result) => match users_result {
-            Some(mut user) => {
-                let is_valid_password = auth::validate_password(&user.password, &password);
-                if is_valid_password {
-                    let token = auth::generate_token(&req, user.id.clone(), username);
-                    user.token.clone_from(&token);
-                    HttpResponse::Ok().json(ApiResponse::success(user))
-                } else {
-                    HttpResponse::Unauthorized()
-                        .json(ApiResponse::<()>::error("Invalid credentials".to_string()))
-                }
+    let ip = auth::client_ip(&req).map(|ip| ip.to_string());
+    match auth::login_lockout(&mut conn, &username, ip.as_deref()).await {
+        Ok(None) => {}
+        Ok(Some(retry_after)) => {
+            // NOTE: Hammering away during a lockout still counts against the IP
+            if let Err(error) = auth::record_failed_login(&req, &mut conn, None).await {
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::error(error.to_string()));
+            }
+
+            return auth::login_locked_out(retry_after);
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    let result = db_util::get_user_by_credentials(&mut conn, &username).await;
+    let user = match result {
+        Ok(user) => user,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    // NOTE: Unknown users and wrong passwords get the same answer, so usernames can't be probed
+    let stored_hash = user.as_ref().map(|user| user.password.as_str());
+    let is_valid_password = auth::validate_login_password(stored_hash, &password);
+    let mut user = match user {
+        Some(user) if is_valid_password => user,
+        _ => {
+            if let Err(error) = auth::record_failed_login(&req, &mut conn, Some(&username)).await {
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::error(error.to_string()));
+            }
+
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("Invalid credentials".to_string()));
+        }
+    };
+
+    match auth::totp_enabled(&mut conn, &user.id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return match auth::start_login_challenge(&mut conn, &user.id).await {
+                Ok(challenge) => HttpResponse::Accepted().json(ApiResponse::success(challenge)),
+                Err(error) => HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::error(error.to_string())),
+            };
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    if let Err(error) = auth::clear_failed_logins(&mut conn, &username).await {
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::error(error.to_string()));
+    }
+
+    match auth::start_session(&req, &mut conn, &user.id, &username).await {
+        Ok(tokens) => {
+            user.token = tokens.token;
+            user.refresh_token = tokens.refresh_token;
+            HttpResponse::Ok().json(ApiResponse::success(UserProfile::from_user(user)))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/login/totp", wrap = "RateLimited::new(Bucket::Login)")]
+async fn login_totp_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    payload: web::Json<TotpLogin>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    let challenge_hash = auth::hash_opaque_token(&payload.challenge_token);
+    let challenge = match db_util::get_login_challenge(&mut conn, &challenge_hash).await {
+        Ok(Some(challenge)) if challenge.expires_at > chrono::Utc::now().naive_utc() => challenge,
+        Ok(challenge) => {
+            if let Some(challenge) = challenge {
+                let _ = db_util::delete_login_challenge(&mut conn, challenge.id).await;
+            }
+
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(
+                "Login challenge is invalid or expired".to_string(),
+            ));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    // NOTE: Wrong codes count as failed logins too, since every password login opens a new challenge
+    let ip = auth::client_ip(&req).map(|ip| ip.to_string());
+    match auth::login_lockout(&mut conn, &challenge.username, ip.as_deref()).await {
+        Ok(None) => {}
+        Ok(Some(retry_after)) => return auth::login_locked_out(retry_after),
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    match auth::verify_second_factor(&mut conn, &challenge.user_id, &payload.code).await {
+        Ok(true) => {}
+        Ok(false) => {
+            if let Err(error) =
+                auth::record_failed_login(&req, &mut conn, Some(&challenge.username)).await
+            {
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::error(error.to_string()));
+            }
+
+            // NOTE: A challenge only allows a few guesses, then the password has to be entered again
+            let result = match challenge.attempts + 1 >= auth::LOGIN_CHALLENGE_MAX_ATTEMPTS {
+                true => db_util::delete_login_challenge(&mut conn, challenge.id).await,
+                false => db_util::record_login_challenge_attempt(&mut conn, challenge.id).await,
+            };
+            if let Err(error) = result {
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::error(error.to_string()));
             }
-            None => HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("User not found".to_string())),
-        },
+
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("Invalid code".to_string()));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    if let Err(error) = db_util::delete_login_challenge(&mut conn, challenge.id).await {
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::error(error.to_string()));
+    }
+    if let Err(error) = auth::clear_failed_logins(&mut conn, &challenge.username).await {
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::error(error.to_string()));
+    }
+
+    let mut user = match db_util::get_user_by_credentials(&mut conn, &challenge.username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("User not found".to_string()));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    match auth::start_session(&req, &mut conn, &user.id, &challenge.username).await {
+        Ok(tokens) => {
+            user.token = tokens.token;
+            user.refresh_token = tokens.refresh_token;
+            HttpResponse::Ok().json(ApiResponse::success(UserProfile::from_user(user)))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/refresh")]
+async fn refresh_token_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    payload: web::Json<RefreshTokenRequest>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    let refresh_token = auth::generate_opaque_token();
+    let result = db_util::rotate_refresh_token(
+        &mut conn,
+        &auth::hash_opaque_token(&payload.refresh_token),
+        &auth::hash_opaque_token(&refresh_token),
+        auth::refresh_token_expiry(),
+        &auth::session_origin(&req),
+    )
+    .await;
+    match result {
+        Ok(Some(rotated)) => HttpResponse::Ok().json(ApiResponse::success(AuthTokens {
+            token: auth::generate_token(
+                &req,
+                rotated.user_id,
+                rotated.username,
+                rotated.session_id,
+            ),
+            refresh_token,
+        })),
+        Ok(None) => HttpResponse::Unauthorized().json(ApiResponse::<()>::error(
+            "Invalid refresh token".to_string(),
+        )),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/logout")]
+async fn logout_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    payload: web::Json<RefreshTokenRequest>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::revoke_session_by_refresh_token(
+        &mut conn,
+        &auth::hash_opaque_token(&payload.refresh_token),
+    )
+    .await;
+    match result {
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/logout_all")]
+async fn logout_all_route(pool: web::Data<MySqlPool>, req: HttpRequest) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::revoke_sessions_by_user(&mut conn, &user_claims.id).await;
+    match result {
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/password_reset", wrap = "RateLimited::new(Bucket::Password)")]
+async fn request_password_reset_route(
+    pool: web::Data<MySqlPool>,
+    notifier: web::Data<Notifier>,
+    req: HttpRequest,
+    payload: web::Json<PasswordResetRequest>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    // NOTE: The answer is the same for unknown and rate limited accounts, so it can't be used
+    // to find out which accounts exist
+    let accepted = HttpResponse::Accepted().json(ApiResponse::success(
+        "If the account exists, a password reset link is on its way",
+    ));
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let user = match db_util::get_user_by_credentials(&mut conn, &payload.username).await {
+        Ok(Some(user)) => user,
+        _ => return accepted,
+    };
+
+    let since = (chrono::Utc::now() - chrono::Duration::hours(1)).naive_utc();
+    match db_util::count_password_reset_tokens(&mut conn, &user.id, since).await {
+        Ok(count) if count < auth::PASSWORD_RESETS_PER_HOUR => {}
+        Ok(_) => return accepted,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    let reset_token = auth::generate_opaque_token();
+    let expires_at = (chrono::Utc::now()
+        + chrono::Duration::minutes(auth::PASSWORD_RESET_TTL_MINUTES))
+    .naive_utc();
+    if let Err(error) = db_util::create_password_reset_token(
+        &mut conn,
+        &user.id,
+        &auth::hash_opaque_token(&reset_token),
+        expires_at,
+    )
+    .await
+    {
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::error(error.to_string()));
+    }
+
+    let body = format!(
+        "Reset your password within {} minutes at {}",
+        auth::PASSWORD_RESET_TTL_MINUTES,
+        notifier.link(&format!("/reset-password?token={reset_token}"))
+    );
+    let recipient = Recipient {
+        user_id: user.id,
+        username: user.username,
+        email: user.email,
+    };
+    let pool = pool.get_ref().clone();
+    actix_web::rt::spawn(async move {
+        if let Err(err) = notifier
+            .notify(&pool, &recipient, "Password reset", &body)
+            .await
+        {
+            eprintln!(
+                "ERROR: failed sending password reset to {}: {err}",
+                recipient.username
+            );
+        }
+    });
+
+    accepted
+}
+
+#[post("/password_reset/confirm", wrap = "RateLimited::new(Bucket::Password)")]
+async fn confirm_password_reset_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    payload: web::Json<PasswordResetConfirm>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    if payload.new_password.is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "New password cannot be empty".into(),
+        ));
+    }
+
+    let new_hash = match auth::generate_password_hash(payload.new_password.clone()) {
+        Ok(hash) => hash,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::reset_password(
+        &mut conn,
+        &auth::hash_opaque_token(&payload.token),
+        &new_hash,
+    )
+    .await;
+    match result {
+        Ok(Some(_)) => HttpResponse::Ok().json(ApiResponse::success("Password has been reset")),
+        Ok(None) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Invalid or expired reset token".to_string(),
+        )),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
@@ -99,12 +488,13 @@ async fn get_users_route(pool: web::Data<MySqlPool>, req: HttpRequest) -> HttpRe
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
-    let result = db_util::get_users(&pool).await;
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::get_users_sharing_groups(&mut conn, &user_claims.id).await;
     match result {
         Ok(users) => HttpResponse::Ok().json(ApiResponse::success(users)),
         Err(error) => {
@@ -122,7 +512,7 @@ async fn get_user_oidc_links_route(
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -152,7 +542,7 @@ async fn unlink_oidc_route(
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -184,7 +574,7 @@ async fn update_user_route(
         return err;
     }
 
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -196,14 +586,16 @@ async fn update_user_route(
     let mut conn = db_util::get_connection(&pool).await.unwrap();
     let result = db_util::update_user(&mut conn, &user_id, &user).await;
     match result {
-        Ok(updated_user) => HttpResponse::Ok().json(ApiResponse::success(updated_user)),
+        Ok(updated_user) => {
+            HttpResponse::Ok().json(ApiResponse::success(UserProfile::from_user(updated_user)))
+        }
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[post("/users/{id}/password")]
+#[post("/users/{id}/password", wrap = "RateLimited::new(Bucket::Password)")]
 async fn change_password_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
@@ -214,7 +606,7 @@ async fn change_password_route(
         return err;
     }
 
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -262,25 +654,36 @@ async fn change_password_route(
         }
     };
 
-    match db_util::update_user_password(&mut conn, &user_id, &new_hash).await {
-        Ok(_) => HttpResponse::Ok().json(ApiResponse::success(true)),
+    if let Err(error) = db_util::update_user_password(&mut conn, &user_id, &new_hash).await {
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::error(error.to_string()));
+    }
+
+    // NOTE: Sign out every other session, the caller keeps going in a fresh one
+    if let Err(error) = db_util::revoke_sessions_by_user(&mut conn, &user_id).await {
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::error(error.to_string()));
+    }
+    match auth::start_session(&req, &mut conn, &user_id, &user_claims.username).await {
+        Ok(tokens) => HttpResponse::Ok().json(ApiResponse::success(tokens)),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[delete("/users/{id}")]
-async fn delete_user_route(
+#[put("/users/{id}/email")]
+async fn update_user_email_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
     id: web::Path<String>,
+    update: web::Json<UpdateEmail>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -289,65 +692,611 @@ async fn delete_user_route(
         return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
     }
 
+    let email = match normalize_email(update.email.as_deref()) {
+        Ok(email) => email,
+        Err(error) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::delete_user(&mut conn, &user_id).await;
+    let result = db_util::update_user_email(&mut conn, &user_id, email.as_deref()).await;
     match result {
-        Ok(rows) => HttpResponse::Ok().json(ApiResponse::success(rows.last_insert_id())),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::success(email)),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[post("/subscribe")]
-async fn push_subscribe_route(
+#[get("/users/{id}/totp")]
+async fn get_totp_status_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
-    new_push_subscription: web::Json<NewPushSubscription>,
+    id: web::Path<String>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
+    let user_id = id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::create_push_subscription(
-        &mut conn,
-        &new_push_subscription.user_id,
-        &new_push_subscription.subscription_info,
-    )
-    .await;
+    let enabled = match auth::totp_enabled(&mut conn, &user_id).await {
+        Ok(enabled) => enabled,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    let result = db_util::count_recovery_codes(&mut conn, &user_id).await;
     match result {
-        // TODO: Should we actually return this to the client?
-        Ok(push_subscription) => HttpResponse::Ok().json(ApiResponse::success(push_subscription)),
+        Ok(recovery_codes_left) => HttpResponse::Ok().json(ApiResponse::success(TotpStatus {
+            enabled,
+            recovery_codes_left,
+        })),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[get("/groups/{user_id}")]
-async fn get_group_memberships_by_user_route(
+#[post("/users/{id}/totp")]
+async fn enroll_totp_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
-    user_id: web::Path<String>,
+    id: web::Path<String>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
+    let user_id = id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::get_group_memberships_by_user(&mut conn, &user_id).await;
-    match result {
+    match auth::totp_enabled(&mut conn, &user_id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    let secret = totp::generate_secret();
+    let result = db_util::start_totp_enrollment(&mut conn, &user_id, &secret).await;
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::success(TotpEnrollment {
+            provisioning_uri: totp::provisioning_uri(&secret, &user_claims.username),
+            secret,
+        })),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+/// Enables two-factor authentication once the first code from the app checks out, and hands out
+/// the recovery codes. They are only ever shown here.
+#[post("/users/{id}/totp/verify")]
+async fn verify_totp_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<String>,
+    payload: web::Json<TotpCode>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let user_id = id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let totp_credential = match db_util::get_totp_credential(&mut conn, &user_id).await {
+        Ok(Some(totp_credential)) if totp_credential.confirmed_at.is_none() => totp_credential,
+        Ok(_) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+                "No two-factor enrollment in progress".to_string(),
+            ));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let step = match totp::verify_code(&totp_credential.secret, &payload.code, now, None) {
+        Some(step) => step,
+        None => {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::<()>::error("Invalid code".to_string()));
+        }
+    };
+
+    let recovery_codes = totp::generate_recovery_codes();
+    let recovery_code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| auth::hash_opaque_token(&totp::normalize_recovery_code(code)))
+        .collect();
+    let result =
+        db_util::confirm_totp_enrollment(&mut conn, &user_id, step, &recovery_code_hashes).await;
+    match result {
+        Ok(()) => HttpResponse::Ok().json(ApiResponse::success(RecoveryCodes { recovery_codes })),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/users/{id}/totp/recovery_codes")]
+async fn regenerate_recovery_codes_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<String>,
+    payload: web::Json<TotpCode>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let user_id = id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    match auth::verify_second_factor(&mut conn, &user_id, &payload.code).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("Invalid code".to_string()));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    let recovery_codes = totp::generate_recovery_codes();
+    let recovery_code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| auth::hash_opaque_token(&totp::normalize_recovery_code(code)))
+        .collect();
+    let result = db_util::replace_recovery_codes(&mut conn, &user_id, &recovery_code_hashes).await;
+    match result {
+        Ok(()) => HttpResponse::Ok().json(ApiResponse::success(RecoveryCodes { recovery_codes })),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[delete("/users/{id}/totp")]
+async fn disable_totp_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<String>,
+    payload: web::Json<TotpCode>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let user_id = id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    match auth::verify_second_factor(&mut conn, &user_id, &payload.code).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("Invalid code".to_string()));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    let result = db_util::delete_totp_credential(&mut conn, &user_id).await;
+    match result {
+        Ok(()) => {
+            HttpResponse::Ok().json(ApiResponse::success("Two-factor authentication disabled"))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[delete("/users/{id}")]
+async fn delete_user_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let user_id = id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    // NOTE: The user's sessions are deleted along with it
+    let result = db_util::delete_user(&mut conn, &user_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("User not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/users/{id}/sessions")]
+async fn get_sessions_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let user_id = id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::get_sessions(&mut conn, &user_id).await;
+    match result {
+        Ok(db_sessions) => {
+            let sessions: Vec<Session> = db_sessions
+                .into_iter()
+                .map(|db_session| Session::from_db(db_session, &user_claims.sid))
+                .collect();
+            HttpResponse::Ok().json(ApiResponse::success(sessions))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[delete("/users/{id}/sessions/{session_id}")]
+async fn revoke_session_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, String)>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (user_id, session_id) = params.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::revoke_session(&mut conn, &user_id, &session_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Session not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[put("/users/{id}/superadmin")]
+async fn update_user_superadmin_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<String>,
+    update: web::Json<UpdateSuperadmin>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize_superadmin(&mut conn, &user_claims).await {
+        return err;
+    }
+
+    let result =
+        db_util::update_user_superadmin(&mut conn, &id.into_inner(), update.superadmin).await;
+    match result {
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/ip_blacklist")]
+async fn get_ip_blacklist_route(pool: web::Data<MySqlPool>, req: HttpRequest) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize_superadmin(&mut conn, &user_claims).await {
+        return err;
+    }
+
+    let result = db_util::get_ip_blacklist(&mut *conn).await;
+    match result {
+        Ok(ips) => HttpResponse::Ok().json(ApiResponse::success(ips)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/ip_blacklist")]
+async fn create_ip_blacklist_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    ips: web::Json<Vec<Ip>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    let mut new_ips = Vec::<Ip>::new();
+    for ip in ips.into_inner() {
+        let ip_address = match auth::normalize_ip_network(&ip.ip_address) {
+            Some(ip_address) => ip_address,
+            None => {
+                return HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!(
+                    "Invalid IP address or range: {}",
+                    ip.ip_address
+                )));
+            }
+        };
+        let reason = ip
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+        if reason
+            .as_ref()
+            .is_some_and(|reason| reason.chars().count() > 255)
+        {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+                "Reason must be at most 255 characters".to_string(),
+            ));
+        }
+        if ip.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!(
+                "Expiry of {ip_address} is in the past"
+            )));
+        }
+        if new_ips.iter().any(|new_ip| new_ip.ip_address == ip_address) {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!(
+                "{ip_address} is listed more than once"
+            )));
+        }
+
+        new_ips.push(Ip {
+            ip_address,
+            reason,
+            expires_at: ip.expires_at,
+        });
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize_superadmin(&mut conn, &user_claims).await {
+        return err;
+    }
+
+    let blacklisted = match db_util::get_ip_blacklist(&mut *conn).await {
+        Ok(blacklisted) => blacklisted,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    if let Some(ip) = new_ips.iter().find(|new_ip| {
+        blacklisted
+            .iter()
+            .any(|blacklisted| blacklisted.ip_address == new_ip.ip_address)
+    }) {
+        return HttpResponse::Conflict().json(ApiResponse::<()>::error(format!(
+            "{} is already blacklisted",
+            ip.ip_address
+        )));
+    }
+
+    let result = db_util::create_ip_blacklist(&mut conn, &new_ips).await;
+    match result {
+        Ok(_) => {
+            auth::add_to_blacklist(&req, &new_ips);
+            HttpResponse::Ok().json(ApiResponse::success(new_ips))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+// NOTE: Ranges contain a slash, so the rest of the path is the address, like /ip_blacklist/10.0.0.0/8
+#[delete("/ip_blacklist/{ip_address:.+}")]
+async fn delete_ip_blacklist_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    ip_address: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let ip_address = match auth::normalize_ip_network(&ip_address) {
+        Some(ip_address) => ip_address,
+        None => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!(
+                "Invalid IP address or range: {ip_address}"
+            )));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize_superadmin(&mut conn, &user_claims).await {
+        return err;
+    }
+
+    let result = db_util::delete_ip(&mut conn, &ip_address).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => HttpResponse::NotFound().json(
+            ApiResponse::<()>::error(format!("{ip_address} is not blacklisted")),
+        ),
+        Ok(query_result) => {
+            auth::remove_from_blacklist(&req, &ip_address);
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/subscribe")]
+async fn push_subscribe_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    new_push_subscription: web::Json<NewPushSubscription>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let _user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::create_push_subscription(
+        &mut conn,
+        &new_push_subscription.user_id,
+        &new_push_subscription.subscription_info,
+    )
+    .await;
+    match result {
+        // TODO: Should we actually return this to the client?
+        Ok(push_subscription) => HttpResponse::Ok().json(ApiResponse::success(push_subscription)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/groups/{user_id}")]
+async fn get_group_memberships_by_user_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    user_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let _user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::get_group_memberships_by_user(&mut conn, &user_id).await;
+    match result {
         Ok(group_memberships) => HttpResponse::Ok().json(ApiResponse::success(group_memberships)),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
@@ -355,94 +1304,1976 @@ async fn get_group_memberships_by_user_route(
     }
 }
 
-#[post("/groups")]
-async fn create_group_route(
+#[post("/groups")]
+async fn create_group_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group: web::Json<NewGroup>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let _user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::create_group(&mut conn, &group.0).await;
+    match result {
+        Ok(group_membership) => HttpResponse::Ok().json(ApiResponse::success(group_membership)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/groups/join")]
+async fn join_group_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    join_group: web::Json<JoinGroup>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::redeem_group_invite(&mut conn, &join_group.code, &user_claims.id).await;
+    match result {
+        Ok(group_membership) => HttpResponse::Ok().json(ApiResponse::success(group_membership)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[put("/groups/{group_id}")]
+async fn update_group_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    update_group: web::Json<UpdateGroup>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    if update_group.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Group name cannot be empty".to_string(),
+        ));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::update_group(&mut conn, &group_id, &update_group.0).await;
+    match result {
+        Ok(group) => HttpResponse::Ok().json(ApiResponse::success(group)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[delete("/groups/{group_id}")]
+async fn delete_group_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::delete_group(&mut conn, &group_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Group not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/groups/{group_id}/members")]
+async fn get_group_members_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::get_group_members(&mut conn, &group_id).await;
+    match result {
+        Ok(group_members) => HttpResponse::Ok().json(ApiResponse::success(group_members)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[put("/groups/{group_id}/members/{user_id}")]
+async fn update_group_member_role_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, String)>,
+    update_role: web::Json<UpdateGroupMemberRole>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (group_id, user_id) = params.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &group_id,
+        Permission::ManageMembers,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result =
+        db_util::update_group_member_role(&mut conn, &group_id, &user_id, &update_role.role).await;
+    match result {
+        Ok(group_membership) => HttpResponse::Ok().json(ApiResponse::success(group_membership)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[delete("/groups/{group_id}/members/{user_id}")]
+async fn remove_group_member_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, String)>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (group_id, user_id) = params.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &group_id,
+        Permission::ManageMembers,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result = db_util::remove_group_member(&mut conn, &group_id, &user_id).await;
+    match result {
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[post("/groups/{group_id}/leave")]
+async fn leave_group_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::remove_group_member(&mut conn, &group_id, &user_claims.id).await;
+    match result {
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[put("/groups/{group_id}/period_scheme")]
+async fn update_group_period_scheme_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    update_period_scheme: web::Json<UpdateGroupPeriodScheme>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::update_group_period_scheme(
+        &mut conn,
+        &group_id,
+        &update_period_scheme.period_scheme,
+    )
+    .await;
+    match result {
+        Ok(group) => HttpResponse::Ok().json(ApiResponse::success(group)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[put("/groups/{group_id}/blind_ratings")]
+async fn update_group_blind_ratings_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    update_blind_ratings: web::Json<UpdateGroupBlindRatings>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::update_group_blind_ratings(
+        &mut conn,
+        &group_id,
+        update_blind_ratings.blind_ratings,
+    )
+    .await;
+    match result {
+        Ok(group) => HttpResponse::Ok().json(ApiResponse::success(group)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[put("/groups/{group_id}/rating_scale")]
+async fn update_group_rating_scale_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    rating_scale: web::Json<RatingScale>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::update_group_rating_scale(&mut conn, &group_id, &rating_scale.0).await;
+    match result {
+        Ok(group) => HttpResponse::Ok().json(ApiResponse::success(group)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[put("/groups/{group_id}/rating_quorum")]
+async fn update_group_rating_quorum_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    update_rating_quorum: web::Json<UpdateGroupRatingQuorum>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::update_group_rating_quorum(
+        &mut conn,
+        &group_id,
+        update_rating_quorum.rating_quorum,
+    )
+    .await;
+    match result {
+        Ok(group) => HttpResponse::Ok().json(ApiResponse::success(group)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[get("/groups/{group_id}/absences")]
+async fn get_member_absences_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::get_member_absences(&mut conn, &group_id).await;
+    match result {
+        Ok(absences) => HttpResponse::Ok().json(ApiResponse::success(absences)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/groups/{group_id}/absences")]
+async fn create_member_absence_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    new_absence: web::Json<NewMemberAbsence>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    // NOTE: Members can mark themselves absent, admins can mark anyone
+    let permission = match new_absence.user_id == user_claims.id {
+        true => Permission::Contribute,
+        false => Permission::ManageMembers,
+    };
+    if let Err(err) = auth::authorize(&mut conn, &user_claims, &group_id, permission).await {
+        return err;
+    }
+
+    let result = db_util::create_member_absence(&mut conn, &group_id, &new_absence.0).await;
+    match result {
+        Ok(absence) => HttpResponse::Ok().json(ApiResponse::success(absence)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[delete("/groups/{group_id}/absences/{absence_id}")]
+async fn delete_member_absence_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, i32)>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (group_id, absence_id) = params.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    let absence = match db_util::get_member_absence(&mut conn, absence_id).await {
+        Ok(absence) if absence.group_id == group_id => absence,
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Absence not found".to_string()));
+        }
+    };
+
+    if absence.user_id != user_claims.id {
+        if let Err(err) = auth::authorize(
+            &mut conn,
+            &user_claims,
+            &group_id,
+            Permission::ManageMembers,
+        )
+        .await
+        {
+            return err;
+        }
+    }
+
+    let result = db_util::delete_member_absence(&mut conn, absence_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Absence not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/groups/{group_id}/rounds")]
+async fn get_group_rounds_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::get_group_rounds(&mut conn, &group_id).await;
+    match result {
+        Ok(group_rounds) => HttpResponse::Ok().json(ApiResponse::success(group_rounds)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/groups/{group_id}/rounds")]
+async fn create_group_round_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    new_group_round: web::Json<NewGroupRound>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    if new_group_round.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Round name cannot be empty".to_string(),
+        ));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::create_group_round(&mut conn, &group_id, &new_group_round.0).await;
+    match result {
+        Ok(group_round) => HttpResponse::Ok().json(ApiResponse::success(group_round)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[delete("/groups/{group_id}/rounds/{round_id}")]
+async fn delete_group_round_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, i32)>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (group_id, round_id) = params.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::delete_group_round(&mut conn, round_id, &group_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Round not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/groups/{group_id}/dimensions")]
+async fn get_rating_dimensions_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::get_rating_dimensions(&mut conn, &group_id).await;
+    match result {
+        Ok(rating_dimensions) => HttpResponse::Ok().json(ApiResponse::success(rating_dimensions)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/groups/{group_id}/dimensions")]
+async fn create_rating_dimension_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    new_rating_dimension: web::Json<NewRatingDimension>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result =
+        db_util::create_rating_dimension(&mut conn, &group_id, &new_rating_dimension.0).await;
+    match result {
+        Ok(rating_dimension) => HttpResponse::Ok().json(ApiResponse::success(rating_dimension)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[put("/groups/{group_id}/dimensions/{dimension_id}")]
+async fn update_rating_dimension_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, i32)>,
+    updated_rating_dimension: web::Json<NewRatingDimension>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (group_id, dimension_id) = params.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::update_rating_dimension(
+        &mut conn,
+        &group_id,
+        dimension_id,
+        &updated_rating_dimension.0,
+    )
+    .await;
+    match result {
+        Ok(rating_dimension) => HttpResponse::Ok().json(ApiResponse::success(rating_dimension)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[delete("/groups/{group_id}/dimensions/{dimension_id}")]
+async fn delete_rating_dimension_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, i32)>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (group_id, dimension_id) = params.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, &group_id, Permission::ManageGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::delete_rating_dimension(&mut conn, &group_id, dimension_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => HttpResponse::NotFound()
+            .json(ApiResponse::<()>::error("Dimension not found".to_string())),
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[post("/groups/{group_id}/invites")]
+async fn create_group_invite_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    new_group_invite: web::Json<NewGroupInvite>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    if new_group_invite
+        .max_uses
+        .is_some_and(|max_uses| max_uses < 1)
+    {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "max_uses must be at least 1".to_string(),
+        ));
+    }
+    if new_group_invite
+        .expires_in_hours
+        .is_some_and(|expires_in_hours| !(1..=MAX_INVITE_EXPIRY_HOURS).contains(&expires_in_hours))
+    {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!(
+            "expires_in_hours must be between 1 and {MAX_INVITE_EXPIRY_HOURS}"
+        )));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &group_id,
+        Permission::ManageMembers,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result =
+        db_util::create_group_invite(&mut conn, &group_id, &user_claims.id, &new_group_invite.0)
+            .await;
+    match result {
+        Ok(group_invite) => HttpResponse::Ok().json(ApiResponse::success(group_invite)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/groups/{group_id}/invites")]
+async fn get_group_invites_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &group_id,
+        Permission::ManageMembers,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result = db_util::get_group_invites(&mut conn, &group_id).await;
+    match result {
+        Ok(group_invites) => HttpResponse::Ok().json(ApiResponse::success(group_invites)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[delete("/groups/{group_id}/invites/{invite_id}")]
+async fn revoke_group_invite_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(String, i32)>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let (group_id, invite_id) = params.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &group_id,
+        Permission::ManageMembers,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result = db_util::revoke_group_invite(&mut conn, invite_id, &group_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Invite not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/venues")]
+async fn get_venues_route(pool: web::Data<MySqlPool>, req: HttpRequest) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    if let Err(err) = auth::validate_token(&req).await {
+        return err;
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::get_venues(&mut conn).await;
+    match result {
+        Ok(venues) => HttpResponse::Ok().json(ApiResponse::success(venues)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/venues")]
+async fn create_venue_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    new_venue: web::Json<NewVenue>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    if new_venue.name.trim().is_empty() || new_venue.cuisine.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Venue name and cuisine can't be empty".to_string(),
+        ));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize_superadmin(&mut conn, &user_claims).await {
+        return err;
+    }
+
+    match db_util::get_venue_by_name(&mut conn, &new_venue.name).await {
+        Ok(Some(venue)) => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(format!(
+                "{} is already in the catalogue",
+                venue.name
+            )));
+        }
+        Ok(None) => {}
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    let result = db_util::create_venue(&mut conn, &new_venue.0).await;
+    match result {
+        Ok(venue) => HttpResponse::Ok().json(ApiResponse::success(venue)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[put("/venues/{id}")]
+async fn update_venue_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    updated_venue: web::Json<NewVenue>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let venue_id = id.into_inner();
+
+    if updated_venue.name.trim().is_empty() || updated_venue.cuisine.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Venue name and cuisine can't be empty".to_string(),
+        ));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize_superadmin(&mut conn, &user_claims).await {
+        return err;
+    }
+
+    match db_util::get_venue(&mut conn, venue_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Venue not found".to_string()));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    match db_util::get_venue_by_name(&mut conn, &updated_venue.name).await {
+        Ok(Some(venue)) if venue.id != venue_id => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(format!(
+                "{} is already in the catalogue",
+                venue.name
+            )));
+        }
+        Ok(_) => {}
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    }
+
+    let result = db_util::update_venue(&mut conn, venue_id, &updated_venue.0).await;
+    match result {
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/venues/{id}/adopt")]
+async fn adopt_venue_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    adopt: web::Json<AdoptVenue>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &adopt.group_id,
+        Permission::ManageRestaurants,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let venue = match db_util::get_venue(&mut conn, id.into_inner()).await {
+        Ok(Some(venue)) => venue,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Venue not found".to_string()));
+        }
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    let restaurants = match db_util::get_restaurants(&mut conn, &adopt.group_id).await {
+        Ok(restaurants) => restaurants,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    if restaurants.iter().any(|restaurant| {
+        restaurant.venue_id == venue.id
+            || restaurant.restaurant_code.eq_ignore_ascii_case(&venue.name)
+    }) {
+        return HttpResponse::Conflict().json(ApiResponse::<()>::error(format!(
+            "The group already has {}",
+            venue.name
+        )));
+    }
+
+    let result = db_util::adopt_venue(&mut conn, &venue, &adopt.0).await;
+    match result {
+        Ok(restaurant) => HttpResponse::Ok().json(ApiResponse::success(restaurant)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/groups/{group_id}/restaurants/import")]
+async fn import_restaurants_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    group_id: web::Path<String>,
+    import: web::Json<ImportRestaurants>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let group_id = group_id.into_inner();
+
+    if import.source_group_id == group_id {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Restaurants can't be imported from the same group".to_string(),
+        ));
+    }
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &group_id,
+        Permission::ManageRestaurants,
+    )
+    .await
+    {
+        return err;
+    }
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &import.source_group_id,
+        Permission::ViewGroup,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result = db_util::import_restaurants(&mut conn, &group_id, &import.0).await;
+    match result {
+        Ok(restaurants) => HttpResponse::Ok().json(ApiResponse::success(restaurants)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[put("/restaurants/{id}")]
+async fn update_restaurant_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    restaurant: web::Json<Restaurant>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let restaurant = match restaurant.into_inner().normalize() {
+        Ok(restaurant) => restaurant,
+        Err(error) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &restaurant.group_id,
+        Permission::ManageRestaurants,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result = db_util::update_restaurant(&mut conn, id.into_inner(), &restaurant).await;
+    match result {
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/restaurants")]
+async fn create_restaurant_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    restaurant: web::Json<Restaurant>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let restaurant = match restaurant.into_inner().normalize() {
+        Ok(restaurant) => restaurant,
+        Err(error) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &restaurant.group_id,
+        Permission::ManageRestaurants,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let result = db_util::create_restaurant(&mut conn, &restaurant).await;
+    match result {
+        Ok(restaurant) => HttpResponse::Ok().json(ApiResponse::success(restaurant)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/restaurants")]
+async fn get_restaurants_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::<()>::error("group_id is missing".to_string()));
+        }
+    };
+
+    let filter = match RestaurantFilter::from_query(&query_params) {
+        Ok(filter) => filter,
+        Err(error) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    if let Some(near) = filter.near {
+        let result = db_util::get_restaurants_near(&mut conn, group_id, &near).await;
+        return match result {
+            Ok(restaurants) => {
+                HttpResponse::Ok().json(ApiResponse::success(filter.apply_near(restaurants)))
+            }
+            Err(error) => HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string())),
+        };
+    }
+
+    let result = db_util::get_restaurants(&mut conn, group_id).await;
+    match result {
+        Ok(restaurants) => HttpResponse::Ok().json(ApiResponse::success(filter.apply(restaurants))),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/restaurants/{id}")]
+async fn get_restaurant_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let restaurant_id = path.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::get_restaurant(&mut conn, restaurant_id).await;
+    match result {
+        Ok(restaurant) => HttpResponse::Ok().json(ApiResponse::success(restaurant)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/restaurants_with_avg_rating")]
+async fn get_restaurants_with_avg_rating_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let _user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::get_restaurants_with_avg_rating(&mut conn, group_id).await;
+    match result {
+        Ok(restaurants_with_avg) => {
+            HttpResponse::Ok().json(ApiResponse::success(restaurants_with_avg))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/restaurants/{id}/ratings")]
+async fn get_restaurant_ratings_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let restaurant_id = id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+    let result = db_util::get_ratings_by_restaurant(&mut conn, restaurant_id, group_id).await;
+    match result {
+        Ok(restaurant_ratings) => {
+            let ratings_hidden =
+                match db_util::are_ratings_hidden(&pool, restaurant_id, group_id).await {
+                    Ok(ratings_hidden) => ratings_hidden,
+                    Err(error) => {
+                        return HttpResponse::InternalServerError()
+                            .json(ApiResponse::<()>::error(error.to_string()));
+                    }
+                };
+
+            match ratings_hidden {
+                true => HttpResponse::Ok().json(ApiResponse::success(
+                    restaurant_ratings.blinded(&user_claims.id),
+                )),
+                false => HttpResponse::Ok().json(ApiResponse::success(restaurant_ratings)),
+            }
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/restaurants/{id}/ratings/{year}/{period}")]
+async fn get_restaurant_ratings_per_period_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(i32, i32, String)>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let (restaurant_id, year, period) = params.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    let period = match db_util::get_period_by_path(&mut conn, group_id, year, &period).await {
+        Ok(period) => period,
+        Err(error) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    let ratings = match db_util::get_ratings_by_restaurant_per_period(
+        &mut conn,
+        group_id,
+        restaurant_id,
+        &period,
+    )
+    .await
+    {
+        Ok(ratings) => ratings,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    let result =
+        db_util::get_dimension_averages_per_period(&mut conn, group_id, restaurant_id, &period)
+            .await;
+    let dimension_averages = match result {
+        Ok(dimension_averages) => dimension_averages,
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    let ratings_for_period = RatingsForPeriod::new(period, ratings, dimension_averages);
+
+    // NOTE: Past periods are always revealed, only the running one can still be blind
+    let is_current_period = db_util::get_current_period(&mut conn, group_id)
+        .await
+        .unwrap_or_default()
+        .is_some_and(|current_period| current_period == ratings_for_period.period);
+    let ratings_hidden = match is_current_period {
+        true => db_util::are_ratings_hidden(&pool, restaurant_id, group_id).await,
+        false => Ok(false),
+    };
+
+    match ratings_hidden {
+        Ok(true) => HttpResponse::Ok().json(ApiResponse::success(
+            ratings_for_period.blinded(&user_claims.id),
+        )),
+        Ok(false) => HttpResponse::Ok().json(ApiResponse::success(ratings_for_period)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[delete("/restaurants/{id}/ratings/{rating_id}/review")]
+async fn delete_rating_review_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(i32, i32)>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let (restaurant_id, rating_id) = params.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    let rating = match db_util::get_rating(&mut conn, rating_id).await {
+        Ok(rating) if rating.group_id == *group_id && rating.restaurant_id == restaurant_id => {
+            rating
+        }
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Rating not found".to_string()));
+        }
+    };
+
+    if rating.user_id != user_claims.id {
+        if let Err(err) =
+            auth::authorize(&mut conn, &user_claims, group_id, Permission::Moderate).await
+        {
+            return err;
+        }
+    }
+
+    let result = db_util::delete_rating_review(&mut conn, rating_id, group_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Review not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[get("/restaurants/{id}/comments")]
+async fn get_restaurant_comments_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::get_comments(&mut conn, group_id, id.into_inner()).await;
+    match result {
+        Ok(comments) => HttpResponse::Ok().json(ApiResponse::success(comments)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/restaurants/{id}/comments")]
+async fn create_restaurant_comment_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    query_params: web::Query<HashMap<String, String>>,
+    new_comment: web::Json<NewComment>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::Contribute).await
+    {
+        return err;
+    }
+
+    let result = db_util::create_comment(
+        &mut conn,
+        group_id,
+        id.into_inner(),
+        &user_claims.id,
+        &new_comment.0,
+    )
+    .await;
+    match result {
+        Ok(comment) => HttpResponse::Ok().json(ApiResponse::success(comment)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[put("/restaurants/{id}/comments/{comment_id}")]
+async fn update_restaurant_comment_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
-    group: web::Json<NewGroup>,
+    params: web::Path<(i32, i32)>,
+    query_params: web::Query<HashMap<String, String>>,
+    update_comment: web::Json<UpdateComment>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
+    let (restaurant_id, comment_id) = params.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::create_group(&mut conn, &group.0).await;
+
+    let comment = match db_util::get_comment(&mut conn, comment_id).await {
+        Ok(comment) if comment.group_id == *group_id && comment.restaurant_id == restaurant_id => {
+            comment
+        }
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Comment not found".to_string()));
+        }
+    };
+
+    if comment.user_id != user_claims.id {
+        if let Err(err) =
+            auth::authorize(&mut conn, &user_claims, group_id, Permission::Moderate).await
+        {
+            return err;
+        }
+    }
+
+    let result = db_util::update_comment(&mut conn, comment_id, &update_comment.0).await;
     match result {
-        Ok(group_membership) => HttpResponse::Ok().json(ApiResponse::success(group_membership)),
+        Ok(comment) => HttpResponse::Ok().json(ApiResponse::success(comment)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[delete("/restaurants/{id}/comments/{comment_id}")]
+async fn delete_restaurant_comment_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(i32, i32)>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let (restaurant_id, comment_id) = params.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    let comment = match db_util::get_comment(&mut conn, comment_id).await {
+        Ok(comment) if comment.group_id == *group_id && comment.restaurant_id == restaurant_id => {
+            comment
+        }
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Comment not found".to_string()));
+        }
+    };
+
+    if comment.user_id != user_claims.id {
+        if let Err(err) =
+            auth::authorize(&mut conn, &user_claims, group_id, Permission::Moderate).await
+        {
+            return err;
+        }
+    }
+
+    // NOTE: Replies are removed together with the comment they answer to
+    let result = db_util::delete_comment(&mut conn, comment_id).await;
+    match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Comment not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[post("/groups/join")]
-async fn join_group_route(
+#[get("/restaurants/{id}/visits")]
+async fn get_restaurant_visits_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
-    group_membership: web::Json<NewGroupMembership>,
+    id: web::Path<i32>,
+    query_params: web::Query<HashMap<String, String>>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::create_group_membership(&mut conn, &group_membership.0).await;
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    let result = db_util::get_visits(&mut conn, group_id, id.into_inner()).await;
     match result {
-        Ok(group_membership) => HttpResponse::Ok().json(ApiResponse::success(group_membership)),
+        Ok(visits) => HttpResponse::Ok().json(ApiResponse::success(visits)),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[put("/restaurants/{id}")]
-async fn update_restaurant_route(
+#[post("/restaurants/{id}/visits")]
+async fn create_restaurant_visit_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
     id: web::Path<i32>,
-    restaurant: web::Json<Restaurant>,
+    query_params: web::Query<HashMap<String, String>>,
+    new_visit: web::Json<NewVisit>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
     let mut conn = db_util::get_connection(&pool).await.unwrap();
 
-    let group_membership = db_util::get_group_memberships_by_user(&mut conn, &user_claims.id)
-        .await
-        .unwrap_or_default()
-        .into_iter()
-        .find(|gm| gm.group_id == restaurant.group_id);
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::Contribute).await
+    {
+        return err;
+    }
+
+    let result = db_util::create_visit(
+        &mut conn,
+        group_id,
+        id.into_inner(),
+        &user_claims.id,
+        &new_visit.0,
+    )
+    .await;
+    match result {
+        Ok(visit) => HttpResponse::Ok().json(ApiResponse::success(visit)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[get("/restaurants/{id}/visits/{visit_id}")]
+async fn get_restaurant_visit_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(i32, i32)>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
 
-    if let Some(membership) = group_membership {
-        if !matches!(membership.role, Role::Admin) {
-            return HttpResponse::Forbidden().json(ApiResponse::<()>::error(
-                "Only admins can update restaurants".to_string(),
+    let (restaurant_id, visit_id) = params.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
             ));
         }
-    } else {
-        return HttpResponse::Forbidden().json(ApiResponse::<()>::error(
-            "User is not a member of this group".to_string(),
-        ));
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    match db_util::get_visit(&mut conn, visit_id).await {
+        Ok(visit) if visit.group_id == *group_id && visit.restaurant_id == restaurant_id => {
+            HttpResponse::Ok().json(ApiResponse::success(visit))
+        }
+        Ok(_) | Err(_) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Visit not found".to_string()))
+        }
+    }
+}
+
+#[delete("/restaurants/{id}/visits/{visit_id}")]
+async fn delete_restaurant_visit_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    params: web::Path<(i32, i32)>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let (restaurant_id, visit_id) = params.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    let visit = match db_util::get_visit(&mut conn, visit_id).await {
+        Ok(visit) if visit.group_id == *group_id && visit.restaurant_id == restaurant_id => visit,
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Visit not found".to_string()));
+        }
+    };
+
+    if visit.created_by != user_claims.id {
+        if let Err(err) =
+            auth::authorize(&mut conn, &user_claims, group_id, Permission::Moderate).await
+        {
+            return err;
+        }
     }
 
-    let result = db_util::update_restaurant(&mut conn, id.into_inner(), &restaurant.0).await;
+    // NOTE: Ratings of the visit are kept and count towards the period again
+    let result = db_util::delete_visit(&mut conn, visit_id).await;
     match result {
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Visit not found".to_string()))
+        }
         Ok(query_result) => {
             HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
         }
@@ -452,114 +3283,251 @@ async fn update_restaurant_route(
     }
 }
 
-#[post("/restaurants")]
-async fn create_restaurant_route(
+#[get("/restaurants/{id}/menu")]
+async fn get_menu_items_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    query_params: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let restaurant_id = id.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == *group_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
+        }
+    }
+
+    let result = db_util::get_menu_items(&mut conn, restaurant_id).await;
+    match result {
+        Ok(menu_items) => HttpResponse::Ok().json(ApiResponse::success(menu_items)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
+#[post("/restaurants/{id}/menu")]
+async fn create_menu_item_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    query_params: web::Query<HashMap<String, String>>,
+    new_menu_item: web::Json<NewMenuItem>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+
+    let restaurant_id = id.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::Contribute).await
+    {
+        return err;
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == *group_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
+        }
+    }
+
+    let result = db_util::create_menu_item(&mut conn, restaurant_id, &new_menu_item.0).await;
+    match result {
+        Ok(menu_item) => HttpResponse::Ok().json(ApiResponse::success(menu_item)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
+}
+
+#[put("/restaurants/{id}/menu/{menu_item_id}")]
+async fn update_menu_item_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
-    restaurant: web::Json<Restaurant>,
+    params: web::Path<(i32, i32)>,
+    query_params: web::Query<HashMap<String, String>>,
+    menu_item: web::Json<NewMenuItem>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
+    let (restaurant_id, menu_item_id) = params.into_inner();
+    let group_id = match query_params.get("group_id") {
+        Some(group_id) => group_id,
+        None => {
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
+        }
+    };
+
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::create_restaurant(&mut conn, &restaurant.0).await;
-    match result {
-        Ok(restaurant) => HttpResponse::Ok().json(ApiResponse::success(restaurant)),
-        Err(error) => {
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::Contribute).await
+    {
+        return err;
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == *group_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
+        }
+    }
+
+    match db_util::get_menu_item(&mut conn, menu_item_id).await {
+        Ok(menu_item) if menu_item.restaurant_id == restaurant_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Dish not found".to_string()));
         }
     }
+
+    let result = db_util::update_menu_item(&mut conn, menu_item_id, &menu_item.0).await;
+    match result {
+        Ok(menu_item) => HttpResponse::Ok().json(ApiResponse::success(menu_item)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::<()>::error(error.to_string())),
+    }
 }
 
-#[get("/restaurants")]
-async fn get_restaurants_route(
+#[delete("/restaurants/{id}/menu/{menu_item_id}")]
+async fn delete_menu_item_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
+    params: web::Path<(i32, i32)>,
     query_params: web::Query<HashMap<String, String>>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
+    let (restaurant_id, menu_item_id) = params.into_inner();
     let group_id = match query_params.get("group_id") {
         Some(group_id) => group_id,
         None => {
-            return HttpResponse::BadRequest()
-                .json(ApiResponse::<()>::error("group_id is missing".to_string()));
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
+                "group_id was not provided".to_string(),
+            ));
         }
     };
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
 
-    let group_membership = db_util::get_group_memberships_by_user(&mut conn, &user_claims.id)
-        .await
-        .unwrap_or_default()
-        .into_iter()
-        .find(|gm| gm.group_id == *group_id);
-
-    if group_membership.is_none() {
-        return HttpResponse::Forbidden().json(ApiResponse::<()>::error(
-            "User is not a member of this group".to_string(),
-        ));
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        group_id,
+        Permission::ManageRestaurants,
+    )
+    .await
+    {
+        return err;
     }
 
-    let result = db_util::get_restaurants(&mut conn, group_id).await;
-    match result {
-        Ok(restaurants) => HttpResponse::Ok().json(ApiResponse::success(restaurants)),
-        Err(error) => {
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == *group_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
         }
     }
-}
 
-#[get("/restaurants/{id}")]
-async fn get_restaurant_route(
-    pool: web::Data<MySqlPool>,
-    req: HttpRequest,
-    path: web::Path<i32>,
-) -> HttpResponse {
-    if let Err(err) = auth::validate_ip(&req) {
-        return err;
+    match db_util::get_menu_item(&mut conn, menu_item_id).await {
+        Ok(menu_item) if menu_item.restaurant_id == restaurant_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Dish not found".to_string()));
+        }
     }
 
-    let restaurant_id = path.into_inner();
-
-    let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::get_restaurant(&mut conn, restaurant_id).await;
+    let result = db_util::delete_menu_item(&mut conn, menu_item_id).await;
     match result {
-        Ok(restaurant) => HttpResponse::Ok().json(ApiResponse::success(restaurant)),
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Dish not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[get("/restaurants_with_avg_rating")]
-async fn get_restaurants_with_avg_rating_route(
+#[get("/restaurants/{id}/menu/{menu_item_id}/ratings")]
+async fn get_dish_ratings_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
+    params: web::Path<(i32, i32)>,
     query_params: web::Query<HashMap<String, String>>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
+    let (restaurant_id, menu_item_id) = params.into_inner();
     let group_id = match query_params.get("group_id") {
         Some(group_id) => group_id,
         None => {
@@ -570,33 +3538,56 @@ async fn get_restaurants_with_avg_rating_route(
     };
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::get_restaurants_with_avg_rating(&mut conn, group_id).await;
-    match result {
-        Ok(restaurants_with_avg) => {
-            HttpResponse::Ok().json(ApiResponse::success(restaurants_with_avg))
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == *group_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
+        }
+    }
+
+    match db_util::get_menu_item(&mut conn, menu_item_id).await {
+        Ok(menu_item) if menu_item.restaurant_id == restaurant_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Dish not found".to_string()));
         }
+    }
+
+    let result = db_util::get_dish_ratings(&mut conn, menu_item_id).await;
+    match result {
+        Ok(dish_ratings) => HttpResponse::Ok().json(ApiResponse::success(dish_ratings)),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[get("/restaurants/{id}/ratings")]
-async fn get_restaurant_ratings_route(
+#[post("/restaurants/{id}/menu/{menu_item_id}/ratings")]
+async fn rate_menu_item_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
-    id: web::Path<i32>,
+    params: web::Path<(i32, i32)>,
     query_params: web::Query<HashMap<String, String>>,
+    dish_rating: web::Json<NewDishRating>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
+    let (restaurant_id, menu_item_id) = params.into_inner();
     let group_id = match query_params.get("group_id") {
         Some(group_id) => group_id,
         None => {
@@ -607,32 +3598,76 @@ async fn get_restaurant_ratings_route(
     };
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::get_ratings_by_restaurant(&mut conn, id.into_inner(), group_id).await;
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::Contribute).await
+    {
+        return err;
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == *group_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
+        }
+    }
+
+    match db_util::get_menu_item(&mut conn, menu_item_id).await {
+        Ok(menu_item) if menu_item.restaurant_id == restaurant_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Dish not found".to_string()));
+        }
+    }
+
+    let rating_scale = match db_util::get_group(&mut conn, group_id).await {
+        Ok(group) => group.rating_scale(),
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    if let Err(invalid_score) = rating_scale.check_score(dish_rating.score, None) {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error_with_data(
+            invalid_score.to_string(),
+            invalid_score,
+        ));
+    }
+
+    let result = db_util::rate_menu_item(
+        &mut conn,
+        group_id,
+        menu_item_id,
+        &user_claims.id,
+        dish_rating.score,
+    )
+    .await;
     match result {
-        Ok(restaurant_ratings) => HttpResponse::Ok().json(ApiResponse::success(restaurant_ratings)),
+        Ok(dish_rating) => HttpResponse::Ok().json(ApiResponse::success(dish_rating)),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
     }
 }
 
-#[get("/restaurants/{id}/ratings/{year}/{period}")]
-async fn get_restaurant_ratings_per_period_route(
+#[get("/restaurants/{id}/best_dishes")]
+async fn get_best_dishes_route(
     pool: web::Data<MySqlPool>,
     req: HttpRequest,
-    params: web::Path<(i32, i32, Period)>,
+    id: web::Path<i32>,
     query_params: web::Query<HashMap<String, String>>,
 ) -> HttpResponse {
     if let Err(err) = auth::validate_ip(&req) {
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
 
-    let (restaurant_id, year, period) = params.into_inner();
+    let restaurant_id = id.into_inner();
     let group_id = match query_params.get("group_id") {
         Some(group_id) => group_id,
         None => {
@@ -643,16 +3678,29 @@ async fn get_restaurant_ratings_per_period_route(
     };
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
-    let result = db_util::get_ratings_by_restaurant_per_period(
-        &mut conn,
-        group_id,
-        restaurant_id,
-        year,
-        &period,
-    )
-    .await;
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == *group_id => {}
+        Ok(_) | Err(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
+        }
+    }
+
+    let limit = query_params
+        .get("limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(5);
+
+    let result = db_util::get_best_dishes(&mut conn, restaurant_id, limit).await;
     match result {
-        Ok(restaurant_ratings) => HttpResponse::Ok().json(ApiResponse::success(restaurant_ratings)),
+        Ok(best_dishes) => HttpResponse::Ok().json(ApiResponse::success(best_dishes)),
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
@@ -671,7 +3719,7 @@ async fn is_restaurant_rating_complete_route(
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let _user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -700,6 +3748,66 @@ async fn is_restaurant_rating_complete_route(
     }
 }
 
+/// Archives a restaurant, or brings it back. Archived restaurants can't be rated or visited
+/// anymore, but their ratings still count for the periods they were given in.
+#[put("/restaurants/{id}/active")]
+async fn update_restaurant_active_route(
+    pool: web::Data<MySqlPool>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    update_active: web::Json<UpdateRestaurantActive>,
+) -> HttpResponse {
+    if let Err(err) = auth::validate_ip(&req) {
+        return err;
+    }
+
+    let user_claims = match auth::validate_token(&req).await {
+        Ok(claims) => claims,
+        Err(err) => return err,
+    };
+    let restaurant_id = id.into_inner();
+
+    let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &update_active.group_id,
+        Permission::ManageRestaurants,
+    )
+    .await
+    {
+        return err;
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) if restaurant.group_id == update_active.group_id => {}
+        _ => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("Restaurant not found".to_string()));
+        }
+    }
+
+    let result = db_util::update_restaurant_active(
+        &mut conn,
+        restaurant_id,
+        &update_active.group_id,
+        update_active.active,
+    )
+    .await;
+    if let Err(error) = result {
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::error(error.to_string()));
+    }
+
+    match db_util::get_restaurant(&mut conn, restaurant_id).await {
+        Ok(restaurant) => HttpResponse::Ok().json(ApiResponse::success(restaurant)),
+        Err(error) => {
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
+        }
+    }
+}
+
 #[delete("/restaurants/{id}")]
 async fn delete_restaurant_route(
     pool: web::Data<MySqlPool>,
@@ -711,7 +3819,7 @@ async fn delete_restaurant_route(
         return err;
     }
 
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -726,27 +3834,39 @@ async fn delete_restaurant_route(
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
 
-    let group_membership = db_util::get_group_memberships_by_user(&mut conn, &user_claims.id)
-        .await
-        .unwrap_or_default()
-        .into_iter()
-        .find(|gm| gm.group_id == *group_id);
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        group_id,
+        Permission::ManageRestaurants,
+    )
+    .await
+    {
+        return err;
+    }
 
-    if let Some(membership) = group_membership {
-        if !matches!(membership.role, Role::Admin) {
-            return HttpResponse::Forbidden().json(ApiResponse::<()>::error(
-                "Only admins can delete restaurants".to_string(),
+    let restaurant_id = id.into_inner();
+
+    match db_util::restaurant_has_history(&mut conn, restaurant_id).await {
+        Ok(true) => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "Restaurant has ratings or visits, archive it instead".to_string(),
             ));
         }
-    } else {
-        return HttpResponse::Forbidden().json(ApiResponse::<()>::error(
-            "User is not a member of this group".to_string(),
-        ));
+        Ok(false) => {}
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
     }
 
-    let result = db_util::delete_restaurant(&mut conn, id.into_inner(), group_id).await;
+    let result = db_util::delete_restaurant(&mut conn, restaurant_id, group_id).await;
     match result {
-        Ok(rows) => HttpResponse::Ok().json(ApiResponse::success(rows.last_insert_id())),
+        Ok(query_result) if query_result.rows_affected() == 0 => HttpResponse::NotFound()
+            .json(ApiResponse::<()>::error("Restaurant not found".to_string())),
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
@@ -764,7 +3884,7 @@ async fn rate_restaurant_route(
         return err;
     }
 
-    let user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -778,13 +3898,54 @@ async fn rate_restaurant_route(
     let mut new_rating = rating.into_inner();
     new_rating.user_id = user_claims.id.clone();
 
-    let rated = db_util::is_restaurant_rated_by_user(
+    if let Err(err) = auth::authorize(
         &mut conn,
-        new_rating.restaurant_id,
-        &new_rating.user_id,
+        &user_claims,
         &new_rating.group_id,
+        Permission::Contribute,
     )
     .await
+    {
+        return err;
+    }
+
+    let rating_scale = match db_util::get_group(&mut conn, &new_rating.group_id).await {
+        Ok(group) => group.rating_scale(),
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    if let Err(invalid_score) = rating_scale.check_rating(&new_rating) {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error_with_data(
+            invalid_score.to_string(),
+            invalid_score,
+        ));
+    }
+
+    // NOTE: Archived restaurants can't be rated anymore, not even to change an earlier rating
+    if let Ok(restaurant) = db_util::get_restaurant(&mut conn, new_rating.restaurant_id).await {
+        if !restaurant.active {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "Restaurant is archived".to_string(),
+            ));
+        }
+    }
+
+    let rated = match new_rating.visit_id {
+        Some(visit_id) => {
+            db_util::is_visit_rated_by_user(&mut conn, visit_id, &new_rating.user_id).await
+        }
+        None => {
+            db_util::is_restaurant_rated_by_user(
+                &mut conn,
+                new_rating.restaurant_id,
+                &new_rating.user_id,
+                &new_rating.group_id,
+            )
+            .await
+        }
+    }
     .unwrap_or_default();
 
     let result = match rated {
@@ -809,7 +3970,7 @@ async fn rate_restaurant_route(
 //         return err;
 //     }
 //
-//     let _user_claims = match auth::validate_token(&req) {
+//     let _user_claims = match auth::validate_token(&req).await {
 //         Ok(claims) => claims,
 //         Err(err) => return err,
 //     };
@@ -835,7 +3996,7 @@ async fn get_ratings_by_user_and_group_route(
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -850,9 +4011,43 @@ async fn get_ratings_by_user_and_group_route(
     };
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
     let result = db_util::get_ratings_by_user_and_group(&mut conn, &user_id, group_id).await;
     match result {
-        Ok(ratings) => HttpResponse::Ok().json(ApiResponse::success(ratings)),
+        Ok(mut ratings) => {
+            // NOTE: Someone else's current ratings stay hidden for restaurants a blind group hasn't finished
+            if *user_id != user_claims.id {
+                let mut revealed_ratings = Vec::new();
+                let mut hidden_restaurant_ids = Vec::new();
+                for rating in ratings.current_period_ratings {
+                    match db_util::are_ratings_hidden(&pool, rating.restaurant_id, group_id).await {
+                        Ok(false) => revealed_ratings.push(rating),
+                        Ok(true) => hidden_restaurant_ids.push(rating.restaurant_id),
+                        Err(error) => {
+                            return HttpResponse::InternalServerError()
+                                .json(ApiResponse::<()>::error(error.to_string()));
+                        }
+                    }
+                }
+                ratings.current_period_ratings = revealed_ratings;
+                ratings.ratings_hidden = !hidden_restaurant_ids.is_empty();
+
+                // NOTE: The current period's average of a hidden restaurant is the hidden score itself
+                let current_period = ratings.current_period.clone();
+                ratings.historical_ratings.retain(|average| {
+                    Some(&average.period) != current_period.as_ref()
+                        || !hidden_restaurant_ids.contains(&average.restaurant_id)
+                });
+            }
+
+            HttpResponse::Ok().json(ApiResponse::success(ratings))
+        }
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
@@ -870,7 +4065,7 @@ async fn get_rating_route(
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -886,6 +4081,23 @@ async fn get_rating_route(
     };
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) =
+        auth::authorize(&mut conn, &user_claims, group_id, Permission::ViewGroup).await
+    {
+        return err;
+    }
+
+    if user_id != user_claims.id
+        && db_util::are_ratings_hidden(&pool, restaurant_id, group_id)
+            .await
+            .unwrap_or(true)
+    {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error(
+            "Ratings are hidden until every member has rated this restaurant".to_string(),
+        ));
+    }
+
     let result =
         db_util::get_rating_by_restaurant(&mut conn, &user_id, group_id, restaurant_id).await;
     match result {
@@ -907,12 +4119,42 @@ async fn update_rating_route(
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
+    let user_id = user_id.into_inner();
+    if user_claims.id != user_id {
+        return HttpResponse::Forbidden().json(ApiResponse::<()>::error("Unauthorized".into()));
+    }
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    if let Err(err) = auth::authorize(
+        &mut conn,
+        &user_claims,
+        &rating.group_id,
+        Permission::Contribute,
+    )
+    .await
+    {
+        return err;
+    }
+
+    let rating_scale = match db_util::get_group(&mut conn, &rating.group_id).await {
+        Ok(group) => group.rating_scale(),
+        Err(error) => {
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error(error.to_string()));
+        }
+    };
+    if let Err(invalid_score) = rating_scale.check_rating(&rating.0) {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error_with_data(
+            invalid_score.to_string(),
+            invalid_score,
+        ));
+    }
+
     let result = db_util::update_rating(&mut conn, &rating.0, &user_id).await;
     match result {
         Ok(updated_rating) => HttpResponse::Ok().json(ApiResponse::success(updated_rating)),
@@ -933,7 +4175,7 @@ async fn delete_rating_route(
         return err;
     }
 
-    let _user_claims = match auth::validate_token(&req) {
+    let user_claims = match auth::validate_token(&req).await {
         Ok(claims) => claims,
         Err(err) => return err,
     };
@@ -949,9 +4191,24 @@ async fn delete_rating_route(
     };
 
     let mut conn = db_util::get_connection(&pool).await.unwrap();
+
+    // NOTE: Members can delete their own ratings, admins can delete anyone's
+    let permission = match user_id == user_claims.id {
+        true => Permission::Contribute,
+        false => Permission::Moderate,
+    };
+    if let Err(err) = auth::authorize(&mut conn, &user_claims, group_id, permission).await {
+        return err;
+    }
+
     let result = db_util::delete_rating(&mut conn, rating_id, &user_id, group_id).await;
     match result {
-        Ok(rows) => HttpResponse::Ok().json(ApiResponse::success(rows.last_insert_id())),
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Rating not found".to_string()))
+        }
+        Ok(query_result) => {
+            HttpResponse::Ok().json(ApiResponse::success(query_result.rows_affected()))
+        }
         Err(error) => {
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(error.to_string()))
         }
@@ -0,0 +1,168 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+pub const TOTP_ISSUER: &str = "Ratings";
+const TOTP_PERIOD_SECONDS: i64 = 30;
+const TOTP_DIGITS: u32 = 6;
+// NOTE: Codes from one period before and after are accepted too, to make up for clock drift
+const TOTP_SKEW_STEPS: i64 = 1;
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// A random 160 bit secret, base32 encoded the way authenticator apps expect it.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+/// The `otpauth://` URI authenticator apps scan from a QR code.
+pub fn provisioning_uri(secret: &str, username: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{username}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={TOTP_DIGITS}&period={TOTP_PERIOD_SECONDS}",
+        issuer = urlencoding::encode(TOTP_ISSUER),
+        username = urlencoding::encode(username),
+    )
+}
+
+/// Checks a code against the secret at the given unix time and returns the time step it belongs
+/// to. Steps up to `last_used_step` are rejected, so a code can't be replayed.
+pub fn verify_code(secret: &str, code: &str, now: i64, last_used_step: Option<i64>) -> Option<i64> {
+    let key = base32_decode(secret)?;
+    let code = code.trim().replace(' ', "");
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current_step = now / TOTP_PERIOD_SECONDS;
+    (current_step - TOTP_SKEW_STEPS..=current_step + TOTP_SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last_used_step| *step > last_used_step))
+        .find(|step| format_code(code_at(&key, *step)) == code)
+}
+
+/// The code an authenticator app shows at the given unix time.
+pub fn generate_code(secret: &str, now: i64) -> Option<String> {
+    let key = base32_decode(secret)?;
+    Some(format_code(code_at(&key, now / TOTP_PERIOD_SECONDS)))
+}
+
+fn format_code(code: u32) -> String {
+    format!("{code:0width$}", width = TOTP_DIGITS as usize)
+}
+
+fn code_at(key: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&(step as u64).to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    binary % 10u32.pow(TOTP_DIGITS)
+}
+
+/// One-time codes for signing in without the authenticator, formatted like `a1b2c-3d4e5`.
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 5];
+            OsRng.fill_bytes(&mut bytes);
+            let code = hex::encode(bytes);
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect()
+}
+
+/// Recovery codes are compared case-insensitively and without their separator.
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.trim().replace('-', "").to_lowercase()
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    encoded
+}
+
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in encoded.trim_end_matches('=').chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NOTE: The SHA1 test vectors from RFC 6238, truncated to 6 digits
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_rfc_6238_codes() {
+        for (time, code) in [
+            (59, 287082),
+            (1111111109, 81804),
+            (1111111111, 50471),
+            (1234567890, 5924),
+            (2000000000, 279037),
+        ] {
+            assert_eq!(code_at(RFC_SECRET, time / TOTP_PERIOD_SECONDS), code);
+        }
+    }
+
+    #[test]
+    fn test_verify_code() {
+        let secret = base32_encode(RFC_SECRET);
+        assert_eq!(base32_decode(&secret).unwrap(), RFC_SECRET);
+
+        let step = 1111111109 / TOTP_PERIOD_SECONDS;
+        assert_eq!(verify_code(&secret, "081804", 1111111109, None), Some(step));
+        assert_eq!(
+            verify_code(&secret, "081 804", 1111111139, None),
+            Some(step)
+        );
+        assert_eq!(verify_code(&secret, "081804", 1111111109, Some(step)), None);
+        assert_eq!(verify_code(&secret, "081804", 1111111209, None), None);
+        assert_eq!(verify_code(&secret, "81804", 1111111109, None), None);
+        assert_eq!(
+            generate_code(&secret, 1111111109).as_deref(),
+            Some("081804")
+        );
+    }
+}